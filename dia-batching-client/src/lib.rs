@@ -0,0 +1,218 @@
+//! Typed client for the DIA batching server.
+//!
+//! The server exposes `POST /currencies`, taking a JSON list of `{ blockchain, symbol }` pairs and
+//! answering with the cached [`CoinInfo`] of every pair it knows about. [`Client`] wraps that
+//! contract with per-request timeouts and a bounded number of retries.
+use log::warn;
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+use std::{
+	error,
+	fmt::{Display, Formatter},
+	time::Duration,
+};
+
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:8070/currencies";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Currency {
+	pub blockchain: String,
+	pub symbol: String,
+}
+
+impl Currency {
+	pub fn new(blockchain: impl Into<String>, symbol: impl Into<String>) -> Self {
+		Currency { blockchain: blockchain.into(), symbol: symbol.into() }
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinInfo {
+	pub symbol: SmolStr,
+	pub name: SmolStr,
+	pub blockchain: SmolStr,
+	pub supply: u128,
+	pub last_update_timestamp: u64,
+	pub price: u128,
+	/// Zero when talking to a server that does not send it yet
+	#[serde(default)]
+	pub price_yesterday: u128,
+	#[serde(default)]
+	pub volume_yesterday_usd: u128,
+	#[serde(default)]
+	pub market_cap: Option<u128>,
+	#[serde(default)]
+	pub high_24h: Option<u128>,
+	#[serde(default)]
+	pub low_24h: Option<u128>,
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+	Request(reqwest::Error),
+	Status(reqwest::StatusCode),
+}
+
+impl Display for ClientError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ClientError::Request(err) => write!(f, "Request to batching server failed: {}", err),
+			ClientError::Status(status) => {
+				write!(f, "Batching server responded with status {}", status)
+			},
+		}
+	}
+}
+
+impl error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+	fn from(err: reqwest::Error) -> Self {
+		ClientError::Request(err)
+	}
+}
+
+pub struct ClientBuilder {
+	endpoint: String,
+	timeout: Duration,
+	retries: u32,
+	retry_delay: Duration,
+}
+
+impl Default for ClientBuilder {
+	fn default() -> Self {
+		Self {
+			endpoint: DEFAULT_ENDPOINT.into(),
+			timeout: Duration::from_secs(5),
+			retries: 3,
+			retry_delay: Duration::from_millis(500),
+		}
+	}
+}
+
+impl ClientBuilder {
+	/// Full URL of the `/currencies` route
+	pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+		self.endpoint = endpoint.into();
+		self
+	}
+
+	/// Timeout applied to every single request
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
+	/// Number of retries after the first failed attempt
+	pub fn retries(mut self, retries: u32) -> Self {
+		self.retries = retries;
+		self
+	}
+
+	/// Base delay between retries, multiplied by the attempt number
+	pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+		self.retry_delay = retry_delay;
+		self
+	}
+
+	pub fn build(self) -> Result<Client, ClientError> {
+		let http = reqwest::Client::builder().timeout(self.timeout).build()?;
+		Ok(Client {
+			http,
+			endpoint: self.endpoint,
+			retries: self.retries,
+			retry_delay: self.retry_delay,
+		})
+	}
+}
+
+pub struct Client {
+	http: reqwest::Client,
+	endpoint: String,
+	retries: u32,
+	retry_delay: Duration,
+}
+
+impl Client {
+	pub fn new(endpoint: impl Into<String>) -> Result<Self, ClientError> {
+		ClientBuilder::default().endpoint(endpoint).build()
+	}
+
+	pub fn builder() -> ClientBuilder {
+		ClientBuilder::default()
+	}
+
+	pub fn endpoint(&self) -> &str {
+		&self.endpoint
+	}
+
+	/// Returns the coin infos the server has for the given currencies. Unknown currencies are
+	/// omitted by the server, so the result may be shorter than the input.
+	pub async fn get_coin_infos(
+		&self,
+		currencies: &[Currency],
+	) -> Result<Vec<CoinInfo>, ClientError> {
+		let mut attempt = 0;
+		loop {
+			match self.try_get_coin_infos(currencies).await {
+				Ok(coin_infos) => return Ok(coin_infos),
+				Err(err) if attempt < self.retries => {
+					attempt += 1;
+					warn!("Attempt {} against {} failed: {}", attempt, self.endpoint, err);
+					tokio::time::delay_for(self.retry_delay * attempt).await;
+				},
+				Err(err) => return Err(err),
+			}
+		}
+	}
+
+	async fn try_get_coin_infos(
+		&self,
+		currencies: &[Currency],
+	) -> Result<Vec<CoinInfo>, ClientError> {
+		let response = self.http.post(&self.endpoint).json(currencies).send().await?;
+		if !response.status().is_success() {
+			return Err(ClientError::Status(response.status()))
+		}
+		Ok(response.json().await?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_coin_info_matches_server_format() {
+		let body = r#"[{"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":123456789012,"lastUpdateTimestamp":1671888839,"price":16826489316709616000}]"#;
+		let c: Vec<CoinInfo> = serde_json::from_str(body).unwrap();
+
+		assert_eq!(c.len(), 1);
+		assert_eq!(c[0].symbol, "BTC");
+		assert_eq!(c[0].last_update_timestamp, 1671888839);
+		assert_eq!(c[0].price, 16826489316709616000);
+	}
+
+	#[test]
+	fn test_currency_serializes_like_server_request() {
+		let body = serde_json::to_string(&vec![Currency::new("Bitcoin", "BTC")]).unwrap();
+
+		assert_eq!(body, r#"[{"blockchain":"Bitcoin","symbol":"BTC"}]"#);
+	}
+
+	#[tokio::test]
+	async fn test_get_coin_infos_gives_up_after_retries() {
+		let client = Client::builder()
+			.endpoint("http://127.0.0.1:1/currencies")
+			.timeout(Duration::from_millis(100))
+			.retries(2)
+			.retry_delay(Duration::from_millis(1))
+			.build()
+			.unwrap();
+
+		let r = client.get_coin_infos(&[Currency::new("Bitcoin", "BTC")]).await;
+
+		assert!(matches!(r, Err(ClientError::Request(_))));
+	}
+}
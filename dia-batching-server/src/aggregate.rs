@@ -0,0 +1,377 @@
+use crate::dia::{Quotation, QuotedAsset};
+use crate::source::{PriceSource, Source};
+use crate::AssetSpecifier;
+use async_trait::async_trait;
+use log::error;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// How the prices of an asset quoted by several sources are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Aggregation {
+	Median,
+	/// Mean weighted by the 24h volume of each source, the median if no source reports volume
+	VolumeWeightedMean,
+}
+
+impl Default for Aggregation {
+	fn default() -> Self {
+		Aggregation::Median
+	}
+}
+
+impl FromStr for Aggregation {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"median" => Ok(Aggregation::Median),
+			"volume-weighted-mean" => Ok(Aggregation::VolumeWeightedMean),
+			_ => Err(format!("Unknown aggregation '{}', use median or volume-weighted-mean", s)),
+		}
+	}
+}
+
+/// Names of the sources to quote a routed asset from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route {
+	/// Combines the prices of all the sources, written `<source>+<source>...`
+	All(Vec<String>),
+	/// Takes the price of the first source that quotes the asset, written `<source>|<source>...`
+	Fallback(Vec<String>),
+}
+
+impl Route {
+	pub fn parse(route: &str) -> Self {
+		let names =
+			|separator: char| route.split(separator).map(|name| name.trim().to_string()).collect();
+		if route.contains('|') {
+			Route::Fallback(names('|'))
+		} else {
+			Route::All(names('+'))
+		}
+	}
+
+	pub fn names(&self) -> &[String] {
+		match self {
+			Route::All(names) | Route::Fallback(names) => names,
+		}
+	}
+
+	/// Position of the source in the route, `None` if it is not part of it
+	fn position(&self, source: &Source) -> Option<usize> {
+		self.names().iter().position(|name| name == source.name())
+	}
+}
+
+/// Quotes every asset from the sources it is routed to and combines their prices. Assets without
+/// a route are quoted from every source quoting them.
+pub struct Aggregate {
+	sources: Vec<Source>,
+	routes: HashMap<AssetSpecifier, Route>,
+	aggregation: Aggregation,
+	/// Maximum deviation of a source from the median, as a fraction of it
+	max_deviation: Option<Decimal>,
+	/// The assets of every source quoting them, as of the last `get_quotable_assets`
+	quoted_assets: RwLock<HashMap<AssetSpecifier, Vec<(usize, QuotedAsset)>>>,
+}
+
+impl Aggregate {
+	pub fn new(
+		sources: Vec<Source>,
+		routes: HashMap<AssetSpecifier, Route>,
+		aggregation: Aggregation,
+		max_deviation_percent: Option<Decimal>,
+	) -> Self {
+		Aggregate {
+			sources,
+			routes,
+			aggregation,
+			max_deviation: max_deviation_percent.map(|percent| percent / Decimal::new(100, 0)),
+			quoted_assets: Default::default(),
+		}
+	}
+
+	fn is_routed(&self, asset: &AssetSpecifier, source: &Source) -> bool {
+		self.routes.get(asset).map_or(true, |route| route.position(source).is_some())
+	}
+}
+
+fn asset_specifier(quoted_asset: &QuotedAsset) -> AssetSpecifier {
+	AssetSpecifier {
+		blockchain: quoted_asset.asset.blockchain.clone(),
+		symbol: quoted_asset.asset.symbol.clone(),
+	}
+}
+
+fn median(mut prices: Vec<Decimal>) -> Decimal {
+	prices.sort();
+	let middle = prices.len() / 2;
+	if prices.len() % 2 == 0 {
+		(prices[middle - 1] + prices[middle]) / Decimal::new(2, 0)
+	} else {
+		prices[middle]
+	}
+}
+
+/// Combines the quotations of the sources into the one of the first source left after dropping
+/// the ones deviating too far from the median, with the aggregated price
+fn combine(
+	quotations: Vec<Quotation>,
+	aggregation: Aggregation,
+	max_deviation: Option<Decimal>,
+) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+	if quotations.is_empty() {
+		return Err("No source quoted the asset".into())
+	}
+	let median_price = median(quotations.iter().map(|quotation| quotation.price).collect());
+	let accepted: Vec<Quotation> = match max_deviation {
+		Some(max_deviation) if !median_price.is_zero() => quotations
+			.into_iter()
+			.filter(|quotation| {
+				(quotation.price - median_price).abs() / median_price <= max_deviation
+			})
+			.collect(),
+		_ => quotations,
+	};
+	if accepted.is_empty() {
+		return Err("Every source deviates too far from the median".into())
+	}
+
+	let median_price = median(accepted.iter().map(|quotation| quotation.price).collect());
+	let price = match aggregation {
+		Aggregation::Median => median_price,
+		Aggregation::VolumeWeightedMean => {
+			let total_volume = accepted
+				.iter()
+				.try_fold(Decimal::ZERO, |total, q| total.checked_add(q.volume_yesterday));
+			let weighted_sum = accepted.iter().try_fold(Decimal::ZERO, |sum, q| {
+				sum.checked_add(q.price.checked_mul(q.volume_yesterday)?)
+			});
+			match (weighted_sum, total_volume) {
+				(Some(weighted_sum), Some(total_volume)) if !total_volume.is_zero() =>
+					weighted_sum / total_volume,
+				_ => median_price,
+			}
+		},
+	};
+
+	let sources = accepted.iter().map(|q| q.source.as_str()).collect::<Vec<_>>().join(",");
+	let mut quotation = accepted.into_iter().next().expect("accepted is not empty; qed");
+	quotation.price = price;
+	quotation.source = sources;
+	Ok(quotation)
+}
+
+#[async_trait]
+impl PriceSource for Aggregate {
+	fn name(&self) -> &str {
+		"aggregate"
+	}
+
+	/// The assets quoted by any source they are routed to, each once. Fails only if every source
+	/// fails.
+	async fn get_quotable_assets(
+		&self,
+	) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Send + Sync>> {
+		let mut quoted_assets: HashMap<AssetSpecifier, Vec<(usize, QuotedAsset)>> = HashMap::new();
+		let mut last_error = None;
+		for (index, source) in self.sources.iter().enumerate() {
+			match source.get_quotable_assets().await {
+				Ok(assets) =>
+					for quoted_asset in assets {
+						let asset = asset_specifier(&quoted_asset);
+						if self.is_routed(&asset, source) {
+							quoted_assets.entry(asset).or_default().push((index, quoted_asset));
+						}
+					},
+				Err(err) => {
+					error!("Error while retrieving quotable assets: {}", err);
+					last_error = Some(err);
+				},
+			}
+		}
+		if let (true, Some(err)) = (quoted_assets.is_empty(), last_error) {
+			return Err(err)
+		}
+		for (asset, quoted) in quoted_assets.iter_mut() {
+			if let Some(route @ Route::Fallback(_)) = self.routes.get(asset) {
+				quoted.sort_by_key(|(index, _)| route.position(&self.sources[*index]));
+			}
+		}
+
+		let assets = quoted_assets.values().map(|quoted| quoted[0].1.clone()).collect();
+		*self.quoted_assets.write().unwrap_or_else(|e| e.into_inner()) = quoted_assets;
+		Ok(assets)
+	}
+
+	async fn get_quotation(
+		&self,
+		quoted_asset: &QuotedAsset,
+	) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+		let asset = asset_specifier(quoted_asset);
+		let quoted = self
+			.quoted_assets
+			.read()
+			.unwrap_or_else(|e| e.into_inner())
+			.get(&asset)
+			.cloned()
+			.ok_or_else(|| format!("No source quotes {:?}", asset))?;
+		if let [(index, quoted_asset)] = quoted.as_slice() {
+			return self.sources[*index].get_quotation(quoted_asset).await
+		}
+		if let Some(Route::Fallback(_)) = self.routes.get(&asset) {
+			let mut last_error = None;
+			for (index, quoted_asset) in &quoted {
+				match self.sources[*index].get_quotation(quoted_asset).await {
+					Ok(quotation) => return Ok(quotation),
+					Err(err) => {
+						error!("Error while retrieving quotation for {:?}: {}", asset, err);
+						last_error = Some(err);
+					},
+				}
+			}
+			return Err(last_error.unwrap_or_else(|| "No source quoted the asset".into()))
+		}
+
+		let mut quotations = vec![];
+		for (index, quoted_asset) in &quoted {
+			match self.sources[*index].get_quotation(quoted_asset).await {
+				Ok(quotation) => quotations.push(quotation),
+				Err(err) => error!("Error while retrieving quotation for {:?}: {}", asset, err),
+			}
+		}
+		combine(quotations, self.aggregation, self.max_deviation)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::dia::Asset;
+	use rust_decimal_macros::dec;
+
+	fn quotation(source: &str, price: Decimal, volume: Decimal) -> Quotation {
+		Quotation {
+			price,
+			volume_yesterday: volume,
+			source: source.into(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn combine_should_take_the_median_without_outliers() {
+		let quotations = vec![
+			quotation("diadata.org", dec!(100), dec!(1)),
+			quotation("coingecko.com", dec!(102), dec!(1)),
+			quotation("binance.com", dec!(150), dec!(1)),
+		];
+
+		let combined = combine(quotations.clone(), Aggregation::Median, None).unwrap();
+		assert_eq!(combined.price, dec!(102));
+		assert_eq!(combined.source, "diadata.org,coingecko.com,binance.com");
+
+		let combined = combine(quotations, Aggregation::Median, Some(dec!(0.1))).unwrap();
+		assert_eq!(combined.price, dec!(101));
+		assert_eq!(combined.source, "diadata.org,coingecko.com");
+	}
+
+	#[test]
+	fn combine_should_weight_by_volume() {
+		let quotations = vec![
+			quotation("diadata.org", dec!(100), dec!(3)),
+			quotation("binance.com", dec!(104), dec!(1)),
+		];
+
+		let combined = combine(quotations, Aggregation::VolumeWeightedMean, None).unwrap();
+		assert_eq!(combined.price, dec!(101));
+
+		let quotations = vec![
+			quotation("diadata.org", dec!(100), dec!(0)),
+			quotation("binance.com", dec!(104), dec!(0)),
+		];
+		let combined = combine(quotations, Aggregation::VolumeWeightedMean, None).unwrap();
+		assert_eq!(combined.price, dec!(102));
+	}
+
+	/// Source quoting BTC at a fixed price, failing without one
+	struct Fixed(&'static str, Option<Decimal>);
+
+	#[async_trait]
+	impl PriceSource for Fixed {
+		fn name(&self) -> &str {
+			self.0
+		}
+
+		async fn get_quotable_assets(
+			&self,
+		) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Send + Sync>> {
+			let asset = Asset {
+				symbol: "BTC".into(),
+				name: "Bitcoin".into(),
+				address: self.0.into(),
+				decimals: 8,
+				blockchain: "Bitcoin".into(),
+			};
+			Ok(vec![QuotedAsset { asset, volume: Decimal::ZERO }])
+		}
+
+		async fn get_quotation(
+			&self,
+			_: &QuotedAsset,
+		) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+			let price = self.1.ok_or("unavailable")?;
+			Ok(quotation(self.0, price, Decimal::ONE))
+		}
+	}
+
+	#[tokio::test]
+	async fn get_quotation_should_follow_the_routes() {
+		let btc = AssetSpecifier { blockchain: "Bitcoin".into(), symbol: "BTC".into() };
+		let sources: Vec<Source> = vec![
+			Box::new(Fixed("dia", Some(dec!(100)))),
+			Box::new(Fixed("coingecko", Some(dec!(102)))),
+			Box::new(Fixed("binance", Some(dec!(110)))),
+		];
+		let routes = vec![(btc, Route::parse("dia+binance"))];
+		let aggregate =
+			Aggregate::new(sources, routes.into_iter().collect(), Aggregation::Median, None);
+
+		let quoted_assets = aggregate.get_quotable_assets().await.unwrap();
+		assert_eq!(quoted_assets.len(), 1);
+
+		let quotation = aggregate.get_quotation(&quoted_assets[0]).await.unwrap();
+		assert_eq!(quotation.price, dec!(105));
+		assert_eq!(quotation.source, "dia,binance");
+	}
+
+	#[tokio::test]
+	async fn get_quotation_should_fall_back_in_route_order() {
+		let btc = AssetSpecifier { blockchain: "Bitcoin".into(), symbol: "BTC".into() };
+		let sources: Vec<Source> = vec![
+			Box::new(Fixed("fiat", Some(dec!(101)))),
+			Box::new(Fixed("binance", Some(dec!(110)))),
+			Box::new(Fixed("dia", None)),
+		];
+		let routes = vec![(btc, Route::parse("dia | fiat | binance"))];
+		let aggregate =
+			Aggregate::new(sources, routes.into_iter().collect(), Aggregation::Median, None);
+
+		let quoted_assets = aggregate.get_quotable_assets().await.unwrap();
+		let quotation = aggregate.get_quotation(&quoted_assets[0]).await.unwrap();
+
+		assert_eq!(quotation.price, dec!(101));
+		assert_eq!(quotation.source, "fiat");
+	}
+
+	#[test]
+	fn combine_should_fail_without_quotations() {
+		assert!(combine(vec![], Aggregation::Median, None).is_err());
+	}
+}
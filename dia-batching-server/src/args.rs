@@ -1,18 +1,471 @@
+use crate::aggregate::Aggregation;
+use crate::dex::DexPool;
+use crate::redemption::RedemptionRate;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::error::Error;
+use std::net::IpAddr;
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "dia-batching-server", about = "An server for batching requests to the Dia API")]
 pub struct DiaApiArgs {
-	/// Iteration duration after one batch of requests
-	#[structopt(short, long, default_value = "60")]
-	pub iteration_timeout_in_seconds: u64,
+	/// TOML file with any of the options below, in kebab-case
+	/// Options given on the command line or through DIA_* variables take precedence over the file
+	#[structopt(long, env = "DIA_CONFIG", parse(from_os_str))]
+	pub config: Option<PathBuf>,
 
-	/// Timeout after one request
-	#[structopt(short, long, default_value = "100")]
-	pub request_timeout_in_milliseconds: u64,
+	/// Address the HTTP server listens on, e.g. 127.0.0.1 behind a reverse proxy [default: 0.0.0.0]
+	#[structopt(long, env = "DIA_LISTEN_ADDRESS")]
+	pub listen_address: Option<String>,
+
+	/// Port the HTTP server listens on [default: 8070]
+	#[structopt(long, env = "DIA_PORT")]
+	pub port: Option<u16>,
+
+	/// Iteration duration after one batch of requests [default: 60]
+	#[structopt(short, long, env = "DIA_ITERATION_TIMEOUT")]
+	pub iteration_timeout_in_seconds: Option<u64>,
+
+	/// Timeout after one request [default: 100]
+	#[structopt(short, long, env = "DIA_REQUEST_TIMEOUT")]
+	pub request_timeout_in_milliseconds: Option<u64>,
+
+	/// Retries of a failed request to the DIA API, with exponential backoff [default: 3]
+	#[structopt(long, env = "DIA_MAX_RETRIES")]
+	pub max_retries: Option<u32>,
+
+	/// Time after which a request to a price source or node is abandoned [default: 10]
+	#[structopt(long, env = "DIA_HTTP_TIMEOUT")]
+	pub http_timeout_in_seconds: Option<u64>,
 
 	/// Currencies to support
 	/// Each currency needs to have the format <blockchain>:<symbol>
-	#[structopt(short, long, default_value = "Vec::default()")]
+	/// DIA_SUPPORTED_CURRENCIES separates them with commas
+	#[structopt(short, long, env = "DIA_SUPPORTED_CURRENCIES", use_delimiter = true)]
+	pub supported_currencies: Option<Vec<String>>,
+
+	/// File with a currency to support per line, instead of --supported-currencies. It is read
+	/// again on SIGHUP, so that currencies can be added without a restart.
+	#[structopt(long, env = "DIA_SUPPORTED_CURRENCIES_FILE", parse(from_os_str))]
+	pub supported_currencies_file: Option<PathBuf>,
+
+	/// RPC endpoint of a node to read the pallet's supported currencies from, replacing
+	/// --supported-currencies. It cannot be combined with --supported-currencies-file.
+	#[structopt(long, env = "DIA_CHAIN_RPC_URL")]
+	pub chain_rpc_url: Option<String>,
+
+	/// Interval at which the supported currencies are read from the chain again [default: 300]
+	#[structopt(long, env = "DIA_CHAIN_SYNC_INTERVAL")]
+	pub chain_sync_interval_in_seconds: Option<u64>,
+
+	/// Assets to fetch from CoinGecko instead of DIA, each as <blockchain>:<symbol>=<CoinGecko id>
+	/// DIA_COINGECKO_IDS separates them with commas
+	#[structopt(long, env = "DIA_COINGECKO_IDS", use_delimiter = true)]
+	pub coingecko_ids: Option<Vec<String>>,
+
+	/// Assets to fetch from the Binance spot market, each as <blockchain>:<symbol>=<Binance pair>,
+	/// e.g. Bitcoin:BTC=BTCUSDT
+	/// DIA_BINANCE_SYMBOLS separates them with commas
+	#[structopt(long, env = "DIA_BINANCE_SYMBOLS", use_delimiter = true)]
+	pub binance_symbols: Option<Vec<String>>,
+
+	/// Fiat pairs to quote from the ECB reference rates, each as Fiat:<base>-<quote>
+	/// DIA_FIAT_PAIRS separates them with commas
+	#[structopt(long, env = "DIA_FIAT_PAIRS", use_delimiter = true)]
+	pub fiat_pairs: Option<Vec<String>>,
+
+	/// Sources to quote an asset from, each route as <blockchain>:<symbol>=<source>+<source>...
+	/// to combine their prices or <blockchain>:<symbol>=<source>|<source>... to fall back to the
+	/// next source if one fails, with the sources dia, coingecko, binance, fiat, dex and
+	/// redemption. Assets without a route are quoted from every source quoting them
+	/// DIA_ROUTES separates them with commas
+	#[structopt(long, env = "DIA_ROUTES", use_delimiter = true)]
+	pub routes: Option<Vec<String>>,
+
+	/// How the prices of an asset quoted by several sources are combined, median or
+	/// volume-weighted-mean [default: median]
+	#[structopt(long, env = "DIA_AGGREGATION")]
+	pub aggregation: Option<Aggregation>,
+
+	/// Maximum deviation of a source from the median price in percent, sources deviating further
+	/// are left out of the aggregated price
+	#[structopt(long, env = "DIA_MAX_SOURCE_DEVIATION")]
+	pub max_source_deviation_percent: Option<Decimal>,
+
+	/// Serve quotations with a price of zero instead of dropping them
+	/// Also enabled by DIA_ALLOW_ZERO_PRICES=true
+	#[structopt(long)]
+	pub allow_zero_prices: bool,
+
+	/// Maximum age of the last price update for /ready to report the server as ready [default: 300]
+	#[structopt(long, env = "DIA_MAX_UPDATE_AGE")]
+	pub max_update_age_in_seconds: Option<u64>,
+
+	/// Maximum time since a price was last fetched for it to be served, unlimited if not set
+	#[structopt(long, env = "DIA_MAX_PRICE_AGE")]
+	pub max_price_age_in_seconds: Option<u64>,
+
+	/// File the price cache is saved to after every update and loaded from on startup, so that a
+	/// restarted server serves the last known prices right away
+	#[structopt(long, env = "DIA_CACHE_FILE", parse(from_os_str))]
+	pub cache_file: Option<PathBuf>,
+
+	/// Hex encoded sr25519 seed to sign responses with
+	/// The matching public key needs to be set as batching server key on chain
+	#[structopt(long, env = "DIA_SIGNING_SEED", hide_env_values = true)]
+	pub signing_seed: Option<String>,
+
+	/// Keys clients have to send as `Authorization: Bearer <key>` or `x-api-key` to query coin
+	/// infos, the endpoints are open without any
+	/// DIA_API_KEYS separates them with commas
+	#[structopt(long, env = "DIA_API_KEYS", use_delimiter = true, hide_env_values = true)]
+	pub api_keys: Option<Vec<String>>,
+
+	/// Requests per minute each client IP may send to /currencies, unlimited if not set
+	#[structopt(long, env = "DIA_RATE_LIMIT_PER_MINUTE")]
+	pub rate_limit_per_minute: Option<u32>,
+
+	/// Requests a client may send at once before being limited [default: --rate-limit-per-minute]
+	#[structopt(long, env = "DIA_RATE_LIMIT_BURST")]
+	pub rate_limit_burst: Option<u32>,
+
+	/// Addresses of reverse proxies in front of the server. Requests through them are rate limited
+	/// by the client address they add to `X-Forwarded-For` instead of their own.
+	/// DIA_TRUSTED_PROXIES separates them with commas
+	#[structopt(long, env = "DIA_TRUSTED_PROXIES", use_delimiter = true)]
+	pub trusted_proxies: Option<Vec<IpAddr>>,
+
+	/// Origins browsers may query the server from, `*` allowing any
+	/// DIA_CORS_ALLOWED_ORIGINS separates them with commas
+	#[structopt(long, env = "DIA_CORS_ALLOWED_ORIGINS", use_delimiter = true)]
+	pub cors_allowed_origins: Option<Vec<String>>,
+
+	/// PEM certificate chain to serve HTTPS with, requires --tls-key
+	#[structopt(long, env = "DIA_TLS_CERT", parse(from_os_str))]
+	pub tls_cert: Option<PathBuf>,
+
+	/// PEM private key of the certificate, requires --tls-cert
+	#[structopt(long, env = "DIA_TLS_KEY", parse(from_os_str))]
+	pub tls_key: Option<PathBuf>,
+}
+
+/// Options read from the `--config` file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ConfigFile {
+	pub listen_address: Option<String>,
+	pub port: Option<u16>,
+	pub iteration_timeout_in_seconds: Option<u64>,
+	pub request_timeout_in_milliseconds: Option<u64>,
+	pub max_retries: Option<u32>,
+	pub http_timeout_in_seconds: Option<u64>,
 	pub supported_currencies: Option<Vec<String>>,
+	pub supported_currencies_file: Option<PathBuf>,
+	pub chain_rpc_url: Option<String>,
+	pub chain_sync_interval_in_seconds: Option<u64>,
+	pub coingecko_ids: Option<Vec<String>>,
+	pub binance_symbols: Option<Vec<String>>,
+	pub fiat_pairs: Option<Vec<String>>,
+	pub routes: Option<Vec<String>>,
+	/// DEX pools to price assets from, only settable in the config file
+	pub dex_pools: Vec<DexPool>,
+	/// Redemption rates to price liquid-staking derivatives from, only settable in the config file
+	pub redemption_rates: Vec<RedemptionRate>,
+	pub aggregation: Option<Aggregation>,
+	pub max_source_deviation_percent: Option<Decimal>,
+	pub allow_zero_prices: Option<bool>,
+	pub max_update_age_in_seconds: Option<u64>,
+	pub max_price_age_in_seconds: Option<u64>,
+	pub cache_file: Option<PathBuf>,
+	pub signing_seed: Option<String>,
+	pub api_keys: Option<Vec<String>>,
+	pub rate_limit_per_minute: Option<u32>,
+	pub rate_limit_burst: Option<u32>,
+	pub trusted_proxies: Option<Vec<IpAddr>>,
+	pub cors_allowed_origins: Option<Vec<String>>,
+	pub tls_cert: Option<PathBuf>,
+	pub tls_key: Option<PathBuf>,
+}
+
+/// Options the server runs with, from the command line, the config file or the defaults
+#[derive(Debug, PartialEq, Eq)]
+pub struct Settings {
+	pub listen_address: String,
+	pub port: u16,
+	pub iteration_timeout_in_seconds: u64,
+	pub request_timeout_in_milliseconds: u64,
+	pub max_retries: u32,
+	pub http_timeout_in_seconds: u64,
+	pub supported_currencies: Option<Vec<String>>,
+	/// File the supported currencies are read from, taking precedence over them
+	pub supported_currencies_file: Option<PathBuf>,
+	/// Node RPC endpoint the supported currencies are synced from, taking precedence over both
+	pub chain_rpc_url: Option<String>,
+	pub chain_sync_interval_in_seconds: u64,
+	/// CoinGecko ids of the assets fetched from CoinGecko, as <blockchain>:<symbol>=<id>
+	pub coingecko_ids: Vec<String>,
+	/// Binance pairs of the assets fetched from Binance, as <blockchain>:<symbol>=<pair>
+	pub binance_symbols: Vec<String>,
+	/// Fiat pairs quoted from the ECB reference rates, as Fiat:<base>-<quote>
+	pub fiat_pairs: Vec<String>,
+	/// Sources of the routed assets, as <blockchain>:<symbol>=<source>+<source>... or with `|`
+	pub routes: Vec<String>,
+	pub dex_pools: Vec<DexPool>,
+	pub redemption_rates: Vec<RedemptionRate>,
+	pub aggregation: Aggregation,
+	pub max_source_deviation_percent: Option<Decimal>,
+	pub allow_zero_prices: bool,
+	pub max_update_age_in_seconds: u64,
+	/// Maximum age of a served price, prices fetched longer ago are left out
+	pub max_price_age_in_seconds: Option<u64>,
+	/// File the price cache is persisted to, not persisted if not set
+	pub cache_file: Option<PathBuf>,
+	pub signing_seed: Option<String>,
+	/// Keys accepted on the coin info endpoints, open if empty
+	pub api_keys: Vec<String>,
+	/// Requests per minute and burst allowed per client IP, unlimited if not set
+	pub rate_limit: Option<(u32, u32)>,
+	/// Proxies whose `X-Forwarded-For` header names the client to rate limit
+	pub trusted_proxies: Vec<IpAddr>,
+	/// Origins allowed to make cross-origin requests, none if empty
+	pub cors_allowed_origins: Vec<String>,
+	/// Certificate chain and private key, serving HTTPS instead of HTTP
+	pub tls: Option<(PathBuf, PathBuf)>,
+}
+
+impl DiaApiArgs {
+	/// Reads the config file, if any, and merges it with the command line options
+	pub fn settings(self) -> Result<Settings, Box<dyn Error + Send + Sync>> {
+		let file = match &self.config {
+			Some(path) => toml::from_str(&std::fs::read_to_string(path)?)?,
+			None => ConfigFile::default(),
+		};
+		self.merge(file)
+	}
+
+	fn merge(self, file: ConfigFile) -> Result<Settings, Box<dyn Error + Send + Sync>> {
+		let tls = match (self.tls_cert.or(file.tls_cert), self.tls_key.or(file.tls_key)) {
+			(Some(cert), Some(key)) => Some((cert, key)),
+			(None, None) => None,
+			_ => return Err("TLS needs both a certificate and a private key".into()),
+		};
+
+		let supported_currencies_file =
+			self.supported_currencies_file.or(file.supported_currencies_file);
+		let chain_rpc_url = self.chain_rpc_url.or(file.chain_rpc_url);
+		if supported_currencies_file.is_some() && chain_rpc_url.is_some() {
+			return Err("The supported currencies come either from a file or from the chain".into())
+		}
+
+		let rate_limit_burst = self.rate_limit_burst.or(file.rate_limit_burst);
+		let rate_limit = self
+			.rate_limit_per_minute
+			.or(file.rate_limit_per_minute)
+			.map(|per_minute| (per_minute, rate_limit_burst.unwrap_or(per_minute)));
+
+		Ok(Settings {
+			listen_address: self
+				.listen_address
+				.or(file.listen_address)
+				.unwrap_or_else(|| "0.0.0.0".into()),
+			port: self.port.or(file.port).unwrap_or(8070),
+			iteration_timeout_in_seconds: self
+				.iteration_timeout_in_seconds
+				.or(file.iteration_timeout_in_seconds)
+				.unwrap_or(60),
+			request_timeout_in_milliseconds: self
+				.request_timeout_in_milliseconds
+				.or(file.request_timeout_in_milliseconds)
+				.unwrap_or(100),
+			max_retries: self.max_retries.or(file.max_retries).unwrap_or(3),
+			http_timeout_in_seconds: self
+				.http_timeout_in_seconds
+				.or(file.http_timeout_in_seconds)
+				.unwrap_or(10),
+			supported_currencies: self.supported_currencies.or(file.supported_currencies),
+			supported_currencies_file,
+			chain_rpc_url,
+			chain_sync_interval_in_seconds: self
+				.chain_sync_interval_in_seconds
+				.or(file.chain_sync_interval_in_seconds)
+				.unwrap_or(300),
+			coingecko_ids: self.coingecko_ids.or(file.coingecko_ids).unwrap_or_default(),
+			binance_symbols: self.binance_symbols.or(file.binance_symbols).unwrap_or_default(),
+			fiat_pairs: self.fiat_pairs.or(file.fiat_pairs).unwrap_or_default(),
+			routes: self.routes.or(file.routes).unwrap_or_default(),
+			dex_pools: file.dex_pools,
+			redemption_rates: file.redemption_rates,
+			aggregation: self.aggregation.or(file.aggregation).unwrap_or_default(),
+			max_source_deviation_percent: self
+				.max_source_deviation_percent
+				.or(file.max_source_deviation_percent),
+			allow_zero_prices: self.allow_zero_prices ||
+				flag_from_env("DIA_ALLOW_ZERO_PRICES") ||
+				file.allow_zero_prices.unwrap_or(false),
+			max_update_age_in_seconds: self
+				.max_update_age_in_seconds
+				.or(file.max_update_age_in_seconds)
+				.unwrap_or(300),
+			max_price_age_in_seconds: self
+				.max_price_age_in_seconds
+				.or(file.max_price_age_in_seconds),
+			cache_file: self.cache_file.or(file.cache_file),
+			signing_seed: self.signing_seed.or(file.signing_seed),
+			api_keys: self.api_keys.or(file.api_keys).unwrap_or_default(),
+			rate_limit,
+			trusted_proxies: self.trusted_proxies.or(file.trusted_proxies).unwrap_or_default(),
+			cors_allowed_origins: self
+				.cors_allowed_origins
+				.or(file.cors_allowed_origins)
+				.unwrap_or_default(),
+			tls,
+		})
+	}
+}
+
+/// Whether the environment variable is set to `true` or `1`. Flags cannot take their value from
+/// the environment through structopt.
+fn flag_from_env(name: &str) -> bool {
+	std::env::var(name).map_or(false, |value| matches!(value.trim(), "true" | "1"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn command_line_options_should_override_the_config_file() {
+		let file: ConfigFile = toml::from_str(
+			r#"
+				port = 9000
+				iteration-timeout-in-seconds = 30
+				supported-currencies = ["Bitcoin:BTC", "Ethereum:ETH"]
+			"#,
+		)
+		.unwrap();
+		let args =
+			DiaApiArgs::from_iter(&["dia-batching-server", "--iteration-timeout-in-seconds", "10"]);
+
+		let settings = args.merge(file).unwrap();
+
+		assert_eq!(settings.port, 9000);
+		assert_eq!(settings.iteration_timeout_in_seconds, 10);
+		assert_eq!(settings.request_timeout_in_milliseconds, 100);
+		assert_eq!(
+			settings.supported_currencies,
+			Some(vec!["Bitcoin:BTC".to_string(), "Ethereum:ETH".to_string()])
+		);
+	}
+
+	#[test]
+	fn flag_from_env_should_accept_true_and_one() {
+		assert!(!flag_from_env("DIA_TEST_FLAG"));
+		std::env::set_var("DIA_TEST_FLAG", "true");
+		assert!(flag_from_env("DIA_TEST_FLAG"));
+		std::env::set_var("DIA_TEST_FLAG", "1");
+		assert!(flag_from_env("DIA_TEST_FLAG"));
+		std::env::set_var("DIA_TEST_FLAG", "no");
+		assert!(!flag_from_env("DIA_TEST_FLAG"));
+		std::env::remove_var("DIA_TEST_FLAG");
+	}
+
+	#[test]
+	fn tls_should_need_a_certificate_and_a_key() {
+		let file: ConfigFile = toml::from_str(r#"tls-key = "key.pem""#).unwrap();
+		let args = DiaApiArgs::from_iter(&["dia-batching-server"]);
+		assert!(args.merge(file).is_err());
+
+		let file: ConfigFile = toml::from_str(r#"tls-key = "key.pem""#).unwrap();
+		let args = DiaApiArgs::from_iter(&["dia-batching-server", "--tls-cert", "cert.pem"]);
+		assert_eq!(
+			args.merge(file).unwrap().tls,
+			Some((PathBuf::from("cert.pem"), PathBuf::from("key.pem")))
+		);
+	}
+
+	#[test]
+	fn chain_sync_should_exclude_the_supported_currencies_file() {
+		let file: ConfigFile =
+			toml::from_str(r#"chain-rpc-url = "http://127.0.0.1:9933""#).unwrap();
+		let args = DiaApiArgs::from_iter(&[
+			"dia-batching-server",
+			"--supported-currencies-file",
+			"currencies.txt",
+		]);
+		assert!(args.merge(file).is_err());
+	}
+
+	#[test]
+	fn rate_limit_burst_should_default_to_the_rate() {
+		let file: ConfigFile = toml::from_str("rate-limit-per-minute = 30").unwrap();
+		let args = DiaApiArgs::from_iter(&["dia-batching-server"]);
+		assert_eq!(args.merge(file).unwrap().rate_limit, Some((30, 30)));
+
+		let file: ConfigFile = toml::from_str("rate-limit-burst = 5").unwrap();
+		let args = DiaApiArgs::from_iter(&["dia-batching-server"]);
+		assert_eq!(args.merge(file).unwrap().rate_limit, None);
+	}
+
+	#[test]
+	fn trusted_proxies_should_parse_as_addresses() {
+		let file: ConfigFile = toml::from_str(r#"trusted-proxies = ["10.0.0.1", "::1"]"#).unwrap();
+		let args = DiaApiArgs::from_iter(&["dia-batching-server"]);
+		assert_eq!(
+			args.merge(file).unwrap().trusted_proxies,
+			vec![IpAddr::from([10, 0, 0, 1]), IpAddr::from([0, 0, 0, 0, 0, 0, 0, 1u16])]
+		);
+
+		let args =
+			DiaApiArgs::from_iter_safe(&["dia-batching-server", "--trusted-proxies", "proxy"]);
+		assert!(args.is_err());
+	}
+
+	#[test]
+	fn aggregation_should_parse_from_the_command_line_and_the_config_file() {
+		let file: ConfigFile = toml::from_str(
+			r#"
+				aggregation = "volume-weighted-mean"
+				max-source-deviation-percent = 2.5
+			"#,
+		)
+		.unwrap();
+		let args = DiaApiArgs::from_iter(&["dia-batching-server"]);
+		let settings = args.merge(file).unwrap();
+		assert_eq!(settings.aggregation, Aggregation::VolumeWeightedMean);
+		assert_eq!(settings.max_source_deviation_percent, Some(Decimal::new(25, 1)));
+
+		let args = DiaApiArgs::from_iter(&["dia-batching-server", "--aggregation", "median"]);
+		assert_eq!(args.merge(ConfigFile::default()).unwrap().aggregation, Aggregation::Median);
+	}
+
+	#[test]
+	fn dex_pools_should_be_read_from_the_config_file() {
+		let file: ConfigFile = toml::from_str(
+			r#"
+				[[dex-pools]]
+				asset = "Moonbeam:WELL"
+				quote = "Moonbeam:GLMR"
+				rpc-url = "https://rpc.api.moonbeam.network"
+				pair = "0xb536c1f9a157b263b70a9a35705168acc0271742"
+				asset-is-token0 = false
+				asset-decimals = 18
+				quote-decimals = 18
+			"#,
+		)
+		.unwrap();
+		let args = DiaApiArgs::from_iter(&["dia-batching-server"]);
+
+		let dex_pools = args.merge(file).unwrap().dex_pools;
+
+		assert_eq!(dex_pools.len(), 1);
+		assert_eq!(dex_pools[0].quote, "Moonbeam:GLMR");
+		assert!(!dex_pools[0].asset_is_token0);
+	}
+
+	#[test]
+	fn config_file_should_reject_unknown_options() {
+		assert!(toml::from_str::<ConfigFile>("prot = 9000").is_err());
+	}
 }
@@ -0,0 +1,164 @@
+use crate::dia::{Asset, Quotation, QuotedAsset};
+use crate::source::PriceSource;
+use crate::AssetSpecifier;
+use async_trait::async_trait;
+use chrono::prelude::*;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error;
+
+const TICKER_ENDPOINT: &str = "https://api.binance.com/api/v3/ticker/24hr";
+/// ### 24hr Ticker
+///
+/// `GET : https://api.binance.com/api/v3/ticker/24hr?symbol=:pair`
+///
+/// Get the spot price statistics of the last 24 hours of a trading pair
+///
+/// Response:
+/// ```ignore
+/// {
+/// 	"symbol": "BTCUSDT",
+/// 	"openPrice": "16813.21000000",
+/// 	"highPrice": "16901.20000000",
+/// 	"lowPrice": "16780.10000000",
+/// 	"lastPrice": "16826.49000000",
+/// 	"quoteVolume": "3680339928.15131800",
+/// 	"closeTime": 1671888839982,
+/// 	...
+/// }
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Ticker {
+	pub symbol: String,
+	pub open_price: Decimal,
+	pub high_price: Decimal,
+	pub low_price: Decimal,
+	pub last_price: Decimal,
+	pub quote_volume: Decimal,
+	pub close_time: i64,
+}
+
+/// Quotes the assets mapped to a Binance pair from the Binance spot market
+pub struct Binance {
+	pairs: HashMap<AssetSpecifier, String>,
+	client: reqwest::Client,
+}
+
+impl Binance {
+	pub fn new(pairs: HashMap<AssetSpecifier, String>, client: reqwest::Client) -> Self {
+		Binance { pairs, client }
+	}
+}
+
+fn into_quotation(
+	asset: &Asset,
+	ticker: Ticker,
+) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+	let time = Utc
+		.timestamp_millis_opt(ticker.close_time)
+		.single()
+		.ok_or_else(|| format!("Invalid close time {} of {}", ticker.close_time, ticker.symbol))?;
+	Ok(Quotation {
+		symbol: asset.symbol.clone(),
+		name: asset.name.clone(),
+		address: ticker.symbol,
+		blockchain: asset.blockchain.clone(),
+		price: ticker.last_price,
+		price_yesterday: ticker.open_price,
+		volume_yesterday: ticker.quote_volume,
+		time,
+		source: "binance.com".into(),
+		market_cap: None,
+		high_24h: Some(ticker.high_price),
+		low_24h: Some(ticker.low_price),
+	})
+}
+
+#[async_trait]
+impl PriceSource for Binance {
+	fn name(&self) -> &str {
+		"binance"
+	}
+
+	async fn get_quotable_assets(
+		&self,
+	) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Send + Sync>> {
+		Ok(self
+			.pairs
+			.iter()
+			.map(|(asset, pair)| QuotedAsset {
+				asset: Asset {
+					symbol: asset.symbol.clone(),
+					name: asset.symbol.clone(),
+					address: pair.clone(),
+					decimals: 0,
+					blockchain: asset.blockchain.clone(),
+				},
+				volume: Decimal::ZERO,
+			})
+			.collect())
+	}
+
+	async fn get_quotation(
+		&self,
+		quoted_asset: &QuotedAsset,
+	) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+		let ticker: Ticker = self
+			.client
+			.get(TICKER_ENDPOINT)
+			.query(&[("symbol", quoted_asset.asset.address.as_str())])
+			.send()
+			.await?
+			.error_for_status()?
+			.json()
+			.await?;
+		into_quotation(&quoted_asset.asset, ticker)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rust_decimal_macros::dec;
+
+	#[test]
+	fn tickers_should_convert_into_quotations() {
+		let mut ticker: Ticker = serde_json::from_str(
+			r#"{
+				"symbol": "BTCUSDT",
+				"priceChange": "13.28000000",
+				"openPrice": "16813.21000000",
+				"highPrice": "16901.20000000",
+				"lowPrice": "16780.10000000",
+				"lastPrice": "16826.49000000",
+				"volume": "218729.12000000",
+				"quoteVolume": "3680339928.15131800",
+				"openTime": 1671802439982,
+				"closeTime": 1671888839982
+			}"#,
+		)
+		.unwrap();
+		let asset = Asset {
+			symbol: "BTC".into(),
+			name: "BTC".into(),
+			address: "BTCUSDT".into(),
+			decimals: 0,
+			blockchain: "Bitcoin".into(),
+		};
+
+		let quotation = into_quotation(&asset, ticker.clone()).unwrap();
+
+		assert_eq!((quotation.blockchain.as_str(), quotation.symbol.as_str()), ("Bitcoin", "BTC"));
+		assert_eq!(quotation.price, dec!(16826.49));
+		assert_eq!(quotation.price_yesterday, dec!(16813.21));
+		assert_eq!(quotation.volume_yesterday, dec!(3680339928.151318));
+		assert_eq!(quotation.high_24h, Some(dec!(16901.2)));
+		assert_eq!(quotation.time.timestamp_millis(), 1671888839982);
+		assert_eq!(quotation.source, "binance.com");
+
+		ticker.close_time = i64::MAX;
+		assert!(into_quotation(&asset, ticker).is_err());
+	}
+}
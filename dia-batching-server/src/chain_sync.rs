@@ -0,0 +1,123 @@
+use crate::price_updater::SupportedCurrencies;
+use crate::AssetSpecifier;
+use log::{error, info};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::error;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// RPC method of the pallet listing the `(blockchain, symbol)` pairs of the supported currencies
+const LIST_SUPPORTED_CURRENCIES: &str = "dia_listSupportedCurrencies";
+
+/// Delay between the attempts to read the supported currencies on startup
+const STARTUP_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize, Debug)]
+struct RpcResponse {
+	result: Option<Vec<(String, String)>>,
+	error: Option<serde_json::Value>,
+}
+
+/// Lists the currencies the pallet supports through the RPC endpoint of a node
+pub async fn fetch_supported_currencies(
+	client: &reqwest::Client,
+	rpc_url: &str,
+) -> Result<HashSet<AssetSpecifier>, Box<dyn error::Error + Send + Sync>> {
+	let response: RpcResponse = client
+		.post(rpc_url)
+		.json(&json!({
+			"jsonrpc": "2.0",
+			"id": 1,
+			"method": LIST_SUPPORTED_CURRENCIES,
+			"params": [],
+		}))
+		.send()
+		.await?
+		.error_for_status()?
+		.json()
+		.await?;
+	into_supported_currencies(response)
+}
+
+fn into_supported_currencies(
+	response: RpcResponse,
+) -> Result<HashSet<AssetSpecifier>, Box<dyn error::Error + Send + Sync>> {
+	match response {
+		RpcResponse { result: Some(currencies), .. } => Ok(currencies
+			.into_iter()
+			.map(|(blockchain, symbol)| AssetSpecifier { blockchain, symbol })
+			.collect()),
+		RpcResponse { error, .. } =>
+			Err(format!("{} failed: {:?}", LIST_SUPPORTED_CURRENCIES, error).into()),
+	}
+}
+
+/// Lists the currencies the pallet supports, retrying until the node answers, so that the server
+/// does not start out fetching every asset
+pub async fn wait_for_supported_currencies(
+	client: &reqwest::Client,
+	rpc_url: &str,
+) -> HashSet<AssetSpecifier> {
+	loop {
+		match fetch_supported_currencies(client, rpc_url).await {
+			Ok(currencies) => return currencies,
+			Err(e) => {
+				error!("Failed to read the supported currencies from {}: {}", rpc_url, e);
+				tokio::time::delay_for(STARTUP_RETRY_DELAY).await;
+			},
+		}
+	}
+}
+
+/// Replaces the supported currencies with the pallet's every `interval`. The current currencies
+/// are kept while the node cannot be reached.
+pub fn spawn(
+	client: reqwest::Client,
+	rpc_url: String,
+	interval: Duration,
+	supported_currencies: SupportedCurrencies,
+) -> JoinHandle<()> {
+	tokio::spawn(async move {
+		loop {
+			tokio::time::delay_for(interval).await;
+			match fetch_supported_currencies(&client, &rpc_url).await {
+				Ok(currencies) => {
+					let current = supported_currencies.load_full();
+					if current.as_deref() != Some(&currencies) {
+						info!("The chain supports {} currencies now", currencies.len());
+						supported_currencies.store(Some(Arc::new(currencies)));
+					}
+				},
+				Err(e) => error!("Failed to sync the supported currencies from {}: {}", rpc_url, e),
+			}
+		}
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn rpc_responses_should_convert_into_supported_currencies() {
+		let response: RpcResponse = serde_json::from_str(
+			r#"{"jsonrpc": "2.0", "result": [["Bitcoin", "BTC"], ["Fiat", "PEN-USD"]], "id": 1}"#,
+		)
+		.unwrap();
+
+		let supported = into_supported_currencies(response).unwrap();
+
+		assert_eq!(supported.len(), 2);
+		assert!(supported
+			.contains(&AssetSpecifier { blockchain: "Fiat".into(), symbol: "PEN-USD".into() }));
+
+		let response: RpcResponse = serde_json::from_str(
+			r#"{"jsonrpc": "2.0", "error": {"code": -32601, "message": "Not found"}, "id": 1}"#,
+		)
+		.unwrap();
+		assert!(into_supported_currencies(response).is_err());
+	}
+}
@@ -0,0 +1,166 @@
+use crate::dia::{Asset, Quotation, QuotedAsset};
+use crate::source::PriceSource;
+use crate::AssetSpecifier;
+use async_trait::async_trait;
+use chrono::prelude::*;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error;
+
+const MARKETS_ENDPOINT: &str = "https://api.coingecko.com/api/v3/coins/markets";
+/// ### Markets
+///
+/// `GET : https://api.coingecko.com/api/v3/coins/markets?vs_currency=usd&ids=:id`
+///
+/// Get the USD price and market data of the coin with the given CoinGecko id
+///
+/// Response:
+/// ```ignore
+/// [{
+/// 	"id": "bitcoin",
+/// 	"symbol": "btc",
+/// 	"name": "Bitcoin",
+/// 	"current_price": 16826.49,
+/// 	"market_cap": 323746521418,
+/// 	"total_volume": 3680339928.15,
+/// 	"high_24h": 16901.2,
+/// 	"low_24h": 16780.1,
+/// 	"price_change_24h": 13.27,
+/// 	"last_updated": "2022-12-24T13:33:59.982Z",
+/// 	...
+/// }]
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+pub struct Market {
+	pub id: String,
+	pub name: String,
+	pub current_price: Option<Decimal>,
+	pub market_cap: Option<Decimal>,
+	pub total_volume: Option<Decimal>,
+	pub high_24h: Option<Decimal>,
+	pub low_24h: Option<Decimal>,
+	pub price_change_24h: Option<Decimal>,
+	pub last_updated: DateTime<Utc>,
+}
+
+/// Quotes the assets mapped to a CoinGecko id from CoinGecko
+pub struct CoinGecko {
+	ids: HashMap<AssetSpecifier, String>,
+	client: reqwest::Client,
+}
+
+impl CoinGecko {
+	pub fn new(ids: HashMap<AssetSpecifier, String>, client: reqwest::Client) -> Self {
+		CoinGecko { ids, client }
+	}
+}
+
+fn into_quotation(
+	asset: &Asset,
+	market: Market,
+) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+	let price = market.current_price.ok_or("CoinGecko has no price")?;
+	Ok(Quotation {
+		symbol: asset.symbol.clone(),
+		name: market.name,
+		address: market.id,
+		blockchain: asset.blockchain.clone(),
+		price,
+		price_yesterday: price - market.price_change_24h.unwrap_or_default(),
+		volume_yesterday: market.total_volume.unwrap_or_default(),
+		time: market.last_updated,
+		source: "coingecko.com".into(),
+		market_cap: market.market_cap,
+		high_24h: market.high_24h,
+		low_24h: market.low_24h,
+	})
+}
+
+#[async_trait]
+impl PriceSource for CoinGecko {
+	fn name(&self) -> &str {
+		"coingecko"
+	}
+
+	async fn get_quotable_assets(
+		&self,
+	) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Send + Sync>> {
+		Ok(self
+			.ids
+			.iter()
+			.map(|(asset, id)| QuotedAsset {
+				asset: Asset {
+					symbol: asset.symbol.clone(),
+					name: id.clone(),
+					address: id.clone(),
+					decimals: 0,
+					blockchain: asset.blockchain.clone(),
+				},
+				volume: Decimal::ZERO,
+			})
+			.collect())
+	}
+
+	async fn get_quotation(
+		&self,
+		quoted_asset: &QuotedAsset,
+	) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+		let id = &quoted_asset.asset.address;
+		let markets: Vec<Market> = self
+			.client
+			.get(MARKETS_ENDPOINT)
+			.query(&[("vs_currency", "usd"), ("ids", id.as_str())])
+			.send()
+			.await?
+			.error_for_status()?
+			.json()
+			.await?;
+		let market = markets
+			.into_iter()
+			.find(|market| &market.id == id)
+			.ok_or_else(|| format!("CoinGecko has no coin with id {}", id))?;
+		into_quotation(&quoted_asset.asset, market)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rust_decimal_macros::dec;
+
+	#[test]
+	fn markets_should_convert_into_quotations() {
+		let markets: Vec<Market> = serde_json::from_str(
+			r#"[{
+				"id": "polkadot",
+				"symbol": "dot",
+				"name": "Polkadot",
+				"current_price": 4.5,
+				"market_cap": 5200000000,
+				"total_volume": 120000000,
+				"high_24h": 4.7,
+				"low_24h": 4.4,
+				"price_change_24h": -0.25,
+				"last_updated": "2022-12-24T13:33:59.982Z"
+			}]"#,
+		)
+		.unwrap();
+		let asset = Asset {
+			symbol: "DOT".into(),
+			name: "polkadot".into(),
+			address: "polkadot".into(),
+			decimals: 0,
+			blockchain: "Polkadot".into(),
+		};
+
+		let quotation = into_quotation(&asset, markets[0].clone()).unwrap();
+
+		assert_eq!((quotation.blockchain.as_str(), quotation.symbol.as_str()), ("Polkadot", "DOT"));
+		assert_eq!(quotation.price, dec!(4.5));
+		assert_eq!(quotation.price_yesterday, dec!(4.75));
+		assert_eq!(quotation.volume_yesterday, dec!(120000000));
+		assert_eq!(quotation.market_cap, Some(dec!(5200000000)));
+		assert_eq!(quotation.source, "coingecko.com");
+	}
+}
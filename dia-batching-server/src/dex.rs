@@ -0,0 +1,253 @@
+use crate::dia::{Asset, Quotation, QuotedAsset};
+use crate::handlers::Currency;
+use crate::source::PriceSource;
+use crate::storage::CoinInfoStorage;
+use crate::AssetSpecifier;
+use async_trait::async_trait;
+use chrono::prelude::*;
+use log::error;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+use std::error;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Selector of `getReserves()` of Uniswap V2 compatible pair contracts
+const GET_RESERVES: &str = "0x0902f1ac";
+
+/// A Uniswap V2 compatible pool pricing `asset` in `quote`, from the config file:
+/// ```toml
+/// [[dex-pools]]
+/// asset = "Moonbeam:WELL"
+/// quote = "Moonbeam:GLMR"
+/// rpc-url = "https://rpc.api.moonbeam.network"
+/// pair = "0xb536c1f9a157b263b70a9a35705168acc0271742"
+/// asset-is-token0 = false
+/// asset-decimals = 18
+/// quote-decimals = 18
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DexPool {
+	pub asset: String,
+	pub quote: String,
+	pub rpc_url: String,
+	pub pair: String,
+	pub asset_is_token0: bool,
+	pub asset_decimals: u32,
+	pub quote_decimals: u32,
+}
+
+#[derive(Deserialize, Debug)]
+struct RpcResponse {
+	result: Option<String>,
+	error: Option<serde_json::Value>,
+}
+
+/// Prices long-tail assets from the reserves of a DEX pool against a quote asset, multiplied by the
+/// USD price of the quote asset in the cache
+pub struct Dex {
+	pools: Vec<(AssetSpecifier, AssetSpecifier, DexPool)>,
+	storage: Arc<CoinInfoStorage>,
+	client: reqwest::Client,
+}
+
+impl Dex {
+	/// Leaves out the pools whose asset or quote are malformed
+	pub fn new(
+		pools: Vec<DexPool>,
+		storage: Arc<CoinInfoStorage>,
+		client: reqwest::Client,
+	) -> Self {
+		let pools = pools
+			.into_iter()
+			.filter_map(|pool| {
+				match (AssetSpecifier::parse(&pool.asset), AssetSpecifier::parse(&pool.quote)) {
+					(Some(asset), Some(quote)) => Some((asset, quote, pool)),
+					_ => {
+						error!("Invalid DEX pool {:?} – asset and quote need the form <blockchain>:<symbol>", pool);
+						None
+					},
+				}
+			})
+			.collect();
+		Dex { pools, storage, client }
+	}
+
+	async fn get_reserves(
+		&self,
+		pool: &DexPool,
+	) -> Result<(u128, u128), Box<dyn error::Error + Send + Sync>> {
+		decode_reserves(&eth_call(&self.client, &pool.rpc_url, &pool.pair, GET_RESERVES).await?)
+	}
+}
+
+/// Calls the contract at `to` with `data` on the latest block, returning the hex encoded result
+pub(crate) async fn eth_call(
+	client: &reqwest::Client,
+	rpc_url: &str,
+	to: &str,
+	data: &str,
+) -> Result<String, Box<dyn error::Error + Send + Sync>> {
+	let response: RpcResponse = client
+		.post(rpc_url)
+		.json(&json!({
+			"jsonrpc": "2.0",
+			"id": 1,
+			"method": "eth_call",
+			"params": [{ "to": to, "data": data }, "latest"],
+		}))
+		.send()
+		.await?
+		.error_for_status()?
+		.json()
+		.await?;
+	match response {
+		RpcResponse { result: Some(result), .. } => Ok(result),
+		RpcResponse { error, .. } => Err(format!("eth_call failed: {:?}", error).into()),
+	}
+}
+
+/// USD price of the asset in the cache
+pub(crate) fn cached_price(
+	storage: &CoinInfoStorage,
+	asset: &AssetSpecifier,
+) -> Option<Decimal> {
+	storage
+		.get_currencies_by_blockchains_and_symbols(vec![Currency {
+			blockchain: asset.blockchain.clone(),
+			symbol: asset.symbol.clone(),
+		}])
+		.first()
+		.map(|coin_info| Decimal::from_i128_with_scale(coin_info.price as i128, 12))
+}
+
+/// Decodes the two reserves returned by `getReserves()`, each a 32 byte big endian word
+fn decode_reserves(result: &str) -> Result<(u128, u128), Box<dyn error::Error + Send + Sync>> {
+	let bytes = hex::decode(result.trim_start_matches("0x"))?;
+	if bytes.len() < 64 || bytes[..16] != [0; 16] || bytes[32..48] != [0; 16] {
+		return Err("Malformed getReserves() result".into())
+	}
+	let word = |offset: usize| {
+		let mut reserve = [0; 16];
+		reserve.copy_from_slice(&bytes[offset + 16..offset + 32]);
+		u128::from_be_bytes(reserve)
+	};
+	Ok((word(0), word(32)))
+}
+
+pub(crate) fn to_decimal(
+	amount: u128,
+	decimals: u32,
+) -> Result<Decimal, Box<dyn error::Error + Send + Sync>> {
+	let mut amount = Decimal::from_str(&amount.to_string())?;
+	amount.set_scale(decimals)?;
+	Ok(amount)
+}
+
+/// USD price of the pool's asset, from its reserves and the USD price of the quote asset
+fn price(
+	pool: &DexPool,
+	(reserve0, reserve1): (u128, u128),
+	quote_price: Decimal,
+) -> Result<Decimal, Box<dyn error::Error + Send + Sync>> {
+	let (asset_reserve, quote_reserve) =
+		if pool.asset_is_token0 { (reserve0, reserve1) } else { (reserve1, reserve0) };
+	let asset_reserve = to_decimal(asset_reserve, pool.asset_decimals)?;
+	let quote_reserve = to_decimal(quote_reserve, pool.quote_decimals)?;
+	if asset_reserve.is_zero() {
+		return Err("The pool holds none of the asset".into())
+	}
+	quote_reserve
+		.checked_div(asset_reserve)
+		.and_then(|price| price.checked_mul(quote_price))
+		.ok_or_else(|| "Price too large".into())
+}
+
+#[async_trait]
+impl PriceSource for Dex {
+	fn name(&self) -> &str {
+		"dex"
+	}
+
+	async fn get_quotable_assets(
+		&self,
+	) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Send + Sync>> {
+		Ok(self
+			.pools
+			.iter()
+			.map(|(asset, _, pool)| QuotedAsset {
+				asset: Asset {
+					symbol: asset.symbol.clone(),
+					name: asset.symbol.clone(),
+					address: pool.pair.clone(),
+					decimals: pool.asset_decimals as u8,
+					blockchain: asset.blockchain.clone(),
+				},
+				volume: Decimal::ZERO,
+			})
+			.collect())
+	}
+
+	async fn get_quotation(
+		&self,
+		quoted_asset: &QuotedAsset,
+	) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+		let (asset, quote, pool) = self
+			.pools
+			.iter()
+			.find(|(asset, _, _)| {
+				asset.blockchain == quoted_asset.asset.blockchain &&
+					asset.symbol == quoted_asset.asset.symbol
+			})
+			.ok_or("No DEX pool for the asset")?;
+		let quote_price = cached_price(&self.storage, quote)
+			.ok_or_else(|| format!("No price of the quote asset {:?}", quote))?;
+
+		let price = price(pool, self.get_reserves(pool).await?, quote_price)?;
+		Ok(Quotation {
+			symbol: asset.symbol.clone(),
+			name: asset.symbol.clone(),
+			address: pool.pair.clone(),
+			blockchain: asset.blockchain.clone(),
+			price,
+			price_yesterday: price,
+			volume_yesterday: Decimal::ZERO,
+			time: Utc::now(),
+			source: "dex".into(),
+			market_cap: None,
+			high_24h: None,
+			low_24h: None,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rust_decimal_macros::dec;
+
+	#[test]
+	fn reserves_should_price_the_asset_in_usd() {
+		let result = format!(
+			"0x{:064x}{:064x}{:064x}",
+			2_000_000_000_000_000_000_000_u128, 500_000_000_u128, 1671888839_u128
+		);
+		let reserves = decode_reserves(&result).unwrap();
+		assert_eq!(reserves, (2_000_000_000_000_000_000_000, 500_000_000));
+
+		let pool = DexPool {
+			asset: "Moonbeam:WELL".into(),
+			quote: "Ethereum:USDC".into(),
+			rpc_url: "http://localhost:9933".into(),
+			pair: "0x0".into(),
+			asset_is_token0: true,
+			asset_decimals: 18,
+			quote_decimals: 6,
+		};
+		assert_eq!(price(&pool, reserves, dec!(1.5)).unwrap(), dec!(0.375));
+
+		assert!(decode_reserves("0x00").is_err());
+	}
+}
@@ -1,5 +1,7 @@
+use crate::source::PriceSource;
 use async_trait::async_trait;
 use chrono::prelude::*;
+use log::warn;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use std::error;
@@ -73,6 +75,9 @@ const QUOTATION_ENDPOINT: &str = "https://api.diadata.org/v1/assetQuotation";
 /// 	"Source": "diadata.org"
 /// }
 /// ```
+///
+/// `MarketCap`, `High24h` and `Low24h` are not part of the quotation, [`Dia`] fills them in from
+/// the supply and chart point statistics of the asset.
 #[derive(Deserialize, Debug, Clone)]
 pub struct Quotation {
 	#[serde(rename(deserialize = "Symbol"))]
@@ -93,6 +98,12 @@ pub struct Quotation {
 	pub time: DateTime<Utc>,
 	#[serde(rename(deserialize = "Source"))]
 	pub source: String,
+	#[serde(skip_deserializing)]
+	pub market_cap: Option<Decimal>,
+	#[serde(skip_deserializing)]
+	pub high_24h: Option<Decimal>,
+	#[serde(skip_deserializing)]
+	pub low_24h: Option<Decimal>,
 }
 
 impl Default for Quotation {
@@ -107,40 +118,222 @@ impl Default for Quotation {
 			volume_yesterday: Default::default(),
 			time: Utc::now(),
 			source: Default::default(),
+			market_cap: None,
+			high_24h: None,
+			low_24h: None,
 		}
 	}
 }
 
-#[async_trait]
-pub trait DiaApi {
-	async fn get_quotable_assets(
+const SUPPLY_ENDPOINT: &str = "https://api.diadata.org/v1/assetSupply";
+/// ### Supply
+///
+/// `GET : https://api.diadata.org/v1/assetSupply/:blockchain/:address`
+///
+/// Get the latest circulating supply of the asset corresponding to a blockchain/address pair
+///
+/// Response:
+/// ```ignore
+/// {
+/// 	"Asset": { "Symbol": "BTC", ... },
+/// 	"Supply": 19247593,
+/// 	"CirculatingSupply": 19247593,
+/// 	"Source": "diadata.org",
+/// 	"Time": "2022-12-24T13:30:00Z",
+/// 	"Block": 0
+/// }
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+pub struct Supply {
+	#[serde(rename(deserialize = "CirculatingSupply"))]
+	pub circulating_supply: Decimal,
+}
+
+const CHART_POINTS_ENDPOINT: &str = "https://api.diadata.org/v1/assetChartPoints/MAIR120";
+/// ### Chart Points
+///
+/// `GET : https://api.diadata.org/v1/assetChartPoints/MAIR120/:blockchain/:address`
+///
+/// Get the prices the MAIR120 filter computed for the asset between the `starttime` and `endtime`
+/// unix timestamps
+///
+/// Response:
+/// ```ignore
+/// {
+/// 	"DataPoints": [{
+/// 		"Series": [{
+/// 			"name": "filters",
+/// 			"columns": ["time", "allExchanges", "filter", "symbol", "value"],
+/// 			"values": [["2022-12-24T13:28:00Z", true, "MAIR120", "BTC", 16826.48], ...]
+/// 		}]
+/// 	}]
+/// }
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+pub struct ChartPoints {
+	#[serde(rename(deserialize = "DataPoints"), default)]
+	pub data_points: Vec<DataPoint>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DataPoint {
+	#[serde(rename(deserialize = "Series"), default)]
+	pub series: Vec<Series>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Series {
+	pub columns: Vec<String>,
+	#[serde(default)]
+	pub values: Vec<Vec<serde_json::Value>>,
+}
+
+/// Highest and lowest price of the chart points, `None` if there are none
+fn high_low(chart_points: &ChartPoints) -> Option<(Decimal, Decimal)> {
+	let prices = chart_points.data_points.iter().flat_map(|point| &point.series).flat_map(|series| {
+		let column = series.columns.iter().position(|column| column == "value");
+		series.values.iter().filter_map(move |values| {
+			serde_json::from_value::<Decimal>(values.get(column?)?.clone()).ok()
+		})
+	});
+	prices.fold(None, |high_low, price| match high_low {
+		None => Some((price, price)),
+		Some((high, low)) => Some((high.max(price), low.min(price))),
+	})
+}
+
+pub struct Dia {
+	client: reqwest::Client,
+}
+
+impl Dia {
+	pub fn new(client: reqwest::Client) -> Self {
+		Dia { client }
+	}
+
+	async fn get_supply(
 		&self,
-	) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Send + Sync>>;
-	async fn get_quotation(
+		asset: &Asset,
+	) -> Result<Supply, Box<dyn error::Error + Send + Sync>> {
+		let r = self
+			.client
+			.get(&format!("{}/{}/{}", SUPPLY_ENDPOINT, asset.blockchain, asset.address))
+			.send()
+			.await?
+			.error_for_status()?;
+		Ok(r.json().await?)
+	}
+
+	async fn get_chart_points(
 		&self,
-		_: &QuotedAsset,
-	) -> Result<Quotation, Box<dyn error::Error + Sync + Send>>;
+		asset: &Asset,
+		end: DateTime<Utc>,
+	) -> Result<ChartPoints, Box<dyn error::Error + Send + Sync>> {
+		let start = end - chrono::Duration::hours(24);
+		let r = self
+			.client
+			.get(&format!("{}/{}/{}", CHART_POINTS_ENDPOINT, asset.blockchain, asset.address))
+			.query(&[("starttime", start.timestamp()), ("endtime", end.timestamp())])
+			.send()
+			.await?
+			.error_for_status()?;
+		Ok(r.json().await?)
+	}
 }
-pub struct Dia;
 
 #[async_trait]
-impl DiaApi for Dia {
+impl PriceSource for Dia {
+	fn name(&self) -> &str {
+		"dia"
+	}
+
 	async fn get_quotation(
 		&self,
 		asset: &QuotedAsset,
 	) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
 		let QuotedAsset { asset, volume: _ } = asset;
-		let r =
-			reqwest::get(&format!("{}/{}/{}", QUOTATION_ENDPOINT, asset.blockchain, asset.address))
-				.await?;
-		let q: Quotation = r.json().await?;
+		let r = self
+			.client
+			.get(&format!("{}/{}/{}", QUOTATION_ENDPOINT, asset.blockchain, asset.address))
+			.send()
+			.await?
+			.error_for_status()?;
+		let mut q: Quotation = r.json().await?;
+
+		// The statistics are optional, a quotation without them is still submitted
+		let (supply, chart_points) =
+			tokio::join!(self.get_supply(asset), self.get_chart_points(asset, q.time));
+		match supply {
+			Ok(supply) => q.market_cap = q.price.checked_mul(supply.circulating_supply),
+			Err(e) => warn!("Failed to get the supply of {}: {}", asset.symbol, e),
+		}
+		match chart_points {
+			Ok(chart_points) => {
+				let high_low = high_low(&chart_points);
+				q.high_24h = high_low.map(|(high, _)| high);
+				q.low_24h = high_low.map(|(_, low)| low);
+			},
+			Err(e) => warn!("Failed to get the chart points of {}: {}", asset.symbol, e),
+		}
 		Ok(q)
 	}
 
 	async fn get_quotable_assets(
 		&self,
 	) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Sync + Send>> {
-		let r = reqwest::get(QUOTABLE_ASSETS_ENDPOINT).await?;
+		let r = self.client.get(QUOTABLE_ASSETS_ENDPOINT).send().await?.error_for_status()?;
 		Ok(r.json().await?)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rust_decimal_macros::dec;
+
+	#[test]
+	fn quotations_should_not_deserialize_statistics() {
+		let quotation: Quotation = serde_json::from_str(
+			r#"{
+				"Symbol": "BTC",
+				"Name": "Bitcoin",
+				"Address": "0x0000000000000000000000000000000000000000",
+				"Blockchain": "Bitcoin",
+				"Price": 16826.489316709616,
+				"PriceYesterday": 16813.219221169464,
+				"VolumeYesterdayUSD": 3680339928.151318,
+				"Time": "2022-12-24T13:33:59.982Z",
+				"Source": "diadata.org",
+				"MarketCap": 1
+			}"#,
+		)
+		.unwrap();
+
+		assert_eq!(quotation.price, dec!(16826.489316709616));
+		assert_eq!(quotation.market_cap, None);
+		assert_eq!((quotation.high_24h, quotation.low_24h), (None, None));
+	}
+
+	#[test]
+	fn chart_points_should_give_the_high_and_low() {
+		let chart_points: ChartPoints = serde_json::from_str(
+			r#"{
+				"DataPoints": [{
+					"Series": [{
+						"name": "filters",
+						"columns": ["time", "allExchanges", "filter", "symbol", "value"],
+						"values": [
+							["2022-12-24T11:28:00Z", true, "MAIR120", "BTC", 16826.4],
+							["2022-12-24T12:28:00Z", true, "MAIR120", "BTC", 16901.2],
+							["2022-12-24T13:28:00Z", true, "MAIR120", "BTC", 16780.1]
+						]
+					}]
+				}]
+			}"#,
+		)
+		.unwrap();
+
+		assert_eq!(high_low(&chart_points), Some((dec!(16901.2), dec!(16780.1))));
+		assert_eq!(high_low(&ChartPoints { data_points: vec![] }), None);
+	}
+}
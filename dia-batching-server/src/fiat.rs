@@ -0,0 +1,152 @@
+use crate::dia::{Asset, Quotation, QuotedAsset};
+use crate::source::PriceSource;
+use crate::AssetSpecifier;
+use async_trait::async_trait;
+use chrono::prelude::*;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error;
+
+const LATEST_ENDPOINT: &str = "https://api.frankfurter.app/latest";
+/// ### Latest reference rates
+///
+/// `GET : https://api.frankfurter.app/latest?from=:base&to=:quote`
+///
+/// Get the latest ECB reference rate of a currency pair, published every working day
+///
+/// Response:
+/// ```ignore
+/// {
+/// 	"amount": 1.0,
+/// 	"base": "BRL",
+/// 	"date": "2022-12-23",
+/// 	"rates": { "USD": 0.19287 }
+/// }
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+pub struct Rates {
+	pub base: String,
+	pub date: NaiveDate,
+	pub rates: HashMap<String, Decimal>,
+}
+
+/// Quotes fiat pairs like `Fiat:BRL-USD` from the ECB reference rates
+pub struct Fiat {
+	pairs: Vec<AssetSpecifier>,
+	client: reqwest::Client,
+}
+
+impl Fiat {
+	pub fn new(pairs: Vec<AssetSpecifier>, client: reqwest::Client) -> Self {
+		Fiat { pairs, client }
+	}
+}
+
+/// Splits the symbol of a fiat pair into its base and quote currency
+fn currencies(symbol: &str) -> Result<(&str, &str), Box<dyn error::Error + Send + Sync>> {
+	symbol
+		.split_once('-')
+		.ok_or_else(|| format!("Fiat pair {} needs the form <base>-<quote>", symbol).into())
+}
+
+/// Stamps the quotation with the time the rates were `fetched` at rather than their reference
+/// date. The ECB publishes once per working day, so the reference date is regularly older than the
+/// pallet's `MaxPriceAge` although the rate is the latest one there is.
+fn into_quotation(
+	asset: &Asset,
+	rates: Rates,
+	fetched: DateTime<Utc>,
+) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+	let (_, quote) = currencies(&asset.symbol)?;
+	let price = *rates.rates.get(quote).ok_or_else(|| format!("No {} rate", quote))?;
+	Ok(Quotation {
+		symbol: asset.symbol.clone(),
+		name: asset.symbol.clone(),
+		address: rates.base,
+		blockchain: asset.blockchain.clone(),
+		price,
+		price_yesterday: price,
+		volume_yesterday: Decimal::ZERO,
+		time: fetched,
+		source: "frankfurter.app".into(),
+		market_cap: None,
+		high_24h: None,
+		low_24h: None,
+	})
+}
+
+#[async_trait]
+impl PriceSource for Fiat {
+	fn name(&self) -> &str {
+		"fiat"
+	}
+
+	async fn get_quotable_assets(
+		&self,
+	) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Send + Sync>> {
+		Ok(self
+			.pairs
+			.iter()
+			.map(|pair| QuotedAsset {
+				asset: Asset {
+					symbol: pair.symbol.clone(),
+					name: pair.symbol.clone(),
+					address: pair.symbol.clone(),
+					decimals: 0,
+					blockchain: pair.blockchain.clone(),
+				},
+				volume: Decimal::ZERO,
+			})
+			.collect())
+	}
+
+	async fn get_quotation(
+		&self,
+		quoted_asset: &QuotedAsset,
+	) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+		let (base, quote) = currencies(&quoted_asset.asset.symbol)?;
+		let rates: Rates = self
+			.client
+			.get(LATEST_ENDPOINT)
+			.query(&[("from", base), ("to", quote)])
+			.send()
+			.await?
+			.error_for_status()?
+			.json()
+			.await?;
+		into_quotation(&quoted_asset.asset, rates, Utc::now())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rust_decimal_macros::dec;
+
+	#[test]
+	fn rates_should_convert_into_quotations() {
+		let rates: Rates = serde_json::from_str(
+			r#"{"amount": 1.0, "base": "BRL", "date": "2022-12-23", "rates": {"USD": 0.19287}}"#,
+		)
+		.unwrap();
+		let asset = Asset {
+			symbol: "BRL-USD".into(),
+			name: "BRL-USD".into(),
+			address: "BRL-USD".into(),
+			decimals: 0,
+			blockchain: "Fiat".into(),
+		};
+
+		let fetched = Utc.ymd(2022, 12, 27).and_hms(9, 30, 0);
+
+		let quotation = into_quotation(&asset, rates.clone(), fetched).unwrap();
+
+		assert_eq!((quotation.blockchain.as_str(), quotation.symbol.as_str()), ("Fiat", "BRL-USD"));
+		assert_eq!(quotation.price, dec!(0.19287));
+		assert_eq!(quotation.time, fetched);
+
+		let asset = Asset { symbol: "BRL-EUR".into(), ..asset };
+		assert!(into_quotation(&asset, rates, fetched).is_err());
+	}
+}
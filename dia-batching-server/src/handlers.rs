@@ -1,18 +1,174 @@
+use crate::metrics::METRICS;
+use crate::rate_limit::RateLimiter;
+use crate::signing::{ResponseSigner, SIGNATURE_HEADER};
 use crate::storage::{CoinInfo, CoinInfoStorage};
-use actix_web::web::Json;
-use actix_web::{post, web};
+use actix_web::error::{
+	ErrorBadRequest, ErrorInternalServerError, ErrorServiceUnavailable, ErrorUnauthorized,
+	InternalError,
+};
+use actix_web::http::header;
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use codec::{Decode, Encode};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Content type of SCALE encoded requests and responses
+pub const SCALE_CONTENT_TYPE: &str = "application/scale";
+
+/// Seconds clients are asked to wait before retrying while the first price update runs
+const FIRST_UPDATE_RETRY_AFTER: u64 = 10;
+
+/// Header carrying the API key, as an alternative to `Authorization: Bearer <key>`
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Keys accepted on the coin info endpoints. Without them, the endpoints are open.
+#[derive(Debug, Clone)]
+pub struct ApiKeys(pub Vec<String>);
+
+impl ApiKeys {
+	fn authorize(&self, req: &HttpRequest) -> Result<(), actix_web::Error> {
+		let header = |name| req.headers().get(name).and_then(|value| value.to_str().ok());
+		let key = header(header::AUTHORIZATION.as_str())
+			.and_then(|value| value.strip_prefix("Bearer "))
+			.or_else(|| header(API_KEY_HEADER))
+			.ok_or_else(|| ErrorUnauthorized("Missing API key"))?;
+
+		if self.0.iter().any(|accepted| constant_time_eq(accepted.as_bytes(), key.as_bytes())) {
+			Ok(())
+		} else {
+			Err(ErrorUnauthorized("Invalid API key"))
+		}
+	}
+}
+
+/// Rejects requests with 503 and a `Retry-After` header until there are prices to serve, so that
+/// clients can tell a starting server from unknown assets
+fn ensure_populated(storage: &CoinInfoStorage) -> Result<(), actix_web::Error> {
+	if storage.is_populated() {
+		return Ok(())
+	}
+	let response = HttpResponse::ServiceUnavailable()
+		.header(header::RETRY_AFTER, FIRST_UPDATE_RETRY_AFTER.to_string())
+		.finish();
+	Err(InternalError::from_response("No price update completed yet", response).into())
+}
 
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Answers with JSON unless SCALE is accepted. The request body is read as SCALE if sent with
+/// the SCALE content type, as JSON otherwise.
 #[post("/currencies")]
 pub async fn currencies_post(
-	web::Json(currencies): web::Json<Vec<Currency>>,
+	req: HttpRequest,
+	body: web::Bytes,
 	storage: web::Data<CoinInfoStorage>,
-) -> Json<Vec<CoinInfo>> {
+	signer: Option<web::Data<ResponseSigner>>,
+	api_keys: Option<web::Data<ApiKeys>>,
+	rate_limiter: Option<web::Data<RateLimiter>>,
+) -> Result<HttpResponse, actix_web::Error> {
+	if let Some(rate_limiter) = rate_limiter {
+		rate_limiter.limit(&req)?;
+	}
+	if let Some(api_keys) = api_keys {
+		api_keys.authorize(&req)?;
+	}
+	let currencies: Vec<Currency> = if has_header(&req, header::CONTENT_TYPE, SCALE_CONTENT_TYPE) {
+		Decode::decode(&mut &body[..]).map_err(ErrorBadRequest)?
+	} else {
+		serde_json::from_slice(&body).map_err(ErrorBadRequest)?
+	};
+
 	println!("Request currencies {:?}", currencies);
-	Json(storage.get_ref().get_currencies_by_blockchains_and_symbols(currencies))
+	ensure_populated(storage.get_ref())?;
+	let coin_infos = storage.get_ref().get_currencies_by_blockchains_and_symbols(currencies);
+	coin_infos_response(&req, &coin_infos, signer)
+}
+
+/// Answers with every cached coin info, in JSON unless SCALE is accepted
+#[get("/currencies")]
+pub async fn currencies_get(
+	req: HttpRequest,
+	storage: web::Data<CoinInfoStorage>,
+	signer: Option<web::Data<ResponseSigner>>,
+	api_keys: Option<web::Data<ApiKeys>>,
+	rate_limiter: Option<web::Data<RateLimiter>>,
+) -> Result<HttpResponse, actix_web::Error> {
+	if let Some(rate_limiter) = rate_limiter {
+		rate_limiter.limit(&req)?;
+	}
+	if let Some(api_keys) = api_keys {
+		api_keys.authorize(&req)?;
+	}
+	ensure_populated(storage.get_ref())?;
+	let coin_infos = storage.get_ref().get_all_currencies();
+	coin_infos_response(&req, &coin_infos, signer)
+}
+
+/// Maximum time since the last price update for the server to report itself ready
+#[derive(Debug, Clone, Copy)]
+pub struct MaxUpdateAge(pub Duration);
+
+/// Liveness probe, answers as long as the process serves requests
+#[get("/health")]
+pub async fn health() -> HttpResponse {
+	HttpResponse::Ok().body("OK")
+}
+
+/// Readiness probe, answers once the first price update completed and the last one is not older
+/// than `MaxUpdateAge`
+#[get("/ready")]
+pub async fn ready(
+	storage: web::Data<CoinInfoStorage>,
+	max_age: web::Data<MaxUpdateAge>,
+) -> Result<HttpResponse, actix_web::Error> {
+	match storage.get_ref().last_update() {
+		Some(updated) if updated.elapsed() <= max_age.0 => Ok(HttpResponse::Ok().body("OK")),
+		Some(updated) => Err(ErrorServiceUnavailable(format!(
+			"Last price update {}s ago",
+			updated.elapsed().as_secs()
+		))),
+		None => Err(ErrorServiceUnavailable("No price update completed yet")),
+	}
+}
+
+/// Counters and gauges in the Prometheus text format
+#[get("/metrics")]
+pub async fn metrics(storage: web::Data<CoinInfoStorage>) -> HttpResponse {
+	HttpResponse::Ok()
+		.content_type("text/plain; version=0.0.4")
+		.body(METRICS.render(storage.get_ref()))
+}
+
+fn coin_infos_response(
+	req: &HttpRequest,
+	coin_infos: &[CoinInfo],
+	signer: Option<web::Data<ResponseSigner>>,
+) -> Result<HttpResponse, actix_web::Error> {
+	METRICS.inc_served_requests();
+	let mut response = HttpResponse::Ok();
+	let body = if has_header(req, header::ACCEPT, SCALE_CONTENT_TYPE) {
+		response.content_type(SCALE_CONTENT_TYPE);
+		coin_infos.encode()
+	} else {
+		response.content_type("application/json");
+		serde_json::to_vec(coin_infos).map_err(ErrorInternalServerError)?
+	};
+	if let Some(signer) = signer {
+		response.header(SIGNATURE_HEADER, signer.sign(&body));
+	}
+	Ok(response.body(body))
+}
+
+fn has_header(req: &HttpRequest, name: header::HeaderName, value: &str) -> bool {
+	req.headers()
+		.get(name)
+		.and_then(|header| header.to_str().ok())
+		.map_or(false, |header| header.contains(value))
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Encode, Decode, Debug)]
 pub struct Currency {
 	pub blockchain: String,
 	pub symbol: String,
@@ -57,6 +213,172 @@ mod tests {
 		assert_eq!(r.len(), 2);
 	}
 
+	#[tokio::test]
+	async fn test_currencies_get() {
+		let storage = get_storage();
+		let data = web::Data::from(storage.clone());
+
+		let mut app =
+			test::init_service(App::new().app_data(data.clone()).service(currencies_get)).await;
+		let req = test::TestRequest::get().uri("http://localhost:8080/currencies").to_request();
+
+		let resp = test::call_service(&mut app, req).await;
+
+		assert_eq!(resp.status(), http::StatusCode::OK);
+
+		let r: Vec<CoinInfo> = test::read_body_json(resp).await;
+
+		assert_eq!(r.len(), 2);
+		assert_eq!(r[0].symbol.as_str(), "BTC");
+		assert_eq!(r[1].symbol.as_str(), "ETH");
+	}
+
+	#[tokio::test]
+	async fn test_currencies_api_keys() {
+		let storage = get_storage();
+		let data = web::Data::from(storage.clone());
+		let api_keys = web::Data::new(ApiKeys(vec!["secret".into()]));
+
+		let mut app = test::init_service(
+			App::new().app_data(data.clone()).app_data(api_keys).service(currencies_get),
+		)
+		.await;
+
+		let req = test::TestRequest::get().uri("http://localhost:8080/currencies").to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+
+		let req = test::TestRequest::get()
+			.uri("http://localhost:8080/currencies")
+			.header(header::AUTHORIZATION, "Bearer wrong")
+			.to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+
+		let req = test::TestRequest::get()
+			.uri("http://localhost:8080/currencies")
+			.header(header::AUTHORIZATION, "Bearer secret")
+			.to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::OK);
+
+		let req = test::TestRequest::get()
+			.uri("http://localhost:8080/currencies")
+			.header(API_KEY_HEADER, "secret")
+			.to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::OK);
+	}
+
+	#[tokio::test]
+	async fn test_currencies_rate_limit() {
+		let storage = get_storage();
+		let data = web::Data::from(storage.clone());
+		let rate_limiter = web::Data::new(RateLimiter::new(1, 1, vec![]));
+
+		let mut app = test::init_service(
+			App::new().app_data(data.clone()).app_data(rate_limiter).service(currencies_get),
+		)
+		.await;
+		let client = "127.0.0.1:4000".parse().unwrap();
+
+		let req = test::TestRequest::get()
+			.uri("http://localhost:8080/currencies")
+			.peer_addr(client)
+			.to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::OK);
+
+		let req = test::TestRequest::get()
+			.uri("http://localhost:8080/currencies")
+			.peer_addr(client)
+			.to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+		assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "60");
+
+		let req = test::TestRequest::get()
+			.uri("http://localhost:8080/currencies")
+			.peer_addr("127.0.0.2:4000".parse().unwrap())
+			.to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::OK);
+	}
+
+	#[tokio::test]
+	async fn test_currencies_rate_limit_behind_trusted_proxy() {
+		let storage = get_storage();
+		let data = web::Data::from(storage.clone());
+		let proxy = "10.0.0.1:4000".parse::<std::net::SocketAddr>().unwrap();
+		let rate_limiter = web::Data::new(RateLimiter::new(1, 1, vec![proxy.ip()]));
+
+		let mut app = test::init_service(
+			App::new().app_data(data.clone()).app_data(rate_limiter).service(currencies_get),
+		)
+		.await;
+		let through_proxy = |client: &str| {
+			test::TestRequest::get()
+				.uri("http://localhost:8080/currencies")
+				.peer_addr(proxy)
+				.header("x-forwarded-for", client)
+				.to_request()
+		};
+
+		let resp = test::call_service(&mut app, through_proxy("203.0.113.7")).await;
+		assert_eq!(resp.status(), http::StatusCode::OK);
+
+		let resp = test::call_service(&mut app, through_proxy("203.0.113.7")).await;
+		assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+
+		// Another client behind the same proxy has a bucket of its own
+		let resp = test::call_service(&mut app, through_proxy("203.0.113.8, 10.0.0.1")).await;
+		assert_eq!(resp.status(), http::StatusCode::OK);
+	}
+
+	#[tokio::test]
+	async fn test_currencies_before_first_update() {
+		let storage = Arc::new(CoinInfoStorage::default());
+		let data = web::Data::from(storage.clone());
+
+		let mut app =
+			test::init_service(App::new().app_data(data.clone()).service(currencies_get)).await;
+
+		let req = test::TestRequest::get().uri("http://localhost:8080/currencies").to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+		assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "10");
+
+		storage.replace_currencies_by_symbols(vec![]);
+		let req = test::TestRequest::get().uri("http://localhost:8080/currencies").to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::OK);
+	}
+
+	#[tokio::test]
+	async fn test_ready() {
+		let storage = Arc::new(CoinInfoStorage::default());
+		let data = web::Data::from(storage.clone());
+		let max_age = web::Data::new(MaxUpdateAge(Duration::from_secs(60)));
+
+		let mut app = test::init_service(
+			App::new().app_data(data.clone()).app_data(max_age).service(ready).service(health),
+		)
+		.await;
+
+		let req = test::TestRequest::get().uri("http://localhost:8080/health").to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::OK);
+
+		let req = test::TestRequest::get().uri("http://localhost:8080/ready").to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+		storage.replace_currencies_by_symbols(vec![]);
+		let req = test::TestRequest::get().uri("http://localhost:8080/ready").to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(resp.status(), http::StatusCode::OK);
+	}
+
 	#[tokio::test]
 	async fn test_currencies_post_empty() {
 		let storage = get_storage();
@@ -164,4 +486,67 @@ mod tests {
 
 		assert_eq!(r.len(), 0);
 	}
+
+	#[tokio::test]
+	async fn test_currencies_post_signed() {
+		let storage = get_storage();
+		let data = web::Data::from(storage.clone());
+		let signer = web::Data::new(
+			ResponseSigner::from_seed(
+				"0x0707070707070707070707070707070707070707070707070707070707070707",
+			)
+			.unwrap(),
+		);
+
+		let mut app = test::init_service(
+			App::new()
+				.app_data(data.clone())
+				.app_data(signer.clone())
+				.service(currencies_post),
+		)
+		.await;
+		let req = test::TestRequest::post()
+			.uri("http://localhost:8080/currencies")
+			.set_json(&vec![Currency { blockchain: "Bitcoin".into(), symbol: "BTC".into() }])
+			.to_request();
+
+		let resp = test::call_service(&mut app, req).await;
+
+		assert_eq!(resp.status(), http::StatusCode::OK);
+
+		let signature = resp.headers().get(SIGNATURE_HEADER).unwrap().to_str().unwrap().to_owned();
+		let body = test::read_body(resp).await;
+
+		assert_eq!(signature, signer.sign(&body));
+	}
+
+	#[tokio::test]
+	async fn test_currencies_post_scale() {
+		let storage = get_storage();
+		let data = web::Data::from(storage.clone());
+
+		let mut app =
+			test::init_service(App::new().app_data(data.clone()).service(currencies_post)).await;
+		let req = test::TestRequest::post()
+			.uri("http://localhost:8080/currencies")
+			.header(header::CONTENT_TYPE, SCALE_CONTENT_TYPE)
+			.header(header::ACCEPT, SCALE_CONTENT_TYPE)
+			.set_payload(
+				vec![Currency { blockchain: "Bitcoin".into(), symbol: "BTC".into() }].encode(),
+			)
+			.to_request();
+
+		let resp = test::call_service(&mut app, req).await;
+
+		assert_eq!(resp.status(), http::StatusCode::OK);
+		assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), SCALE_CONTENT_TYPE);
+
+		let body = test::read_body(resp).await;
+		let btc = storage.get_currencies_by_blockchains_and_symbols(vec![Currency {
+			blockchain: "Bitcoin".into(),
+			symbol: "BTC".into(),
+		}]);
+
+		assert_eq!(body, btc.encode());
+	}
 }
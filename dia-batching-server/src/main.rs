@@ -1,58 +1,374 @@
+use crate::aggregate::{Aggregate, Route};
+use crate::binance::Binance;
+use crate::coingecko::CoinGecko;
+use crate::dex::Dex;
 use crate::dia::Dia;
-use crate::handlers::currencies_post;
+use crate::fiat::Fiat;
+use crate::handlers::{
+	currencies_get, currencies_post, health, metrics, ready, ApiKeys, MaxUpdateAge,
+};
+use crate::price_updater::SupportedCurrencies;
+use crate::rate_limit::RateLimiter;
+use crate::redemption::Redemption;
+use crate::retry::{RetryPolicy, Retrying};
+use crate::signing::{ResponseSigner, SIGNATURE_HEADER};
+use crate::source::Source;
 use crate::storage::CoinInfoStorage;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::path::{Path, PathBuf};
 
 use crate::args::DiaApiArgs;
-use actix_web::{web, App, HttpServer};
-use log::error;
+use actix_cors::Cors;
+use actix_web::{middleware::Condition, web, App, HttpServer};
+use arc_swap::ArcSwapOption;
+use log::{error, info};
 use std::sync::Arc;
 use structopt::StructOpt;
+use tokio::signal::unix::{signal, SignalKind};
 
+mod aggregate;
 mod args;
+mod binance;
+mod chain_sync;
+mod coingecko;
+mod dex;
 mod dia;
+mod fiat;
 mod handlers;
+mod metrics;
 mod price_updater;
+mod rate_limit;
+mod redemption;
+mod retry;
+mod signing;
+mod source;
 mod storage;
+mod tls;
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct AssetSpecifier {
 	blockchain: String,
 	symbol: String,
 }
 
+impl AssetSpecifier {
+	/// Parses `<blockchain>:<symbol>`, rejecting empty names and extra separators
+	fn parse(asset: &str) -> Option<Self> {
+		let (blockchain, symbol) = asset.trim().split_once(':')?;
+		let (blockchain, symbol) = (blockchain.trim(), symbol.trim());
+		if blockchain.is_empty() || symbol.is_empty() || symbol.contains(':') {
+			return None
+		}
+		Some(AssetSpecifier { blockchain: blockchain.into(), symbol: symbol.into() })
+	}
+
+	/// Parses `<blockchain>:<symbol>=<name>`, mapping the asset to its name at another source
+	fn parse_mapping(mapping: &str) -> Option<(Self, String)> {
+		let (asset, name) = mapping.split_once('=')?;
+		let name = name.trim();
+		if name.is_empty() {
+			return None
+		}
+		Some((AssetSpecifier::parse(asset)?, name.into()))
+	}
+}
+
+/// Maps assets to their names at `source`, leaving out the malformed mappings
+fn asset_mappings(mappings: &[String], source: &str) -> HashMap<AssetSpecifier, String> {
+	mappings
+		.iter()
+		.filter_map(|mapping| {
+			AssetSpecifier::parse_mapping(mapping).or_else(|| {
+				error!("Invalid {} mapping '{}' – it needs the form <blockchain>:<symbol>=<name>", source, mapping);
+				None
+			})
+		})
+		.collect()
+}
+
+/// Parses the supported currencies, leaving out the malformed ones. Without any, every quotable
+/// asset is supported.
+fn parse_supported_currencies(assets: Vec<String>) -> Option<HashSet<AssetSpecifier>> {
+	Some(assets).filter(|assets| !assets.is_empty()).map(|assets| {
+		assets
+			.into_iter()
+			.filter_map(|asset| {
+				AssetSpecifier::parse(&asset).or_else(|| {
+					error!("Invalid asset '{}' – every asset needs to have the form <blockchain>:<symbol>", asset);
+					None
+				})
+			})
+			.collect()
+	})
+}
+
+/// Reads the supported currencies from a file with one <blockchain>:<symbol> per line, skipping
+/// blank lines and lines starting with `#`
+fn read_supported_currencies(
+	path: &Path,
+) -> Result<Option<HashSet<AssetSpecifier>>, Box<dyn Error + Send + Sync>> {
+	let content = std::fs::read_to_string(path)?;
+	Ok(parse_supported_currencies(
+		content
+			.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty() && !line.starts_with('#'))
+			.map(String::from)
+			.collect(),
+	))
+}
+
+/// Reads the supported currencies file again on every SIGHUP. The current currencies are kept if
+/// the file cannot be read.
+fn reload_on_sighup(
+	path: PathBuf,
+	supported_currencies: SupportedCurrencies,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+	let mut hangups = signal(SignalKind::hangup())?;
+	tokio::spawn(async move {
+		while hangups.recv().await.is_some() {
+			match read_supported_currencies(&path) {
+				Ok(currencies) => {
+					let count = currencies.as_ref().map_or(0, HashSet::len);
+					info!("Reloaded {} supported currencies from {}", count, path.display());
+					supported_currencies.store(currencies.map(Arc::new));
+				},
+				Err(e) => error!("Failed to reload {}: {}", path.display(), e),
+			}
+		}
+	});
+	Ok(())
+}
+
+/// Lets browsers on the given origins read the coin infos and their signature
+fn cors(allowed_origins: &[String]) -> Cors {
+	let cors = Cors::default()
+		.allowed_methods(vec!["GET", "POST"])
+		.allow_any_header()
+		.expose_headers(vec![SIGNATURE_HEADER])
+		.max_age(3600);
+	allowed_origins.iter().fold(cors, |cors, origin| match origin.as_str() {
+		"*" => cors.allow_any_origin(),
+		origin => cors.allowed_origin(origin),
+	})
+}
+
 #[actix_web::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
 	pretty_env_logger::init();
 
-	let args: DiaApiArgs = DiaApiArgs::from_args();
-	let storage = Arc::new(CoinInfoStorage::default());
+	let args = DiaApiArgs::from_args().settings()?;
+	let max_price_age = args.max_price_age_in_seconds.map(std::time::Duration::from_secs);
+	let storage = Arc::new(CoinInfoStorage::with_max_age(max_price_age));
+	if let Some(cache_file) = &args.cache_file {
+		match storage.load(cache_file) {
+			Ok(loaded) => info!("Loaded {} prices from {}", loaded, cache_file.display()),
+			Err(e) => error!("Failed to load the price cache from {}: {}", cache_file.display(), e),
+		}
+	}
 	let data = web::Data::from(storage.clone());
+	let signer = args.signing_seed.as_deref().map(ResponseSigner::from_seed).transpose()?;
+	if let Some(signer) = &signer {
+		info!("Signing responses with public key {}", signer.public_key());
+	}
+	let signer = signer.map(web::Data::new);
+	let max_update_age = std::time::Duration::from_secs(args.max_update_age_in_seconds);
+	let max_update_age = web::Data::new(MaxUpdateAge(max_update_age));
+	let api_keys = Some(args.api_keys.clone())
+		.filter(|keys| !keys.is_empty())
+		.map(|keys| web::Data::new(ApiKeys(keys)));
+	let rate_limiter = args.rate_limit.map(|(per_minute, burst)| {
+		web::Data::new(RateLimiter::new(per_minute, burst, args.trusted_proxies.clone()))
+	});
+	let tls_config = match &args.tls {
+		Some((cert, key)) => Some(tls::load_server_config(cert, key)?),
+		None => None,
+	};
+
+	// Shared by all requests, so that none can hang the price updates or the shutdown
+	let client = reqwest::Client::builder()
+		.timeout(std::time::Duration::from_secs(args.http_timeout_in_seconds))
+		.build()?;
+	let retry_policy = RetryPolicy { max_retries: args.max_retries, ..Default::default() };
+	let dia = Dia::new(client.clone());
+	let mut sources: Vec<Source> = vec![Box::new(Retrying::new(dia, retry_policy))];
+	let coingecko_ids = asset_mappings(&args.coingecko_ids, "CoinGecko");
+	if !coingecko_ids.is_empty() {
+		let coingecko = CoinGecko::new(coingecko_ids, client.clone());
+		sources.push(Box::new(Retrying::new(coingecko, retry_policy)));
+	}
+	let binance_symbols = asset_mappings(&args.binance_symbols, "Binance");
+	if !binance_symbols.is_empty() {
+		let binance = Binance::new(binance_symbols, client.clone());
+		sources.push(Box::new(Retrying::new(binance, retry_policy)));
+	}
+	let fiat_pairs: Vec<AssetSpecifier> = args
+		.fiat_pairs
+		.iter()
+		.filter_map(|pair| {
+			AssetSpecifier::parse(pair).or_else(|| {
+				error!("Invalid fiat pair '{}' – it needs the form Fiat:<base>-<quote>", pair);
+				None
+			})
+		})
+		.collect();
+	if !fiat_pairs.is_empty() {
+		let fiat = Fiat::new(fiat_pairs, client.clone());
+		sources.push(Box::new(Retrying::new(fiat, retry_policy)));
+	}
+	if !args.dex_pools.is_empty() {
+		let dex = Dex::new(args.dex_pools.clone(), storage.clone(), client.clone());
+		sources.push(Box::new(Retrying::new(dex, retry_policy)));
+	}
+	if !args.redemption_rates.is_empty() {
+		let redemption =
+			Redemption::new(args.redemption_rates.clone(), storage.clone(), client.clone());
+		sources.push(Box::new(Retrying::new(redemption, retry_policy)));
+	}
+	let routes = asset_mappings(&args.routes, "route")
+		.into_iter()
+		.map(|(asset, route)| {
+			let route = Route::parse(&route);
+			for name in route.names() {
+				if !sources.iter().any(|source| source.name() == name) {
+					error!("{:?} is routed to the unconfigured source '{}'", asset, name);
+				}
+			}
+			(asset, route)
+		})
+		.collect();
 
-	price_updater::run_update_prices_loop(
+	let supported_currencies = match &args.supported_currencies_file {
+		Some(path) => read_supported_currencies(path)?,
+		None => parse_supported_currencies(args.supported_currencies.clone().unwrap_or_default()),
+	};
+	let supported_currencies: SupportedCurrencies =
+		Arc::new(ArcSwapOption::from(supported_currencies.map(Arc::new)));
+	if let Some(path) = &args.supported_currencies_file {
+		reload_on_sighup(path.clone(), Arc::clone(&supported_currencies))?;
+	}
+	if let Some(rpc_url) = &args.chain_rpc_url {
+		let interval = std::time::Duration::from_secs(args.chain_sync_interval_in_seconds);
+		let currencies = chain_sync::wait_for_supported_currencies(&client, rpc_url).await;
+		info!("The chain supports {} currencies", currencies.len());
+		supported_currencies.store(Some(Arc::new(currencies)));
+		chain_sync::spawn(client, rpc_url.clone(), interval, Arc::clone(&supported_currencies));
+	}
+
+	let price_updates = price_updater::run_update_prices_loop(
 		storage,
-		args.supported_currencies.filter(|x| x.len() > 0).map(|curs| {
-			curs.into_iter()
-				.filter_map(|asset| {
-					let (blockchain, symbol) = asset.trim().split_once(":").or_else(|| {
-						error!("Invalid asset '{}' – every asset needs to have the form <blockchain>:<symbol>", asset);
-						None
-					})?;
-					Some(AssetSpecifier { blockchain: blockchain.into(), symbol: symbol.into() })
-				})
-				.collect()
-		}),
+		supported_currencies,
 		std::time::Duration::from_millis(args.request_timeout_in_milliseconds),
 		std::time::Duration::from_secs(args.iteration_timeout_in_seconds),
-		Dia,
+		args.allow_zero_prices,
+		args.cache_file.clone(),
+		Aggregate::new(sources, routes, args.aggregation, args.max_source_deviation_percent),
 	)
 	.await?;
 
-	HttpServer::new(move || App::new().app_data(data.clone()).service(currencies_post))
-		.on_connect(|_, _| println!("Serving Request"))
-		.bind("0.0.0.0:8070")?
-		.run()
-		.await?;
+	let cors_allowed_origins = args.cors_allowed_origins.clone();
+	let server = HttpServer::new(move || {
+		let app = App::new()
+			.wrap(Condition::new(!cors_allowed_origins.is_empty(), cors(&cors_allowed_origins)))
+			.app_data(data.clone())
+			.app_data(max_update_age.clone())
+			.service(health)
+			.service(ready)
+			.service(metrics);
+		let app = match &api_keys {
+			Some(api_keys) => app.app_data(api_keys.clone()),
+			None => app,
+		};
+		let app = match &rate_limiter {
+			Some(rate_limiter) => app.app_data(rate_limiter.clone()),
+			None => app,
+		};
+		match &signer {
+			Some(signer) => {
+				app.app_data(signer.clone()).service(currencies_post).service(currencies_get)
+			},
+			None => app.service(currencies_post).service(currencies_get),
+		}
+	})
+	.on_connect(|_, _| println!("Serving Request"));
+	let address = (args.listen_address.as_str(), args.port);
+	let server = match tls_config {
+		Some(tls_config) => server.bind_rustls(address, tls_config)?,
+		None => server.bind(address)?,
+	};
+	// Stops gracefully on SIGINT and SIGTERM, finishing the requests in flight
+	server.run().await?;
+	info!("HTTP server stopped, waiting for the running price update");
+	price_updates.stop().await;
 
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::handlers::health;
+	use actix_web::{http::header, test};
+
+	#[tokio::test]
+	async fn cors_should_allow_configured_origins() {
+		let mut app = test::init_service(
+			App::new().wrap(cors(&["https://dashboard.example".into()])).service(health),
+		)
+		.await;
+
+		let req = test::TestRequest::get()
+			.uri("/health")
+			.header(header::ORIGIN, "https://dashboard.example")
+			.to_request();
+		let resp = test::call_service(&mut app, req).await;
+		assert_eq!(
+			resp.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+			"https://dashboard.example"
+		);
+		assert_eq!(
+			resp.headers().get(header::ACCESS_CONTROL_EXPOSE_HEADERS).unwrap(),
+			SIGNATURE_HEADER
+		);
+	}
+
+	#[test]
+	fn asset_specifier_should_reject_malformed_pairs() {
+		assert_eq!(
+			AssetSpecifier::parse(" Bitcoin:BTC "),
+			Some(AssetSpecifier { blockchain: "Bitcoin".into(), symbol: "BTC".into() })
+		);
+		assert_eq!(AssetSpecifier::parse("BTC"), None);
+		assert_eq!(AssetSpecifier::parse("Bitcoin:"), None);
+		assert_eq!(AssetSpecifier::parse(":BTC"), None);
+		assert_eq!(AssetSpecifier::parse("Bitcoin:BTC:USD"), None);
+	}
+
+	#[test]
+	fn supported_currencies_should_be_read_line_by_line() {
+		let path = std::env::temp_dir().join(format!("dia-currencies-{}.txt", std::process::id()));
+		std::fs::write(&path, "# Stablecoins\nEthereum:USDT\n\n  Bitcoin:BTC  \nBTC\n").unwrap();
+
+		let supported = read_supported_currencies(&path).unwrap().unwrap();
+		std::fs::remove_file(&path).unwrap();
+
+		assert_eq!(supported.len(), 2);
+		assert!(supported
+			.contains(&AssetSpecifier { blockchain: "Bitcoin".into(), symbol: "BTC".into() }));
+	}
+
+	#[test]
+	fn asset_specifier_should_parse_mappings() {
+		assert_eq!(
+			AssetSpecifier::parse_mapping("Polkadot:DOT=polkadot"),
+			Some((
+				AssetSpecifier { blockchain: "Polkadot".into(), symbol: "DOT".into() },
+				"polkadot".into()
+			))
+		);
+		assert_eq!(AssetSpecifier::parse_mapping("Polkadot:DOT"), None);
+		assert_eq!(AssetSpecifier::parse_mapping("Polkadot:DOT="), None);
+		assert_eq!(AssetSpecifier::parse_mapping("DOT=polkadot"), None);
+	}
+}
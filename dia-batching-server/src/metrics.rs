@@ -0,0 +1,157 @@
+use crate::storage::CoinInfoStorage;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Counters of the batching server, rendered in the Prometheus text format on `/metrics`
+pub static METRICS: Metrics = Metrics::new();
+
+#[derive(Debug)]
+pub struct Metrics {
+	source_requests: AtomicU64,
+	source_request_failures: AtomicU64,
+	source_request_duration_micros: AtomicU64,
+	served_requests: AtomicU64,
+}
+
+impl Metrics {
+	const fn new() -> Self {
+		Metrics {
+			source_requests: AtomicU64::new(0),
+			source_request_failures: AtomicU64::new(0),
+			source_request_duration_micros: AtomicU64::new(0),
+			served_requests: AtomicU64::new(0),
+		}
+	}
+
+	/// Records a request to the configured price sources that took `duration`
+	pub fn observe_source_request(&self, duration: Duration, failed: bool) {
+		self.source_requests.fetch_add(1, Ordering::Relaxed);
+		self.source_request_duration_micros
+			.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+		if failed {
+			self.source_request_failures.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	/// Records a coin info request answered by the server
+	pub fn inc_served_requests(&self) {
+		self.served_requests.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Renders the counters together with the gauges read from `storage`
+	pub fn render(&self, storage: &CoinInfoStorage) -> String {
+		let coin_infos = storage.get_all_currencies();
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+		let mut out = String::new();
+
+		let _ = writeln!(out, "# HELP source_requests_total Requests sent to the price sources");
+		let _ = writeln!(out, "# TYPE source_requests_total counter");
+		let _ =
+			writeln!(out, "source_requests_total {}", self.source_requests.load(Ordering::Relaxed));
+
+		let _ = writeln!(
+			out,
+			"# HELP source_request_failures_total Failed requests to the price sources"
+		);
+		let _ = writeln!(out, "# TYPE source_request_failures_total counter");
+		let _ = writeln!(
+			out,
+			"source_request_failures_total {}",
+			self.source_request_failures.load(Ordering::Relaxed)
+		);
+
+		let _ =
+			writeln!(out, "# HELP source_request_duration_seconds Latency of the price sources");
+		let _ = writeln!(out, "# TYPE source_request_duration_seconds summary");
+		let _ = writeln!(
+			out,
+			"source_request_duration_seconds_sum {}",
+			self.source_request_duration_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+		);
+		let _ = writeln!(
+			out,
+			"source_request_duration_seconds_count {}",
+			self.source_requests.load(Ordering::Relaxed)
+		);
+
+		let _ = writeln!(out, "# HELP served_requests_total Coin info requests answered");
+		let _ = writeln!(out, "# TYPE served_requests_total counter");
+		let _ =
+			writeln!(out, "served_requests_total {}", self.served_requests.load(Ordering::Relaxed));
+
+		let _ = writeln!(out, "# HELP cached_coin_infos Number of cached coin infos");
+		let _ = writeln!(out, "# TYPE cached_coin_infos gauge");
+		let _ = writeln!(out, "cached_coin_infos {}", coin_infos.len());
+
+		let _ = writeln!(out, "# HELP coin_info_age_seconds Age of the cached price per asset");
+		let _ = writeln!(out, "# TYPE coin_info_age_seconds gauge");
+		for coin_info in &coin_infos {
+			let _ = writeln!(
+				out,
+				"coin_info_age_seconds{{blockchain=\"{}\",symbol=\"{}\"}} {}",
+				escape_label(&coin_info.blockchain),
+				escape_label(&coin_info.symbol),
+				now.saturating_sub(coin_info.last_update_timestamp)
+			);
+		}
+
+		let _ = writeln!(
+			out,
+			"# HELP quotation_consecutive_failures Failed fetches in a row of an asset's quotation"
+		);
+		let _ = writeln!(out, "# TYPE quotation_consecutive_failures gauge");
+		for ((blockchain, symbol), failures) in storage.consecutive_failures() {
+			let _ = writeln!(
+				out,
+				"quotation_consecutive_failures{{blockchain=\"{}\",symbol=\"{}\"}} {}",
+				escape_label(&blockchain),
+				escape_label(&symbol),
+				failures
+			);
+		}
+
+		out
+	}
+}
+
+fn escape_label(value: &str) -> String {
+	value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::storage::CoinInfo;
+
+	#[test]
+	fn render_should_list_counters_and_asset_ages() {
+		let metrics = Metrics::new();
+		metrics.observe_source_request(Duration::from_millis(1_500), false);
+		metrics.observe_source_request(Duration::from_millis(500), true);
+		metrics.inc_served_requests();
+
+		let storage = CoinInfoStorage::default();
+		storage.upsert_currencies(
+			vec![CoinInfo {
+				symbol: "BTC".into(),
+				blockchain: "Bit\"coin".into(),
+				..Default::default()
+			}],
+			vec![("Ethereum".into(), "ETH".into())],
+		);
+
+		let rendered = metrics.render(&storage);
+
+		assert!(rendered.contains("source_requests_total 2\n"));
+		assert!(rendered.contains("source_request_failures_total 1\n"));
+		assert!(rendered.contains("source_request_duration_seconds_sum 2\n"));
+		assert!(rendered.contains("served_requests_total 1\n"));
+		assert!(rendered.contains("cached_coin_infos 1\n"));
+		assert!(rendered
+			.contains("coin_info_age_seconds{blockchain=\"Bit\\\"coin\",symbol=\"BTC\"}"));
+		assert!(rendered.contains(
+			"quotation_consecutive_failures{blockchain=\"Ethereum\",symbol=\"ETH\"} 1\n"
+		));
+	}
+}
@@ -1,44 +1,175 @@
-use crate::dia::{DiaApi, Quotation};
+use crate::dia::{Quotation, QuotedAsset};
+use crate::metrics::METRICS;
+use crate::source::PriceSource;
 use crate::storage::{CoinInfo, CoinInfoStorage};
 use crate::AssetSpecifier;
+use arc_swap::ArcSwapOption;
 use log::{error, info};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use std::{error::Error, sync::Arc};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Handle of the spawned price update loop
+pub struct PriceUpdateLoop {
+	shutdown: watch::Sender<bool>,
+	handle: JoinHandle<()>,
+}
+
+impl PriceUpdateLoop {
+	/// Lets a running update iteration complete, then ends the loop
+	pub async fn stop(self) {
+		let _ = self.shutdown.broadcast(true);
+		if let Err(e) = self.handle.await {
+			error!("Price update loop failed: {}", e);
+		}
+	}
+}
+
+/// Age after which the asset index is rebuilt to pick up changed addresses
+const ASSET_INDEX_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+/// Age after which the asset index is rebuilt when a supported currency is missing from it
+const ASSET_INDEX_MISSING_RETRY: Duration = Duration::from_secs(60 * 60);
+
+/// Quotable assets by blockchain and symbol, so that the supported currencies are quoted without
+/// enumerating every quotable asset of DIA on each iteration
+#[derive(Debug, Default)]
+struct AssetIndex {
+	assets: HashMap<AssetSpecifier, QuotedAsset>,
+	refreshed_at: Option<Instant>,
+}
+
+impl AssetIndex {
+	/// Looks up the supported currencies, rebuilding the index first if it is outdated
+	async fn resolve<T>(
+		&mut self,
+		supported: &HashSet<AssetSpecifier>,
+		api: &T,
+	) -> Result<Vec<QuotedAsset>, Box<dyn Error + Send + Sync>>
+	where
+		T: PriceSource + Send + Sync + 'static,
+	{
+		let missing = supported.iter().any(|asset| !self.assets.contains_key(asset));
+		let max_age = if missing { ASSET_INDEX_MISSING_RETRY } else { ASSET_INDEX_MAX_AGE };
+		if self.refreshed_at.map_or(true, |refreshed_at| refreshed_at.elapsed() > max_age) {
+			self.assets = get_quotable_assets(api)
+				.await?
+				.into_iter()
+				.map(|quoted_asset| {
+					let asset = AssetSpecifier {
+						blockchain: quoted_asset.asset.blockchain.clone(),
+						symbol: quoted_asset.asset.symbol.clone(),
+					};
+					(asset, quoted_asset)
+				})
+				.collect();
+			self.refreshed_at = Some(Instant::now());
+			info!("No. of quotable assets indexed : {}", self.assets.len());
+		}
+
+		Ok(supported
+			.iter()
+			.filter_map(|asset| {
+				let quoted_asset = self.assets.get(asset).cloned();
+				if quoted_asset.is_none() {
+					error!("{:?} is not quotable by DIA", asset);
+				}
+				quoted_asset
+			})
+			.collect())
+	}
+}
+
+/// Supported currencies shared with the price update loop, which picks up replaced ones on its next
+/// iteration. Every quotable asset is fetched if there are none.
+pub type SupportedCurrencies = Arc<ArcSwapOption<HashSet<AssetSpecifier>>>;
 
 pub async fn run_update_prices_loop<T>(
 	storage: Arc<CoinInfoStorage>,
-	maybe_supported_currencies: Option<HashSet<AssetSpecifier>>,
+	supported_currencies: SupportedCurrencies,
 	rate: std::time::Duration,
 	duration: std::time::Duration,
+	allow_zero_prices: bool,
+	cache_file: Option<PathBuf>,
 	api: T,
-) -> Result<(), Box<dyn Error + Send + Sync + 'static>>
+) -> Result<PriceUpdateLoop, Box<dyn Error + Send + Sync + 'static>>
 where
-	T: DiaApi + Send + Sync + 'static,
+	T: PriceSource + Send + Sync + 'static,
 {
 	let coins = Arc::clone(&storage);
-	let _ = tokio::spawn(async move {
+	let (shutdown, mut shutdown_requested) = watch::channel(false);
+	let handle = tokio::spawn(async move {
+		// The first `recv` returns the initial value at once
+		let _ = shutdown_requested.recv().await;
+		let mut asset_index = AssetIndex::default();
 		loop {
 			let time_elapsed = std::time::Instant::now();
 
-			let coins = Arc::clone(&coins);
-
-			update_prices(coins, &maybe_supported_currencies, &api, rate).await;
+			update_prices(
+				Arc::clone(&coins),
+				supported_currencies.load_full().as_deref(),
+				&mut asset_index,
+				&api,
+				rate,
+				allow_zero_prices,
+			)
+			.await;
+			if let Some(cache_file) = &cache_file {
+				if let Err(e) = coins.save(cache_file) {
+					error!("Failed to save the price cache to {}: {}", cache_file.display(), e);
+				}
+			}
 
-			tokio::time::delay_for(duration.saturating_sub(time_elapsed.elapsed())).await;
+			if *shutdown_requested.borrow() {
+				break
+			}
+			let delay = tokio::time::delay_for(duration.saturating_sub(time_elapsed.elapsed()));
+			let stop = tokio::select! {
+				_ = delay => false,
+				requested = shutdown_requested.recv() => requested.unwrap_or(true),
+			};
+			if stop {
+				break
+			}
 		}
+		info!("Price updates stopped");
 	});
 
-	Ok(())
+	Ok(PriceUpdateLoop { shutdown, handle })
 }
 
-fn convert_to_coin_info(value: Quotation) -> Result<CoinInfo, Box<dyn Error + Sync + Send>> {
-	let Quotation { name, symbol, blockchain, price, time, volume_yesterday, .. } = value;
+fn convert_to_coin_info(
+	value: Quotation,
+	allow_zero_prices: bool,
+) -> Result<CoinInfo, Box<dyn Error + Sync + Send>> {
+	let Quotation {
+		name,
+		symbol,
+		blockchain,
+		price,
+		price_yesterday,
+		time,
+		volume_yesterday,
+		market_cap,
+		high_24h,
+		low_24h,
+		..
+	} = value;
 
 	let price = convert_decimal_to_u128(&price)?;
+	if price == 0 && !allow_zero_prices {
+		return Err(ConvertingError::ZeroPrice.into())
+	}
 	let supply = convert_decimal_to_u128(&volume_yesterday)?;
+	let price_yesterday = convert_decimal_to_u128(&price_yesterday)?;
+	let market_cap = market_cap.as_ref().map(convert_decimal_to_u128).transpose()?;
+	let high_24h = high_24h.as_ref().map(convert_decimal_to_u128).transpose()?;
+	let low_24h = low_24h.as_ref().map(convert_decimal_to_u128).transpose()?;
 
 	let coin_info = CoinInfo {
 		name: name.into(),
@@ -47,6 +178,11 @@ fn convert_to_coin_info(value: Quotation) -> Result<CoinInfo, Box<dyn Error + Sy
 		price,
 		last_update_timestamp: time.timestamp().unsigned_abs(),
 		supply,
+		price_yesterday,
+		volume_yesterday_usd: supply,
+		market_cap,
+		high_24h,
+		low_24h,
 	};
 
 	info!("Coin Price: {:#?}", price);
@@ -56,54 +192,85 @@ fn convert_to_coin_info(value: Quotation) -> Result<CoinInfo, Box<dyn Error + Sy
 	Ok(coin_info)
 }
 
+async fn get_quotable_assets<T>(api: &T) -> Result<Vec<QuotedAsset>, Box<dyn Error + Send + Sync>>
+where
+	T: PriceSource + Send + Sync + 'static,
+{
+	let started = Instant::now();
+	let quotable_assets = api.get_quotable_assets().await;
+	METRICS.observe_source_request(started.elapsed(), quotable_assets.is_err());
+	quotable_assets
+}
+
+/// Quotes the supported currencies through the asset index, or every quotable asset if there is
+/// no list of supported currencies
 async fn update_prices<T>(
 	coins: Arc<CoinInfoStorage>,
-	maybe_supported_currencies: &Option<HashSet<AssetSpecifier>>,
+	maybe_supported_currencies: Option<&HashSet<AssetSpecifier>>,
+	asset_index: &mut AssetIndex,
 	api: &T,
 	rate: std::time::Duration,
+	allow_zero_prices: bool,
 ) where
-	T: DiaApi + Send + Sync + 'static,
+	T: PriceSource + Send + Sync + 'static,
 {
-	if let Ok(quotable_assets) = api.get_quotable_assets().await {
-		info!("No. of quotable assets to retrieve : {}", quotable_assets.len());
+	let quotable_assets = match maybe_supported_currencies {
+		Some(supported) => asset_index.resolve(supported, api).await,
+		None => get_quotable_assets(api).await,
+	};
 
-		let mut currencies = vec![];
+	match quotable_assets {
+		Ok(quotable_assets) => {
+			info!("No. of quotable assets to retrieve : {}", quotable_assets.len());
 
-		for quotable_asset in quotable_assets {
-			let asset = AssetSpecifier {
-				blockchain: quotable_asset.asset.blockchain.clone(),
-				symbol: quotable_asset.asset.symbol.clone(),
-			};
+			let mut currencies = vec![];
+			let mut failed = vec![];
 
-			if maybe_supported_currencies
-				.as_ref()
-				.map_or(true, |supported| supported.contains(&asset))
-			{
-				match api.get_quotation(&quotable_asset).await.and_then(convert_to_coin_info) {
+			for quotable_asset in quotable_assets {
+				let started = Instant::now();
+				let quotation = api.get_quotation(&quotable_asset).await;
+				METRICS.observe_source_request(started.elapsed(), quotation.is_err());
+
+				match quotation
+					.and_then(|quotation| convert_to_coin_info(quotation, allow_zero_prices))
+				{
 					Ok(coin_info) => {
 						currencies.push(coin_info);
 					},
 					Err(err) => {
-						error!("Error while retrieving quotation for {:?}: {}", quotable_asset, err)
+						error!(
+							"Error while retrieving quotation for {:?}: {}",
+							quotable_asset, err
+						);
+						let QuotedAsset { asset, .. } = quotable_asset;
+						failed.push((asset.blockchain.into(), asset.symbol.into()));
 					},
 				}
 				tokio::time::delay_for(rate).await;
 			}
-		}
 
-		coins.replace_currencies_by_symbols(currencies);
-		info!("Currencies Updated");
+			coins.upsert_currencies(currencies, failed);
+			if let Some(supported) = maybe_supported_currencies {
+				coins.retain_supported(supported);
+			}
+			info!("Currencies Updated");
+		},
+		Err(err) => error!("Error while retrieving quotable assets: {}", err),
 	}
 }
 #[derive(Debug)]
 pub enum ConvertingError {
 	DecimalTooLarge,
+	NegativeValue,
+	ZeroPrice,
 }
 
 impl Display for ConvertingError {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		match self {
 			ConvertingError::DecimalTooLarge => write!(f, "Decimal given is too large"),
+			ConvertingError::NegativeValue => write!(f, "Decimal given is negative"),
+			ConvertingError::ZeroPrice => write!(f, "Price is zero"),
 		}
 	}
 }
@@ -111,6 +278,9 @@ impl Display for ConvertingError {
 impl Error for ConvertingError {}
 
 fn convert_decimal_to_u128(input: &Decimal) -> Result<u128, ConvertingError> {
+	if input.is_sign_negative() && !input.is_zero() {
+		return Err(ConvertingError::NegativeValue)
+	}
 	let fract = (input.fract() * Decimal::from(1_000_000_000_000_u128))
 		.to_u128()
 		.ok_or(ConvertingError::DecimalTooLarge)?;
@@ -127,7 +297,12 @@ mod tests {
 		dia::{Asset, QuotedAsset},
 		handlers::Currency,
 	};
-	use std::{collections::HashMap, error::Error, sync::Arc};
+	use std::{
+		collections::HashMap,
+		error::Error,
+		sync::atomic::{AtomicUsize, Ordering},
+		sync::Arc,
+	};
 
 	use async_trait::async_trait;
 	use chrono::Utc;
@@ -137,6 +312,7 @@ mod tests {
 
 	struct MockDia {
 		quotation: HashMap<AssetSpecifier, Quotation>,
+		quotable_asset_requests: AtomicUsize,
 	}
 
 	impl MockDia {
@@ -154,6 +330,9 @@ mod tests {
 					address: "0x0000000000000000000000000000000000000000".into(),
 					blockchain: "Bitcoin".into(),
 					source: "diadata.org".into(),
+					market_cap: Some(dec!(320000000000)),
+					high_24h: Some(dec!(1.1)),
+					low_24h: Some(dec!(0.9)),
 				},
 			);
 			quotation.insert(
@@ -168,6 +347,9 @@ mod tests {
 					address: "0x0000000000000000000000000000000000000000".into(),
 					blockchain: "Ethereum".into(),
 					source: "diadata.org".into(),
+					market_cap: None,
+					high_24h: None,
+					low_24h: None,
 				},
 			);
 			quotation.insert(
@@ -182,6 +364,9 @@ mod tests {
 					address: "0x0000000000000000000000000000000000000000".into(),
 					blockchain: "Ethereum".into(),
 					source: "diadata.org".into(),
+					market_cap: None,
+					high_24h: None,
+					low_24h: None,
 				},
 			);
 			quotation.insert(
@@ -196,14 +381,21 @@ mod tests {
 					address: "0x0000000000000000000000000000000000000000".into(),
 					blockchain: "Ethereum".into(),
 					source: "diadata.org".into(),
+					market_cap: None,
+					high_24h: None,
+					low_24h: None,
 				},
 			);
-			Self { quotation }
+			Self { quotation, quotable_asset_requests: AtomicUsize::new(0) }
 		}
 	}
 
 	#[async_trait]
-	impl DiaApi for MockDia {
+	impl PriceSource for MockDia {
+		fn name(&self) -> &str {
+			"mock"
+		}
+
 		async fn get_quotation(
 			&self,
 			asset: &QuotedAsset,
@@ -219,6 +411,7 @@ mod tests {
 		async fn get_quotable_assets(
 			&self,
 		) -> Result<Vec<QuotedAsset>, Box<dyn Error + Send + Sync>> {
+			self.quotable_asset_requests.fetch_add(1, Ordering::Relaxed);
 			Ok(vec![
 				QuotedAsset {
 					asset: Asset {
@@ -269,7 +462,15 @@ mod tests {
 		let storage = Arc::new(CoinInfoStorage::default());
 		let coins = Arc::clone(&storage);
 		let all_currencies = None;
-		update_prices(coins, &all_currencies, &mock_api, std::time::Duration::from_secs(1)).await;
+		update_prices(
+			coins,
+			all_currencies.as_ref(),
+			&mut AssetIndex::default(),
+			&mock_api,
+			std::time::Duration::from_secs(1),
+			false,
+		)
+		.await;
 
 		let c = storage.get_currencies_by_blockchains_and_symbols(vec![
 			Currency { blockchain: "Bitcoin".into(), symbol: "BTC".into() },
@@ -285,13 +486,71 @@ mod tests {
 		assert_eq!(c[1].name, "ETH");
 	}
 
+	#[tokio::test]
+	async fn test_update_prices_supported_reuses_asset_index() {
+		let mock_api = MockDia::new();
+		let storage = Arc::new(CoinInfoStorage::default());
+		let supported = Some(
+			vec![
+				AssetSpecifier { blockchain: "Bitcoin".into(), symbol: "BTC".into() },
+				AssetSpecifier { blockchain: "Ethereum".into(), symbol: "USDT".into() },
+			]
+			.into_iter()
+			.collect(),
+		);
+		let mut asset_index = AssetIndex::default();
+
+		for _ in 0..2 {
+			update_prices(
+				Arc::clone(&storage),
+				supported.as_ref(),
+				&mut asset_index,
+				&mock_api,
+				std::time::Duration::from_millis(1),
+				false,
+			)
+			.await;
+		}
+
+		assert_eq!(mock_api.quotable_asset_requests.load(Ordering::Relaxed), 1);
+		let c = storage.get_all_currencies();
+		assert_eq!(c.len(), 2);
+		assert_eq!(c[0].symbol, "BTC");
+		assert_eq!(c[1].symbol, "USDT");
+
+		let supported = vec![AssetSpecifier { blockchain: "Bitcoin".into(), symbol: "BTC".into() }]
+			.into_iter()
+			.collect();
+		update_prices(
+			Arc::clone(&storage),
+			Some(&supported),
+			&mut asset_index,
+			&mock_api,
+			std::time::Duration::from_millis(1),
+			false,
+		)
+		.await;
+
+		let c = storage.get_all_currencies();
+		assert_eq!(c.len(), 1);
+		assert_eq!(c[0].symbol, "BTC");
+	}
+
 	#[tokio::test]
 	async fn test_update_prices_non_existent() {
 		let mock_api = MockDia::new();
 		let storage = Arc::new(CoinInfoStorage::default());
 		let coins = Arc::clone(&storage);
 		let all_currencies = None;
-		update_prices(coins, &all_currencies, &mock_api, std::time::Duration::from_secs(1)).await;
+		update_prices(
+			coins,
+			all_currencies.as_ref(),
+			&mut AssetIndex::default(),
+			&mock_api,
+			std::time::Duration::from_secs(1),
+			false,
+		)
+		.await;
 
 		let c = storage.get_currencies_by_blockchains_and_symbols(vec![
 			Currency { blockchain: "Bitcoin".into(), symbol: "BTCCash".into() },
@@ -301,13 +560,42 @@ mod tests {
 		assert_eq!(0, c.len());
 	}
 
+	#[tokio::test]
+	async fn test_update_prices_loop_stop() {
+		let storage = Arc::new(CoinInfoStorage::default());
+		let price_updates = run_update_prices_loop(
+			Arc::clone(&storage),
+			Default::default(),
+			std::time::Duration::from_millis(1),
+			std::time::Duration::from_secs(3600),
+			false,
+			None,
+			MockDia::new(),
+		)
+		.await
+		.unwrap();
+
+		price_updates.stop().await;
+
+		assert!(storage.last_update().is_some());
+		assert_eq!(storage.get_all_currencies().len(), 4);
+	}
+
 	#[tokio::test]
 	async fn test_update_prices_one_available() {
 		let mock_api = MockDia::new();
 		let storage = Arc::new(CoinInfoStorage::default());
 		let coins = Arc::clone(&storage);
 		let all_currencies = None;
-		update_prices(coins, &all_currencies, &mock_api, std::time::Duration::from_secs(1)).await;
+		update_prices(
+			coins,
+			all_currencies.as_ref(),
+			&mut AssetIndex::default(),
+			&mock_api,
+			std::time::Duration::from_secs(1),
+			false,
+		)
+		.await;
 
 		let c = storage.get_currencies_by_blockchains_and_symbols(vec![
 			Currency { blockchain: "Bitcoin".into(), symbol: "BTC".into() },
@@ -327,7 +615,15 @@ mod tests {
 		let storage = Arc::new(CoinInfoStorage::default());
 		let coins = Arc::clone(&storage);
 		let all_currencies = None;
-		update_prices(coins, &all_currencies, &mock_api, std::time::Duration::from_secs(1)).await;
+		update_prices(
+			coins,
+			all_currencies.as_ref(),
+			&mut AssetIndex::default(),
+			&mock_api,
+			std::time::Duration::from_secs(1),
+			false,
+		)
+		.await;
 
 		let c = storage.get_currencies_by_blockchains_and_symbols(vec![]);
 
@@ -341,7 +637,15 @@ mod tests {
 		let coins = Arc::clone(&storage);
 		let all_currencies = None;
 
-		update_prices(coins, &all_currencies, &mock_api, std::time::Duration::from_secs(1)).await;
+		update_prices(
+			coins,
+			all_currencies.as_ref(),
+			&mut AssetIndex::default(),
+			&mock_api,
+			std::time::Duration::from_secs(1),
+			false,
+		)
+		.await;
 
 		let c = storage.get_currencies_by_blockchains_and_symbols(vec![Currency {
 			blockchain: "Bitcoin".into(),
@@ -358,7 +662,15 @@ mod tests {
 		let coins = Arc::clone(&storage);
 		let all_currencies = None;
 
-		update_prices(coins, &all_currencies, &mock_api, std::time::Duration::from_secs(1)).await;
+		update_prices(
+			coins,
+			all_currencies.as_ref(),
+			&mut AssetIndex::default(),
+			&mock_api,
+			std::time::Duration::from_secs(1),
+			false,
+		)
+		.await;
 
 		let c = storage.get_currencies_by_blockchains_and_symbols(vec![
 			Currency { blockchain: "Bitcoin".into(), symbol: "BTC".into() },
@@ -368,9 +680,14 @@ mod tests {
 
 		assert_eq!(c[0].price, 1000000000000);
 		assert_eq!(c[0].supply, 123456789012);
+		assert_eq!(c[0].market_cap, Some(320000000000000000000000));
+		assert_eq!((c[0].high_24h, c[0].low_24h), (Some(1100000000000), Some(900000000000)));
 
 		assert_eq!(c[1].price, 123456789123456789012);
 		assert_eq!(c[1].supply, 298134760000000000000);
+		assert_eq!(c[1].price_yesterday, 1000000000000);
+		assert_eq!(c[1].volume_yesterday_usd, 298134760000000000000);
+		assert_eq!(c[1].market_cap, None);
 
 		assert_eq!(c[2].price, 1000000000001);
 		assert_eq!(c[2].supply, 1);
@@ -379,4 +696,23 @@ mod tests {
 		assert_eq!(c[1].name, "USDC");
 		assert_eq!(c[2].name, "USDT");
 	}
+
+	#[test]
+	fn test_convert_zero_price() {
+		let quotation = Quotation { price: dec!(0), ..Default::default() };
+
+		assert!(convert_to_coin_info(quotation.clone(), false).is_err());
+		assert_eq!(convert_to_coin_info(quotation, true).unwrap().price, 0);
+	}
+
+	#[test]
+	fn test_convert_negative_price() {
+		let quotation = Quotation { price: dec!(-1.5), ..Default::default() };
+
+		assert!(convert_to_coin_info(quotation.clone(), true).is_err());
+		assert!(matches!(
+			convert_decimal_to_u128(&dec!(-1.5)),
+			Err(ConvertingError::NegativeValue)
+		));
+	}
 }
@@ -0,0 +1,142 @@
+use actix_web::error::InternalError;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Header reverse proxies append the address of their peer to
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Number of tracked clients above which the buckets of clients at full burst are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Token bucket per client IP, refilled with `per_minute` requests per minute up to `burst`
+#[derive(Debug)]
+pub struct RateLimiter {
+	per_minute: u32,
+	burst: u32,
+	/// Reverse proxies whose `X-Forwarded-For` header names the client
+	trusted_proxies: Vec<IpAddr>,
+	buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+	tokens: f64,
+	refilled_at: Instant,
+}
+
+impl RateLimiter {
+	pub fn new(per_minute: u32, burst: u32, trusted_proxies: Vec<IpAddr>) -> Self {
+		RateLimiter {
+			per_minute,
+			burst: burst.max(1),
+			trusted_proxies,
+			buckets: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Rejects the request with 429 and a `Retry-After` header once its client is out of tokens
+	pub fn limit(&self, req: &HttpRequest) -> Result<(), actix_web::Error> {
+		let peer = match req.peer_addr() {
+			Some(address) => address.ip(),
+			None => return Ok(()),
+		};
+		let forwarded_for: Vec<&str> = req
+			.headers()
+			.get_all(X_FORWARDED_FOR)
+			.filter_map(|value| value.to_str().ok())
+			.collect();
+		let ip = self.client_ip(peer, &forwarded_for.join(","));
+		self.acquire(ip, Instant::now()).map_err(|retry_after| {
+			let response = HttpResponse::TooManyRequests()
+				.header(header::RETRY_AFTER, retry_after.as_secs().max(1).to_string())
+				.finish();
+			InternalError::from_response("Too many requests", response).into()
+		})
+	}
+
+	/// The client behind a trusted proxy is the last address of `X-Forwarded-For` that is not a
+	/// trusted proxy itself, as the addresses before it could have been sent by the client. Any
+	/// other peer is the client.
+	fn client_ip(&self, peer: IpAddr, forwarded_for: &str) -> IpAddr {
+		let mut client = peer;
+		if !self.trusted_proxies.contains(&client) {
+			return client
+		}
+		for address in forwarded_for.rsplit(',') {
+			match address.trim().parse() {
+				Ok(address) => client = address,
+				Err(_) => break,
+			}
+			if !self.trusted_proxies.contains(&client) {
+				break
+			}
+		}
+		client
+	}
+
+	/// Takes a token of the client, or returns how long it has to wait for the next one
+	fn acquire(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+		let per_second = f64::from(self.per_minute) / 60.0;
+		let burst = f64::from(self.burst);
+		let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+		if buckets.len() >= MAX_TRACKED_CLIENTS {
+			buckets.retain(|_, bucket| {
+				bucket.tokens + now.duration_since(bucket.refilled_at).as_secs_f64() * per_second <
+					burst
+			});
+		}
+
+		let bucket = buckets.entry(ip).or_insert(Bucket { tokens: burst, refilled_at: now });
+		let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+		bucket.tokens = (bucket.tokens + elapsed * per_second).min(burst);
+		bucket.refilled_at = now;
+
+		if bucket.tokens >= 1.0 {
+			bucket.tokens -= 1.0;
+			Ok(())
+		} else if per_second > 0.0 {
+			Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+		} else {
+			Err(Duration::from_secs(60))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn acquire_should_allow_the_burst_then_refill() {
+		let limiter = RateLimiter::new(60, 2, vec![]);
+		let client = IpAddr::from([127, 0, 0, 1]);
+		let other = IpAddr::from([127, 0, 0, 2]);
+		let start = Instant::now();
+
+		assert!(limiter.acquire(client, start).is_ok());
+		assert!(limiter.acquire(client, start).is_ok());
+		assert_eq!(limiter.acquire(client, start), Err(Duration::from_secs(1)));
+		assert!(limiter.acquire(other, start).is_ok());
+
+		assert!(limiter.acquire(client, start + Duration::from_secs(1)).is_ok());
+		assert!(limiter.acquire(client, start + Duration::from_secs(1)).is_err());
+	}
+
+	#[test]
+	fn client_ip_should_only_follow_trusted_proxies() {
+		let proxy = IpAddr::from([10, 0, 0, 1]);
+		let client = IpAddr::from([203, 0, 113, 7]);
+		let limiter = RateLimiter::new(60, 2, vec![proxy]);
+
+		assert_eq!(limiter.client_ip(proxy, "203.0.113.7"), client);
+		assert_eq!(limiter.client_ip(proxy, "198.51.100.1, 203.0.113.7, 10.0.0.1"), client);
+		assert_eq!(limiter.client_ip(proxy, ""), proxy);
+		assert_eq!(limiter.client_ip(proxy, "unknown, 10.0.0.1"), proxy);
+		assert_eq!(limiter.client_ip(client, "198.51.100.1"), client);
+	}
+}
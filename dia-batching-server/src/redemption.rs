@@ -0,0 +1,158 @@
+use crate::dex::{cached_price, eth_call, to_decimal};
+use crate::dia::{Asset, Quotation, QuotedAsset};
+use crate::source::PriceSource;
+use crate::storage::CoinInfoStorage;
+use crate::AssetSpecifier;
+use async_trait::async_trait;
+use chrono::prelude::*;
+use log::error;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::error;
+use std::sync::Arc;
+
+/// A liquid-staking derivative priced from the rate at which it redeems for its underlying asset,
+/// from the config file:
+/// ```toml
+/// [[redemption-rates]]
+/// asset = "Moonbeam:xcvDOT"
+/// underlying = "Polkadot:DOT"
+/// rpc-url = "https://rpc.api.moonbeam.network"
+/// contract = "0x0000000000000000000000000000000000000000"
+/// data = "0x3ba0b9a9"
+/// rate-decimals = 18
+/// ```
+/// `data` is the calldata of the contract's view returning the amount of the underlying asset one
+/// derivative token redeems for, scaled by `rate-decimals`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RedemptionRate {
+	pub asset: String,
+	pub underlying: String,
+	pub rpc_url: String,
+	pub contract: String,
+	pub data: String,
+	pub rate_decimals: u32,
+}
+
+/// Prices liquid-staking derivatives as the USD price of their underlying asset in the cache times
+/// their on-chain redemption rate
+pub struct Redemption {
+	rates: Vec<(AssetSpecifier, AssetSpecifier, RedemptionRate)>,
+	storage: Arc<CoinInfoStorage>,
+	client: reqwest::Client,
+}
+
+impl Redemption {
+	/// Leaves out the rates whose asset or underlying are malformed
+	pub fn new(
+		rates: Vec<RedemptionRate>,
+		storage: Arc<CoinInfoStorage>,
+		client: reqwest::Client,
+	) -> Self {
+		let rates = rates
+			.into_iter()
+			.filter_map(|rate| {
+				let underlying = AssetSpecifier::parse(&rate.underlying);
+				match (AssetSpecifier::parse(&rate.asset), underlying) {
+					(Some(asset), Some(underlying)) => Some((asset, underlying, rate)),
+					_ => {
+						error!("Invalid redemption rate {:?} – asset and underlying need the form <blockchain>:<symbol>", rate);
+						None
+					},
+				}
+			})
+			.collect();
+		Redemption { rates, storage, client }
+	}
+}
+
+/// Decodes the redemption rate returned by the contract, a 32 byte big endian word
+fn decode_rate(
+	result: &str,
+	rate_decimals: u32,
+) -> Result<Decimal, Box<dyn error::Error + Send + Sync>> {
+	let bytes = hex::decode(result.trim_start_matches("0x"))?;
+	if bytes.len() < 32 || bytes[..16] != [0; 16] {
+		return Err("Malformed redemption rate".into())
+	}
+	let mut rate = [0; 16];
+	rate.copy_from_slice(&bytes[16..32]);
+	to_decimal(u128::from_be_bytes(rate), rate_decimals)
+}
+
+#[async_trait]
+impl PriceSource for Redemption {
+	fn name(&self) -> &str {
+		"redemption"
+	}
+
+	async fn get_quotable_assets(
+		&self,
+	) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Send + Sync>> {
+		Ok(self
+			.rates
+			.iter()
+			.map(|(asset, _, rate)| QuotedAsset {
+				asset: Asset {
+					symbol: asset.symbol.clone(),
+					name: asset.symbol.clone(),
+					address: rate.contract.clone(),
+					decimals: 0,
+					blockchain: asset.blockchain.clone(),
+				},
+				volume: Decimal::ZERO,
+			})
+			.collect())
+	}
+
+	async fn get_quotation(
+		&self,
+		quoted_asset: &QuotedAsset,
+	) -> Result<Quotation, Box<dyn error::Error + Send + Sync>> {
+		let (asset, underlying, rate) = self
+			.rates
+			.iter()
+			.find(|(asset, _, _)| {
+				asset.blockchain == quoted_asset.asset.blockchain &&
+					asset.symbol == quoted_asset.asset.symbol
+			})
+			.ok_or("No redemption rate for the asset")?;
+		let underlying_price = cached_price(&self.storage, underlying)
+			.ok_or_else(|| format!("No price of the underlying asset {:?}", underlying))?;
+
+		let result = eth_call(&self.client, &rate.rpc_url, &rate.contract, &rate.data).await?;
+		let price = decode_rate(&result, rate.rate_decimals)?
+			.checked_mul(underlying_price)
+			.ok_or("Price too large")?;
+		Ok(Quotation {
+			symbol: asset.symbol.clone(),
+			name: asset.symbol.clone(),
+			address: rate.contract.clone(),
+			blockchain: asset.blockchain.clone(),
+			price,
+			price_yesterday: price,
+			volume_yesterday: Decimal::ZERO,
+			time: Utc::now(),
+			source: "redemption".into(),
+			market_cap: None,
+			high_24h: None,
+			low_24h: None,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rust_decimal_macros::dec;
+
+	#[test]
+	fn decode_rate_should_scale_the_word() {
+		let result = format!("0x{:064x}", 1_250_000_000_000_000_000_u128);
+		assert_eq!(decode_rate(&result, 18).unwrap(), dec!(1.25));
+
+		assert!(decode_rate("0x00", 18).is_err());
+		assert!(decode_rate(&format!("0x1{:063x}", 0), 18).is_err());
+	}
+}
@@ -0,0 +1,159 @@
+use crate::dia::{Quotation, QuotedAsset};
+use crate::source::PriceSource;
+use async_trait::async_trait;
+use log::warn;
+use rand::Rng;
+use std::error::Error;
+use std::future::Future;
+use std::time::Duration;
+
+/// How often and how long to wait before failed requests to the DIA API are sent again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+	pub max_retries: u32,
+	pub initial_backoff: Duration,
+	pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		RetryPolicy {
+			max_retries: 3,
+			initial_backoff: Duration::from_millis(250),
+			max_backoff: Duration::from_secs(5),
+		}
+	}
+}
+
+impl RetryPolicy {
+	/// Calls `request` until it succeeds, fails permanently or runs out of retries
+	pub async fn run<F, Fut, T>(&self, mut request: F) -> Result<T, Box<dyn Error + Send + Sync>>
+	where
+		F: FnMut() -> Fut,
+		Fut: Future<Output = Result<T, Box<dyn Error + Send + Sync>>>,
+	{
+		let mut retry = 0;
+		loop {
+			match request().await {
+				Err(err) if retry < self.max_retries && is_transient(err.as_ref()) => {
+					let backoff = self.backoff(retry);
+					warn!("Request failed: {}, retrying in {:?}", err, backoff);
+					tokio::time::delay_for(backoff).await;
+					retry += 1;
+				},
+				result => return result,
+			}
+		}
+	}
+
+	/// Exponential backoff of the `retry`th retry with full jitter over its upper half
+	fn backoff(&self, retry: u32) -> Duration {
+		let backoff = self
+			.initial_backoff
+			.checked_mul(2_u32.saturating_pow(retry))
+			.map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+		backoff / 2 + backoff.mul_f64(rand::thread_rng().gen_range(0.0..0.5))
+	}
+}
+
+/// DIA API retrying failed requests of the wrapped one
+pub struct Retrying<T> {
+	api: T,
+	policy: RetryPolicy,
+}
+
+impl<T> Retrying<T> {
+	pub fn new(api: T, policy: RetryPolicy) -> Self {
+		Retrying { api, policy }
+	}
+}
+
+#[async_trait]
+impl<T> PriceSource for Retrying<T>
+where
+	T: PriceSource + Send + Sync,
+{
+	fn name(&self) -> &str {
+		self.api.name()
+	}
+
+	async fn get_quotable_assets(&self) -> Result<Vec<QuotedAsset>, Box<dyn Error + Send + Sync>> {
+		self.policy.run(|| self.api.get_quotable_assets()).await
+	}
+
+	async fn get_quotation(
+		&self,
+		asset: &QuotedAsset,
+	) -> Result<Quotation, Box<dyn Error + Send + Sync>> {
+		self.policy.run(|| self.api.get_quotation(asset)).await
+	}
+}
+
+/// Whether sending the request again may succeed, like after a timeout. Client errors and
+/// malformed responses are not retried, as the DIA API would answer the same again.
+fn is_transient(err: &(dyn Error + Send + Sync + 'static)) -> bool {
+	match err.downcast_ref::<reqwest::Error>() {
+		Some(err) => match err.status() {
+			Some(status) => status.is_server_error() || status.as_u16() == 429,
+			None => !err.is_decode(),
+		},
+		None => true,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicU32, Ordering};
+
+	fn policy(max_retries: u32) -> RetryPolicy {
+		RetryPolicy {
+			max_retries,
+			initial_backoff: Duration::from_millis(1),
+			max_backoff: Duration::from_millis(4),
+		}
+	}
+
+	#[tokio::test]
+	async fn run_should_retry_until_success() {
+		let attempts = AtomicU32::new(0);
+
+		let result = policy(3)
+			.run(|| async {
+				match attempts.fetch_add(1, Ordering::Relaxed) {
+					0 | 1 => Err("unavailable".into()),
+					_ => Ok(42),
+				}
+			})
+			.await;
+
+		assert_eq!(result.unwrap(), 42);
+		assert_eq!(attempts.load(Ordering::Relaxed), 3);
+	}
+
+	#[tokio::test]
+	async fn run_should_give_up_after_max_retries() {
+		let attempts = AtomicU32::new(0);
+
+		let result: Result<(), _> = policy(2)
+			.run(|| async {
+				attempts.fetch_add(1, Ordering::Relaxed);
+				Err("unavailable".into())
+			})
+			.await;
+
+		assert!(result.is_err());
+		assert_eq!(attempts.load(Ordering::Relaxed), 3);
+	}
+
+	#[test]
+	fn backoff_should_grow_up_to_the_maximum() {
+		let policy = policy(10);
+
+		for retry in 0..10 {
+			let ceiling = Duration::from_millis(1 << retry.min(2));
+			let backoff = policy.backoff(retry);
+			assert!(backoff >= ceiling / 2 && backoff <= ceiling, "{:?}", backoff);
+		}
+	}
+}
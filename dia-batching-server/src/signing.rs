@@ -0,0 +1,73 @@
+use schnorrkel::{signing_context, ExpansionMode, Keypair, MiniSecretKey};
+use std::fmt::{Display, Formatter};
+
+/// Response header carrying the hex encoded signature over the response body
+pub const SIGNATURE_HEADER: &str = "x-dia-signature";
+
+/// Signing context of substrate's sr25519 implementation, which the pallet verifies against
+const SIGNING_CONTEXT: &[u8] = b"substrate";
+
+/// Signs response bodies with an sr25519 key, so the offchain worker can detect tampered
+/// responses
+pub struct ResponseSigner {
+	keypair: Keypair,
+}
+
+impl ResponseSigner {
+	/// Creates a signer from a hex encoded 32 byte seed, optionally prefixed with `0x`
+	pub fn from_seed(seed: &str) -> Result<Self, InvalidSeed> {
+		let seed = hex::decode(seed.trim_start_matches("0x")).map_err(|_| InvalidSeed)?;
+		let secret = MiniSecretKey::from_bytes(&seed).map_err(|_| InvalidSeed)?;
+		Ok(Self { keypair: secret.expand_to_keypair(ExpansionMode::Ed25519) })
+	}
+
+	/// Hex encoded public key, to be set as batching server key on chain
+	pub fn public_key(&self) -> String {
+		format!("0x{}", hex::encode(self.keypair.public.to_bytes()))
+	}
+
+	/// Hex encoded signature over `payload`
+	pub fn sign(&self, payload: &[u8]) -> String {
+		hex::encode(self.keypair.sign(signing_context(SIGNING_CONTEXT).bytes(payload)).to_bytes())
+	}
+}
+
+#[derive(Debug)]
+pub struct InvalidSeed;
+
+impl Display for InvalidSeed {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Signing seed must be 32 hex encoded bytes")
+	}
+}
+
+impl std::error::Error for InvalidSeed {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use schnorrkel::{PublicKey, Signature};
+
+	const SEED: &str = "0x0707070707070707070707070707070707070707070707070707070707070707";
+
+	#[test]
+	fn test_sign_verifies_with_public_key() {
+		let signer = ResponseSigner::from_seed(SEED).unwrap();
+		let signature = signer.sign(b"[]");
+
+		let public =
+			PublicKey::from_bytes(&hex::decode(&signer.public_key()[2..]).unwrap()).unwrap();
+		let signature = Signature::from_bytes(&hex::decode(signature).unwrap()).unwrap();
+
+		assert!(public.verify(signing_context(SIGNING_CONTEXT).bytes(b"[]"), &signature).is_ok());
+		assert!(public
+			.verify(signing_context(SIGNING_CONTEXT).bytes(b"[{}]"), &signature)
+			.is_err());
+	}
+
+	#[test]
+	fn test_from_seed_invalid() {
+		assert!(ResponseSigner::from_seed("0x0707").is_err());
+		assert!(ResponseSigner::from_seed("not hex").is_err());
+	}
+}
@@ -0,0 +1,21 @@
+use crate::dia::{Quotation, QuotedAsset};
+use async_trait::async_trait;
+use std::error;
+
+/// A source of quotations, like the DIA API or an exchange
+#[async_trait]
+pub trait PriceSource {
+	/// Name of the source in the routes of the config
+	fn name(&self) -> &str;
+
+	async fn get_quotable_assets(
+		&self,
+	) -> Result<Vec<QuotedAsset>, Box<dyn error::Error + Send + Sync>>;
+
+	async fn get_quotation(
+		&self,
+		_: &QuotedAsset,
+	) -> Result<Quotation, Box<dyn error::Error + Sync + Send>>;
+}
+
+pub type Source = Box<dyn PriceSource + Send + Sync>;
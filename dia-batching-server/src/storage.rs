@@ -1,10 +1,15 @@
-use arc_swap::ArcSwap;
+use arc_swap::{ArcSwap, ArcSwapOption};
+use codec::{Encode, Output};
 use serde::{Deserialize, Serialize};
 use smol_str::SmolStr;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::handlers::Currency;
+use crate::AssetSpecifier;
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -15,14 +20,58 @@ pub struct CoinInfo {
 	pub supply: u128,
 	pub last_update_timestamp: u64,
 	pub price: u128,
+	pub price_yesterday: u128,
+	pub volume_yesterday_usd: u128,
+	pub market_cap: Option<u128>,
+	pub high_24h: Option<u128>,
+	pub low_24h: Option<u128>,
+}
+
+/// Same layout as the pallet's `CoinInfo`, strings encode like the pallet's byte vectors
+impl Encode for CoinInfo {
+	fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+		self.symbol.as_str().encode_to(dest);
+		self.name.as_str().encode_to(dest);
+		self.blockchain.as_str().encode_to(dest);
+		self.supply.encode_to(dest);
+		self.last_update_timestamp.encode_to(dest);
+		self.price.encode_to(dest);
+		self.price_yesterday.encode_to(dest);
+		self.volume_yesterday_usd.encode_to(dest);
+		self.market_cap.encode_to(dest);
+		self.high_24h.encode_to(dest);
+		self.low_24h.encode_to(dest);
+	}
+}
+
+/// Coin info in the cache file, with the UNIX time in seconds it was fetched
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedCoinInfo {
+	coin_info: CoinInfo,
+	fetched_at: u64,
 }
 
 #[derive(Debug, Default)]
 pub struct CoinInfoStorage {
-	currencies_by_blockchain_and_symbol: ArcSwap<HashMap<(SmolStr, SmolStr), CoinInfo>>,
+	/// Coin infos with the time they were last fetched
+	currencies_by_blockchain_and_symbol: ArcSwap<HashMap<(SmolStr, SmolStr), (CoinInfo, Instant)>>,
+	last_update: ArcSwapOption<Instant>,
+	max_age: Option<Duration>,
+	/// Failed fetches in a row of the assets whose last fetch failed
+	consecutive_failures: ArcSwap<HashMap<(SmolStr, SmolStr), u32>>,
 }
 
 impl CoinInfoStorage {
+	/// Storage leaving out coin infos not fetched again within `max_age`
+	pub fn with_max_age(max_age: Option<Duration>) -> Self {
+		CoinInfoStorage { max_age, ..Default::default() }
+	}
+
+	fn is_fresh(&self, refreshed_at: &Instant) -> bool {
+		self.max_age.map_or(true, |max_age| refreshed_at.elapsed() <= max_age)
+	}
+
 	pub fn get_currencies_by_blockchains_and_symbols(
 		&self,
 		blockchain_and_symbols: Vec<Currency>,
@@ -33,17 +82,241 @@ impl CoinInfoStorage {
 			.filter_map(|Currency { blockchain, symbol }| {
 				reference.get(&(blockchain.into(), symbol.into()))
 			})
-			.cloned()
+			.filter(|(_, refreshed_at)| self.is_fresh(refreshed_at))
+			.map(|(coin_info, _)| coin_info.clone())
 			.collect()
 	}
 
+	/// Every cached coin info, ordered by blockchain and symbol
+	pub fn get_all_currencies(&self) -> Vec<CoinInfo> {
+		let mut coin_infos: Vec<CoinInfo> = self
+			.currencies_by_blockchain_and_symbol
+			.load()
+			.values()
+			.filter(|(_, refreshed_at)| self.is_fresh(refreshed_at))
+			.map(|(coin_info, _)| coin_info.clone())
+			.collect();
+		coin_infos.sort_by(|a, b| (&a.blockchain, &a.symbol).cmp(&(&b.blockchain, &b.symbol)));
+		coin_infos
+	}
+
 	#[allow(dead_code)]
 	pub fn replace_currencies_by_symbols(&self, currencies: Vec<CoinInfo>) {
+		let now = Instant::now();
 		let map_to_replace_with = currencies
 			.into_iter()
-			.map(|x| ((x.blockchain.clone(), x.symbol.clone()), x))
+			.map(|x| ((x.blockchain.clone(), x.symbol.clone()), (x, now)))
+			.collect();
+
+		self.currencies_by_blockchain_and_symbol.store(Arc::new(map_to_replace_with));
+		self.consecutive_failures.store(Default::default());
+		self.last_update.store(Some(Arc::new(now)));
+	}
+
+	/// Merges the fetched coin infos into the cache. Assets not fetched, like those whose fetch
+	/// failed, keep their previous coin info with the time it was fetched. The failures are counted
+	/// per asset until it is fetched again.
+	pub fn upsert_currencies(&self, fetched: Vec<CoinInfo>, failed: Vec<(SmolStr, SmolStr)>) {
+		let now = Instant::now();
+		let previous = self.currencies_by_blockchain_and_symbol.load();
+		let previous_failures = self.consecutive_failures.load();
+
+		let mut map_to_replace_with = HashMap::clone(&previous);
+		map_to_replace_with.extend(
+			fetched.into_iter().map(|x| ((x.blockchain.clone(), x.symbol.clone()), (x, now))),
+		);
+		let failures = failed
+			.into_iter()
+			.map(|key| {
+				let count = previous_failures.get(&key).copied().unwrap_or(0).saturating_add(1);
+				(key, count)
+			})
+			.collect();
+
+		self.currencies_by_blockchain_and_symbol.store(Arc::new(map_to_replace_with));
+		self.consecutive_failures.store(Arc::new(failures));
+		self.last_update.store(Some(Arc::new(now)));
+	}
+
+	/// Drops the coin infos and failure counts of the assets that are no longer supported, e.g.
+	/// after a reload of the supported currencies file or a chain sync
+	pub fn retain_supported(&self, supported: &HashSet<AssetSpecifier>) {
+		let is_supported = |(blockchain, symbol): &(SmolStr, SmolStr)| {
+			let asset =
+				AssetSpecifier { blockchain: blockchain.to_string(), symbol: symbol.to_string() };
+			supported.contains(&asset)
+		};
+		let mut coin_infos = HashMap::clone(&self.currencies_by_blockchain_and_symbol.load());
+		coin_infos.retain(|key, _| is_supported(key));
+		let mut failures = HashMap::clone(&self.consecutive_failures.load());
+		failures.retain(|key, _| is_supported(key));
+
+		self.currencies_by_blockchain_and_symbol.store(Arc::new(coin_infos));
+		self.consecutive_failures.store(Arc::new(failures));
+	}
+
+	/// Failed fetches in a row per blockchain and symbol, ordered by them
+	pub fn consecutive_failures(&self) -> Vec<((SmolStr, SmolStr), u32)> {
+		let mut failures: Vec<_> = self
+			.consecutive_failures
+			.load()
+			.iter()
+			.map(|(key, count)| (key.clone(), *count))
+			.collect();
+		failures.sort();
+		failures
+	}
+
+	/// Writes the cached coin infos to `path`, replacing the file at once so that a crash while
+	/// writing leaves the previous one intact
+	pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+		let (now, unix_now) = (Instant::now(), SystemTime::now());
+		let persisted: Vec<PersistedCoinInfo> = self
+			.currencies_by_blockchain_and_symbol
+			.load()
+			.values()
+			.map(|(coin_info, fetched_at)| PersistedCoinInfo {
+				coin_info: coin_info.clone(),
+				fetched_at: (unix_now - now.duration_since(*fetched_at))
+					.duration_since(UNIX_EPOCH)
+					.map_or(0, |time| time.as_secs()),
+			})
+			.collect();
+		let tmp = path.with_extension("tmp");
+		std::fs::write(&tmp, serde_json::to_vec(&persisted)?)?;
+		std::fs::rename(&tmp, path)?;
+		Ok(())
+	}
+
+	/// Fills the cache from a file written by `save`, keeping the age of every coin info so that
+	/// they turn stale as if the server had kept running. Returns the number of coin infos loaded,
+	/// none if the file doesn't exist yet.
+	pub fn load(&self, path: &Path) -> Result<usize, Box<dyn Error + Send + Sync>> {
+		let content = match std::fs::read(path) {
+			Ok(content) => content,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+			Err(e) => return Err(e.into()),
+		};
+		let persisted: Vec<PersistedCoinInfo> = serde_json::from_slice(&content)?;
+		let (now, unix_now) = (Instant::now(), SystemTime::now());
+		let map_to_replace_with: HashMap<_, _> = persisted
+			.into_iter()
+			.filter_map(|PersistedCoinInfo { coin_info, fetched_at }| {
+				let fetched_at = UNIX_EPOCH + Duration::from_secs(fetched_at);
+				let age = unix_now.duration_since(fetched_at).unwrap_or_default();
+				if self.max_age.map_or(false, |max_age| age > max_age) {
+					return None
+				}
+				let fetched_at = now.checked_sub(age).unwrap_or(now);
+				let key = (coin_info.blockchain.clone(), coin_info.symbol.clone());
+				Some((key, (coin_info, fetched_at)))
+			})
 			.collect();
 
+		let loaded = map_to_replace_with.len();
 		self.currencies_by_blockchain_and_symbol.store(Arc::new(map_to_replace_with));
+		Ok(loaded)
+	}
+
+	/// Whether there are prices to serve, from a completed price update or the cache file
+	pub fn is_populated(&self) -> bool {
+		self.last_update().is_some() || !self.currencies_by_blockchain_and_symbol.load().is_empty()
+	}
+
+	/// When the cache was last replaced, `None` before the first price update completed
+	pub fn last_update(&self) -> Option<Instant> {
+		self.last_update.load().as_deref().copied()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn coin_infos_should_be_left_out_once_stale() {
+		let storage = CoinInfoStorage::with_max_age(Some(Duration::from_millis(50)));
+		storage.replace_currencies_by_symbols(vec![CoinInfo {
+			symbol: "BTC".into(),
+			blockchain: "Bitcoin".into(),
+			..Default::default()
+		}]);
+		let btc = || vec![Currency { blockchain: "Bitcoin".into(), symbol: "BTC".into() }];
+
+		assert_eq!(storage.get_all_currencies().len(), 1);
+		assert_eq!(storage.get_currencies_by_blockchains_and_symbols(btc()).len(), 1);
+
+		std::thread::sleep(Duration::from_millis(60));
+
+		assert!(storage.get_all_currencies().is_empty());
+		assert!(storage.get_currencies_by_blockchains_and_symbols(btc()).is_empty());
+	}
+
+	#[test]
+	fn saved_coin_infos_should_be_loaded_with_their_age() {
+		let path = std::env::temp_dir().join(format!("dia-cache-{}.json", std::process::id()));
+		let coin_info = CoinInfo {
+			symbol: "BTC".into(),
+			blockchain: "Bitcoin".into(),
+			price: 16_800_000_000_000_000,
+			..Default::default()
+		};
+		let storage = CoinInfoStorage::default();
+		storage.replace_currencies_by_symbols(vec![coin_info.clone()]);
+		storage.save(&path).unwrap();
+
+		let restarted = CoinInfoStorage::with_max_age(Some(Duration::from_secs(60)));
+		assert_eq!(restarted.load(&path).unwrap(), 1);
+		assert_eq!(restarted.get_all_currencies(), vec![coin_info]);
+		assert_eq!(restarted.last_update(), None);
+
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(CoinInfoStorage::default().load(&path).unwrap(), 0);
+	}
+
+	#[test]
+	fn upsert_currencies_should_keep_assets_not_fetched() {
+		let storage = CoinInfoStorage::default();
+		let coin_info = |symbol: &str, price| CoinInfo {
+			symbol: symbol.into(),
+			blockchain: "Ethereum".into(),
+			price,
+			..Default::default()
+		};
+		let eth = ("Ethereum".into(), "ETH".into());
+		storage.replace_currencies_by_symbols(vec![coin_info("ETH", 1), coin_info("USDT", 1)]);
+
+		storage.upsert_currencies(vec![coin_info("USDT", 2)], vec![eth.clone()]);
+		storage.upsert_currencies(vec![coin_info("USDT", 3)], vec![eth.clone()]);
+
+		assert_eq!(storage.get_all_currencies(), vec![coin_info("ETH", 1), coin_info("USDT", 3)]);
+		assert_eq!(storage.consecutive_failures(), vec![(eth.clone(), 2)]);
+
+		storage.upsert_currencies(vec![coin_info("ETH", 4)], vec![]);
+
+		assert_eq!(storage.get_all_currencies(), vec![coin_info("ETH", 4), coin_info("USDT", 3)]);
+		assert!(storage.consecutive_failures().is_empty());
+	}
+
+	#[test]
+	fn retain_supported_should_drop_unsupported_assets() {
+		let storage = CoinInfoStorage::default();
+		let coin_info = |symbol: &str| CoinInfo {
+			symbol: symbol.into(),
+			blockchain: "Ethereum".into(),
+			..Default::default()
+		};
+		storage.upsert_currencies(
+			vec![coin_info("ETH"), coin_info("USDT")],
+			vec![("Ethereum".into(), "DAI".into())],
+		);
+
+		let supported = vec![AssetSpecifier { blockchain: "Ethereum".into(), symbol: "ETH".into() }]
+			.into_iter()
+			.collect();
+		storage.retain_supported(&supported);
+
+		assert_eq!(storage.get_all_currencies(), vec![coin_info("ETH")]);
+		assert!(storage.consecutive_failures().is_empty());
 	}
 }
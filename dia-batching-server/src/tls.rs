@@ -0,0 +1,28 @@
+use rustls::internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use rustls::{NoClientAuth, ServerConfig};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Builds the rustls configuration from a PEM certificate chain and a PEM private key in PKCS#8
+/// or RSA format
+pub fn load_server_config(
+	cert: &Path,
+	key: &Path,
+) -> Result<ServerConfig, Box<dyn Error + Send + Sync>> {
+	let cert_chain = certs(&mut BufReader::new(File::open(cert)?))
+		.map_err(|_| format!("Invalid certificate in {}", cert.display()))?;
+
+	let mut keys = pkcs8_private_keys(&mut BufReader::new(File::open(key)?))
+		.map_err(|_| format!("Invalid private key in {}", key.display()))?;
+	if keys.is_empty() {
+		keys = rsa_private_keys(&mut BufReader::new(File::open(key)?))
+			.map_err(|_| format!("Invalid private key in {}", key.display()))?;
+	}
+	let key = keys.into_iter().next().ok_or_else(|| format!("No key in {}", key.display()))?;
+
+	let mut config = ServerConfig::new(NoClientAuth::new());
+	config.set_single_cert(cert_chain, key)?;
+	Ok(config)
+}
@@ -0,0 +1,64 @@
+use dia_batching_client::Currency;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "dia-feeder", about = "Feeds batching server prices to the DIA oracle pallet")]
+pub struct FeederArgs {
+	/// Websocket URL of the node to submit prices to
+	#[structopt(long, default_value = "ws://127.0.0.1:9944")]
+	pub url: String,
+
+	/// Full URL of the `/currencies` route of the batching server
+	#[structopt(long, default_value = dia_batching_client::DEFAULT_ENDPOINT)]
+	pub batching_api: String,
+
+	/// Seconds between two price submissions
+	#[structopt(short, long, default_value = "60")]
+	pub interval_in_seconds: u64,
+
+	/// Secret URI of the sr25519 key to sign with, e.g. a mnemonic or `//Alice`
+	/// Its account needs to be authorized on chain
+	#[structopt(long)]
+	pub suri: String,
+
+	/// Currencies to feed
+	/// Each currency needs to have the format <blockchain>:<symbol>
+	#[structopt(short, long, required = true)]
+	pub currencies: Vec<String>,
+
+	/// Smallest price move, in parts per million of the price on chain, worth submitting for
+	/// assets without a `MinChangeThresholds` entry. Prices on chain older than half of the
+	/// pallet's `MaxPriceAge` are resubmitted regardless.
+	#[structopt(long, default_value = "0")]
+	pub min_change_threshold_in_ppm: u32,
+
+	/// Name of the oracle pallet in the runtime
+	#[structopt(long, default_value = dia_oracle_client::DEFAULT_PALLET_NAME)]
+	pub pallet_name: String,
+}
+
+/// Parses `<blockchain>:<symbol>` into a currency
+pub fn parse_currency(currency: &str) -> Result<Currency, String> {
+	match currency.trim().split_once(':') {
+		Some((blockchain, symbol))
+			if !blockchain.is_empty() && !symbol.is_empty() && !symbol.contains(':') =>
+			Ok(Currency::new(blockchain, symbol)),
+		_ => Err(format!(
+			"Invalid currency '{}' – every currency needs to have the form <blockchain>:<symbol>",
+			currency
+		)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_currency_should_split_blockchain_and_symbol() {
+		assert_eq!(parse_currency(" Bitcoin:BTC "), Ok(Currency::new("Bitcoin", "BTC")));
+		assert!(parse_currency("BTC").is_err());
+		assert!(parse_currency("Bitcoin:").is_err());
+		assert!(parse_currency("Bitcoin:BTC:USD").is_err());
+	}
+}
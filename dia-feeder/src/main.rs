@@ -0,0 +1,119 @@
+use crate::args::{parse_currency, FeederArgs};
+use dia_batching_client::Currency;
+use dia_oracle_client::{price_filter, CoinInfo, CoinInfos, OracleClient, Permill, Signer};
+use log::{error, info, warn};
+use std::{
+	error::Error,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use structopt::StructOpt;
+use tokio_compat_02::FutureExt;
+
+mod args;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+	pretty_env_logger::init();
+
+	let args = FeederArgs::from_args();
+	let currencies =
+		args.currencies.iter().map(|c| parse_currency(c)).collect::<Result<Vec<_>, _>>()?;
+	let signer = dia_oracle_client::signer_from_suri(&args.suri)?;
+	let batching_client = dia_batching_client::Client::new(args.batching_api)?;
+	let oracle_client = OracleClient::new(&args.url).await?.pallet_name(args.pallet_name);
+	let default_threshold = Permill::from_parts(args.min_change_threshold_in_ppm);
+
+	let mut interval = tokio::time::interval(Duration::from_secs(args.interval_in_seconds));
+	loop {
+		interval.tick().await;
+		match feed(&batching_client, &oracle_client, &signer, &currencies, default_threshold).await
+		{
+			Ok(0) => info!("No changed prices, nothing submitted"),
+			Ok(count) => info!("Submitted {} prices", count),
+			Err(e) => error!("Failed to feed prices: {}", e),
+		}
+	}
+}
+
+/// Fetches the prices of `currencies` from the batching server and submits those that changed
+/// in one extrinsic, filtered and scaled like the offchain worker does. Returns the number of
+/// submitted prices.
+async fn feed(
+	batching_client: &dia_batching_client::Client,
+	oracle_client: &OracleClient,
+	signer: &Signer,
+	currencies: &[Currency],
+	default_threshold: Permill,
+) -> Result<usize, Box<dyn Error>> {
+	// Read on every run, the constants may change with a runtime upgrade
+	let decimals = oracle_client.price_decimals()?;
+	let limits = oracle_client.price_limits()?;
+	let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+	let fetched = batching_client.get_coin_infos(currencies).compat().await?;
+	let mut coin_infos = CoinInfos::new();
+	for coin_info in fetched.into_iter().map(to_oracle_coin_info) {
+		let coin_info = match scale_coin_info(coin_info, decimals) {
+			Some(coin_info) => coin_info,
+			None => continue,
+		};
+		if let Err(e) = price_filter::check_coin_info(&coin_info, now, &limits) {
+			warn!("Dropping coin info {}: {:?}", String::from_utf8_lossy(&coin_info.symbol), e);
+			continue
+		}
+
+		let (blockchain, symbol) = (coin_info.blockchain.clone(), coin_info.symbol.clone());
+		if let Some(stored) = oracle_client.coin_info(&blockchain, &symbol).await? {
+			let threshold = oracle_client
+				.min_change_threshold(&blockchain, &symbol)
+				.await?
+				.unwrap_or(default_threshold);
+			if !price_filter::has_changed(&stored, &coin_info, threshold, limits.max_price_age) {
+				continue
+			}
+		}
+		coin_infos.push(((blockchain, symbol), coin_info));
+	}
+	if coin_infos.is_empty() {
+		return Ok(0)
+	}
+
+	let count = coin_infos.len();
+	let hash = oracle_client.set_updated_coin_infos(signer, coin_infos).await?;
+	info!("Prices included in extrinsic {:?}", hash);
+	Ok(count)
+}
+
+/// Converts the price to the pallet's `decimals`, `None` if it does not fit any more
+fn scale_coin_info(mut coin_info: CoinInfo, decimals: u8) -> Option<CoinInfo> {
+	match price_filter::scale_price(coin_info.price, decimals) {
+		Some(price) => {
+			coin_info.price = price;
+			Some(coin_info)
+		},
+		None => {
+			warn!(
+				"Skipping {}, its price overflows with {} decimals",
+				String::from_utf8_lossy(&coin_info.symbol),
+				decimals
+			);
+			None
+		},
+	}
+}
+
+fn to_oracle_coin_info(coin_info: dia_batching_client::CoinInfo) -> CoinInfo {
+	CoinInfo {
+		symbol: coin_info.symbol.as_bytes().to_vec(),
+		name: coin_info.name.as_bytes().to_vec(),
+		blockchain: coin_info.blockchain.as_bytes().to_vec(),
+		supply: coin_info.supply,
+		last_update_timestamp: coin_info.last_update_timestamp,
+		price: coin_info.price,
+		price_yesterday: coin_info.price_yesterday,
+		volume_yesterday_usd: coin_info.volume_yesterday_usd,
+		market_cap: coin_info.market_cap,
+		high_24h: coin_info.high_24h,
+		low_24h: coin_info.low_24h,
+	}
+}
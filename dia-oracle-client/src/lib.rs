@@ -0,0 +1,212 @@
+//! subxt based client for the DIA oracle pallet.
+//!
+//! [`OracleClient`] reads coin infos from `CoinInfosMap`, follows the `UpdatedPrices` events of
+//! finalized blocks and submits `set_updated_coin_infos`, so that prices can be fed by a process
+//! outside of the node instead of the offchain worker. The signing account has to be authorized
+//! on chain like any other feeder.
+use codec::{Decode, Encode};
+use futures::{Stream, StreamExt};
+use subxt::{
+	ext::sp_core::{
+		hashing::{blake2_128, twox_128},
+		sr25519, Pair, H256,
+	},
+	metadata::Metadata,
+	tx::{PairSigner, TxPayload},
+	Error, OnlineClient, PolkadotConfig,
+};
+
+pub use dia_oracle::{
+	price_filter::{self, Permill, PriceLimits},
+	AssetId, CoinInfo,
+};
+
+/// Name of the oracle pallet in `construct_runtime!` of the DIA runtime
+pub const DEFAULT_PALLET_NAME: &str = "DiaOracleModule";
+
+/// Coin infos as carried by `set_updated_coin_infos` and the `UpdatedPrices` event
+pub type CoinInfos = Vec<((Vec<u8>, Vec<u8>), CoinInfo)>;
+
+pub type Signer = PairSigner<PolkadotConfig, sr25519::Pair>;
+
+/// Creates a signer from a secret URI, e.g. a mnemonic or `//Alice`
+pub fn signer_from_suri(suri: &str) -> Result<Signer, String> {
+	let pair = sr25519::Pair::from_string(suri, None)
+		.map_err(|e| format!("Invalid secret URI: {:?}", e))?;
+	Ok(PairSigner::new(pair))
+}
+
+pub struct OracleClient {
+	api: OnlineClient<PolkadotConfig>,
+	pallet_name: String,
+}
+
+impl OracleClient {
+	/// Connects to the node at the websocket `url`
+	pub async fn new(url: &str) -> Result<Self, Error> {
+		let api = OnlineClient::from_url(url).await?;
+		Ok(Self { api, pallet_name: DEFAULT_PALLET_NAME.into() })
+	}
+
+	/// Uses the pallet under another name, for runtimes that do not call it `DiaOracleModule`
+	pub fn pallet_name(mut self, pallet_name: impl Into<String>) -> Self {
+		self.pallet_name = pallet_name.into();
+		self
+	}
+
+	/// Reads the coin info of the asset at the best block, `None` if it has no price
+	pub async fn coin_info(
+		&self,
+		blockchain: &[u8],
+		symbol: &[u8],
+	) -> Result<Option<CoinInfo>, Error> {
+		let key = coin_info_key(&self.pallet_name, blockchain, symbol);
+		match self.api.rpc().storage(&key, None).await? {
+			Some(data) => Ok(Some(CoinInfo::decode(&mut &data.0[..])?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Reads the threshold `MinChangeThresholds` sets for the asset at the best block, if any
+	pub async fn min_change_threshold(
+		&self,
+		blockchain: &[u8],
+		symbol: &[u8],
+	) -> Result<Option<Permill>, Error> {
+		let key = min_change_threshold_key(&self.pallet_name, blockchain, symbol);
+		match self.api.rpc().storage(&key, None).await? {
+			Some(data) => Ok(Some(Permill::decode(&mut &data.0[..])?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Number of decimals the pallet stores prices with, its `PriceDecimals` constant
+	pub fn price_decimals(&self) -> Result<u8, Error> {
+		self.constant("PriceDecimals")
+	}
+
+	/// Limits the pallet enforces on submitted coin infos, read from its constants
+	pub fn price_limits(&self) -> Result<PriceLimits, Error> {
+		Ok(PriceLimits {
+			allow_zero_prices: self.constant("AllowZeroPrices")?,
+			max_future_drift: self.constant("MaxFutureDrift")?,
+			max_price_age: self.constant("MaxPriceAge")?,
+		})
+	}
+
+	fn constant<T: Decode>(&self, name: &str) -> Result<T, Error> {
+		let metadata = self.api.metadata();
+		let constant = metadata.pallet(&self.pallet_name)?.constant(name)?;
+		Ok(T::decode(&mut constant.value())?)
+	}
+
+	/// Yields the coin infos of every `UpdatedPrices` event in finalized blocks
+	pub async fn subscribe_prices(
+		&self,
+	) -> Result<impl Stream<Item = Result<CoinInfos, Error>>, Error> {
+		let pallet_name = self.pallet_name.clone();
+		let blocks = self.api.blocks().subscribe_finalized().await?;
+
+		Ok(blocks
+			.then(move |block| {
+				let pallet_name = pallet_name.clone();
+				async move {
+					let events = block?.events().await?;
+					let mut updates = Vec::new();
+					for event in events.iter() {
+						let event = event?;
+						if event.pallet_name() == pallet_name &&
+							event.variant_name() == "UpdatedPrices"
+						{
+							updates.push(CoinInfos::decode(&mut event.field_bytes())?);
+						}
+					}
+					Ok(updates)
+				}
+			})
+			.flat_map(|updates: Result<Vec<CoinInfos>, Error>| {
+				let updates = match updates {
+					Ok(updates) => updates.into_iter().map(Ok).collect(),
+					Err(e) => vec![Err(e)],
+				};
+				futures::stream::iter(updates)
+			}))
+	}
+
+	/// Submits `set_updated_coin_infos` and waits until it is finalized successfully. Returns
+	/// the hash of the extrinsic.
+	pub async fn set_updated_coin_infos(
+		&self,
+		signer: &Signer,
+		coin_infos: CoinInfos,
+	) -> Result<H256, Error> {
+		let call = SetUpdatedCoinInfos { pallet_name: self.pallet_name.clone(), coin_infos };
+		let events = self
+			.api
+			.tx()
+			.sign_and_submit_then_watch_default(&call, signer)
+			.await?
+			.wait_for_finalized_success()
+			.await?;
+		Ok(events.extrinsic_hash())
+	}
+}
+
+/// Storage key of the `CoinInfosMap` entry of an asset, both keys hashed with `Blake2_128Concat`
+pub fn coin_info_key(pallet_name: &str, blockchain: &[u8], symbol: &[u8]) -> Vec<u8> {
+	let (blockchain, symbol) = (blockchain.encode(), symbol.encode());
+	[
+		&twox_128(pallet_name.as_bytes())[..],
+		&twox_128(b"CoinInfosMap")[..],
+		&blake2_128(&blockchain)[..],
+		&blockchain[..],
+		&blake2_128(&symbol)[..],
+		&symbol[..],
+	]
+	.concat()
+}
+
+/// Storage key of the `MinChangeThresholds` entry of an asset, keyed with `Blake2_128Concat`
+pub fn min_change_threshold_key(pallet_name: &str, blockchain: &[u8], symbol: &[u8]) -> Vec<u8> {
+	let asset_id = AssetId::new(blockchain.to_vec(), symbol.to_vec()).encode();
+	[
+		&twox_128(pallet_name.as_bytes())[..],
+		&twox_128(b"MinChangeThresholds")[..],
+		&blake2_128(&asset_id)[..],
+		&asset_id[..],
+	]
+	.concat()
+}
+
+struct SetUpdatedCoinInfos {
+	pallet_name: String,
+	coin_infos: CoinInfos,
+}
+
+impl TxPayload for SetUpdatedCoinInfos {
+	fn encode_call_data(&self, metadata: &Metadata, out: &mut Vec<u8>) -> Result<(), Error> {
+		let pallet = metadata.pallet(&self.pallet_name)?;
+		out.push(pallet.index());
+		out.push(pallet.call_index("set_updated_coin_infos")?);
+		self.coin_infos.encode_to(out);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn coin_info_key_should_encode_blockchain_and_symbol() {
+		let key = coin_info_key(DEFAULT_PALLET_NAME, b"Bitcoin", b"BTC");
+		let (blockchain, symbol) = (b"Bitcoin".to_vec().encode(), b"BTC".to_vec().encode());
+
+		assert_eq!(&key[..16], &twox_128(b"DiaOracleModule"));
+		assert_eq!(&key[16..32], &twox_128(b"CoinInfosMap"));
+		assert_eq!(&key[32..48], &blake2_128(&blockchain));
+		assert_eq!(&key[48..56], &blockchain[..]);
+		assert_eq!(&key[56..72], &blake2_128(&symbol));
+		assert_eq!(&key[72..], &symbol[..]);
+	}
+}
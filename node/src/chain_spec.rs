@@ -1,5 +1,5 @@
 use node_template_runtime::{
-	AccountId, AssetId, AuraConfig, BalancesConfig, DiaOracleModuleConfig, GenesisConfig,
+	dia_oracle, AccountId, AuraConfig, BalancesConfig, DiaOracleModuleConfig, GenesisConfig,
 	GrandpaConfig, Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
@@ -152,11 +152,7 @@ fn testnet_genesis(
 			key: Some(root_key.clone()),
 		},
 		transaction_payment: Default::default(),
-		dia_oracle_module: DiaOracleModuleConfig {
-			authorized_accounts: vec![root_key],
-			supported_currencies: vec![AssetId::new(b"Bitcoin".to_vec(), b"BTC".to_vec())],
-			batching_api: b"http://localhost:8070/currencies".to_vec(),
-			coin_infos_map: vec![],
-		},
+		dia_oracle_module: DiaOracleModuleConfig::preset(dia_oracle::DEV_PRESET, vec![root_key])
+			.expect("the development preset exists; qed"),
 	}
 }
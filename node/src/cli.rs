@@ -7,6 +7,15 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Enable price feeding by the DIA oracle offchain worker on this node
+	#[arg(long)]
+	pub oracle_feeder: bool,
+
+	/// Answer DIA oracle RPC calls without a block hash from the latest finalized block instead
+	/// of the best block
+	#[arg(long)]
+	pub oracle_rpc_finalized: bool,
 }
 
 #[derive(Debug, clap::Subcommand)]
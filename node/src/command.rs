@@ -198,7 +198,8 @@ pub fn run() -> sc_cli::Result<()> {
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+				service::new_full(config, cli.oracle_feeder, cli.oracle_rpc_finalized)
+					.map_err(sc_cli::Error::Service)
 			})
 		},
 	}
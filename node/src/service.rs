@@ -1,7 +1,7 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::BlockBackend;
+use node_template_runtime::{self, dia_oracle, opaque::Block, RuntimeApi};
+use sc_client_api::{Backend, BlockBackend};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
@@ -9,6 +9,7 @@ use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
+use sp_core::{offchain::OffchainStorage, Encode};
 use std::{sync::Arc, time::Duration};
 
 // Our native executor instance.
@@ -155,7 +156,11 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(
+	mut config: Configuration,
+	oracle_feeder: bool,
+	oracle_rpc_finalized: bool,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -204,6 +209,16 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 			warp_sync: Some(warp_sync),
 		})?;
 
+	if oracle_feeder {
+		if let Some(mut offchain_storage) = backend.offchain_storage() {
+			offchain_storage.set(
+				sp_offchain::STORAGE_PREFIX,
+				dia_oracle::FEEDER_ENABLED_KEY,
+				&true.encode(),
+			);
+		}
+	}
+
 	if config.offchain_worker.enabled {
 		sc_service::build_offchain_workers(
 			&config,
@@ -224,9 +239,14 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		let client = client.clone();
 		let pool = transaction_pool.clone();
 
-		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				subscription_executor,
+				oracle_rpc_finalized,
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
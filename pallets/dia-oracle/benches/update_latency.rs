@@ -0,0 +1,124 @@
+//! End-to-end latency of one price update, from a changed batching server response to the new
+//! prices in `CoinInfosMap`, for a growing number of supported assets.
+//!
+//! The batching server is stood in for by the offchain HTTP test state, so the numbers cover the
+//! offchain worker (request, parsing, signing) and the dispatch of the submitted extrinsic, but
+//! not the network.
+//!
+//! Run with `cargo bench -p dia-oracle --features mock`.
+use codec::Decode;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dia_oracle::{
+	crypto::KEY_TYPE,
+	mock::{new_test_ext, DOracle, Extrinsic, RuntimeOrigin, Test},
+	AssetId, AuthorizedAccounts, BatchingApi, CoinInfosMap, SupportedCurrencies,
+	FEEDER_ENABLED_KEY,
+};
+use frame_support::traits::Hooks;
+use parking_lot::RwLock;
+use sp_core::{
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt, Timestamp, TransactionPoolExt},
+	sr25519::Public,
+};
+use sp_keystore::{testing::KeyStore, KeystoreExt, SyncCryptoStore};
+use sp_runtime::{offchain::storage::StorageValueRef, traits::Dispatchable};
+use std::sync::Arc;
+
+const API: &str = "http://localhost:8070/currencies";
+
+struct Setup {
+	ext: sp_io::TestExternalities,
+	offchain_state: Arc<RwLock<testing::OffchainState>>,
+	pool_state: Arc<RwLock<testing::PoolState>>,
+	feeder: Public,
+}
+
+fn setup(assets: u32) -> Setup {
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	offchain_state.write().timestamp = Timestamp::from_unix_millis(1_000_000);
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let keystore = KeyStore::new();
+	let feeder = SyncCryptoStore::sr25519_generate_new(&keystore, KEY_TYPE, None).unwrap();
+
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.register_extension(KeystoreExt(Arc::new(keystore)));
+
+	ext.execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(feeder, ());
+		<BatchingApi<Test>>::put(API.as_bytes().to_vec());
+		StorageValueRef::persistent(FEEDER_ENABLED_KEY).set(&true);
+		for i in 0..assets {
+			<SupportedCurrencies<Test>>::insert(asset_id(i), ());
+		}
+	});
+
+	Setup { ext, offchain_state, pool_state, feeder }
+}
+
+fn asset_id(i: u32) -> AssetId {
+	AssetId::new(b"Chain".to_vec(), format!("SYM{}", i).into_bytes())
+}
+
+fn server_response(assets: u32, price: u128) -> Vec<u8> {
+	let coin_infos = (0..assets)
+		.map(|i| {
+			format!(
+				r#"{{"symbol":"SYM{}","name":"Asset {}","blockchain":"Chain","supply":1,"lastUpdateTimestamp":1,"price":{}}}"#,
+				i, i, price
+			)
+		})
+		.collect::<Vec<_>>()
+		.join(",");
+	format!("[{}]", coin_infos).into_bytes()
+}
+
+/// Runs one full update with the given upstream price and returns once it is in storage.
+fn update_once(setup: &mut Setup, assets: u32, price: u128) {
+	let Setup { ext, offchain_state, pool_state, feeder } = setup;
+
+	ext.execute_with(|| {
+		// The whole response comes with the first chunk, the others find nothing new
+		for (i, body) in DOracle::supported_currencies_request_bodies().into_iter().enumerate() {
+			let response = if i == 0 { server_response(assets, price) } else { b"[]".to_vec() };
+			offchain_state.write().expect_request(testing::PendingRequest {
+				method: "POST".into(),
+				uri: API.into(),
+				headers: vec![("content-type".into(), "application/json".into())],
+				body,
+				response: Some(response),
+				sent: true,
+				..Default::default()
+			});
+		}
+
+		<DOracle as Hooks<u64>>::offchain_worker(1);
+
+		let tx = pool_state.write().transactions.pop().expect("no price update submitted");
+		let tx = Extrinsic::decode(&mut &tx[..]).unwrap();
+		tx.call.dispatch(RuntimeOrigin::signed(*feeder)).unwrap();
+
+		let AssetId { blockchain, symbol } = asset_id(assets - 1);
+		assert_eq!(<CoinInfosMap<Test>>::get(blockchain, symbol).price, price);
+	});
+}
+
+fn update_latency(c: &mut Criterion) {
+	let mut group = c.benchmark_group("update_latency");
+	for assets in [1, 10, 100, 1_000] {
+		let mut setup = setup(assets);
+		let mut price = 0;
+		group.bench_with_input(BenchmarkId::from_parameter(assets), &assets, |b, &assets| {
+			b.iter(|| {
+				price += 1;
+				update_once(&mut setup, assets, price)
+			})
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(benches, update_latency);
+criterion_main!(benches);
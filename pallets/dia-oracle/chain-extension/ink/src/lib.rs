@@ -0,0 +1,85 @@
+//! ink! side of the DIA oracle chain extension. Contracts use [`DiaOracleEnvironment`] as their
+//! environment and read prices through `self.env().extension()`:
+//!
+//! ```ignore
+//! #[ink::contract(env = dia_oracle_ink::DiaOracleEnvironment)]
+//! mod consumer {
+//!     // ...
+//!     #[ink(message)]
+//!     pub fn btc_price(&self) -> Result<u128, dia_oracle_ink::DiaOracleError> {
+//!         self.env().extension().get_value(b"Bitcoin".to_vec(), b"BTC".to_vec())
+//!     }
+//! }
+//! ```
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink::{
+	env::{chain_extension::FromStatusCode, DefaultEnvironment, Environment},
+	prelude::vec::Vec,
+};
+
+/// Coin info as stored by the oracle pallet, with the same SCALE encoding
+#[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct CoinInfo {
+	pub symbol: Vec<u8>,
+	pub name: Vec<u8>,
+	pub blockchain: Vec<u8>,
+	pub supply: u128,
+	pub last_update_timestamp: u64,
+	pub price: u128,
+	pub price_yesterday: u128,
+	pub volume_yesterday_usd: u128,
+	pub market_cap: Option<u128>,
+	pub high_24h: Option<u128>,
+	pub low_24h: Option<u128>,
+}
+
+#[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum DiaOracleError {
+	/// The oracle has no price for the asset
+	NoCoinInfoAvailable,
+	/// The runtime returned a status code this crate does not know
+	UnknownStatusCode,
+}
+
+impl FromStatusCode for DiaOracleError {
+	fn from_status_code(status_code: u32) -> Result<(), Self> {
+		match status_code {
+			0 => Ok(()),
+			1 => Err(Self::NoCoinInfoAvailable),
+			_ => Err(Self::UnknownStatusCode),
+		}
+	}
+}
+
+#[ink::chain_extension]
+pub trait DiaOracleExtension {
+	type ErrorCode = DiaOracleError;
+
+	/// Returns the coin info by given name
+	#[ink(extension = 1)]
+	fn get_coin_info(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<CoinInfo, DiaOracleError>;
+
+	/// Returns the price by given name
+	#[ink(extension = 2)]
+	fn get_value(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<u128, DiaOracleError>;
+}
+
+/// The default environment with the oracle extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum DiaOracleEnvironment {}
+
+impl Environment for DiaOracleEnvironment {
+	const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+	type AccountId = <DefaultEnvironment as Environment>::AccountId;
+	type Balance = <DefaultEnvironment as Environment>::Balance;
+	type Hash = <DefaultEnvironment as Environment>::Hash;
+	type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+	type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+	type ChainExtension = DiaOracleExtension;
+}
@@ -0,0 +1,164 @@
+//! pallet-contracts chain extension that lets ink! contracts read the DIA oracle. Set
+//! `DiaOracleExtension<DiaOracleModule>` as the `ChainExtension` of the contracts pallet and use
+//! the `dia-oracle-ink` crate on the contract side.
+//!
+//! Both functions take the SCALE encoded `(blockchain, symbol)` and write back a SCALE encoded
+//! `Result<_, DiaOracleError>`. The returned status code is `0` on success and
+//! [`NO_COIN_INFO_AVAILABLE`] if the oracle has no price for the asset.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use dia_oracle::DiaOracle;
+use frame_support::traits::Get;
+use pallet_contracts::chain_extension::{ChainExtension, Environment, Ext, InitState, RetVal};
+use sp_runtime::DispatchError;
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Function id of `get_coin_info`, returning `Result<CoinInfo, DiaOracleError>`
+pub const GET_COIN_INFO: u16 = 1;
+
+/// Function id of `get_value`, returning `Result<u128, DiaOracleError>`
+pub const GET_VALUE: u16 = 2;
+
+/// Status code returned when the oracle has no price for the asset
+pub const NO_COIN_INFO_AVAILABLE: u32 = 1;
+
+/// Error written to the output buffer, encoded like the one of the ink! crate
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiaOracleError {
+	NoCoinInfoAvailable,
+}
+
+pub struct DiaOracleExtension<Oracle>(PhantomData<Oracle>);
+
+impl<Oracle> Default for DiaOracleExtension<Oracle> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<C, Oracle> ChainExtension<C> for DiaOracleExtension<Oracle>
+where
+	C: pallet_contracts::Config,
+	Oracle: DiaOracle,
+{
+	fn call<E: Ext<T = C>>(
+		&mut self,
+		env: Environment<E, InitState>,
+	) -> Result<RetVal, DispatchError> {
+		let func_id = env.func_id();
+		let mut env = env.buf_in_buf_out();
+		env.charge_weight(<C as frame_system::Config>::DbWeight::get().reads(1))?;
+
+		let input = env.read(env.in_len())?;
+		let (status, output) = call::<Oracle>(func_id, &input)?;
+		env.write(&output, false, None)?;
+		Ok(RetVal::Converging(status))
+	}
+}
+
+/// Runs the function `func_id` on the encoded `input` and returns the status code together with
+/// the encoded output
+pub fn call<Oracle: DiaOracle>(
+	func_id: u16,
+	input: &[u8],
+) -> Result<(u32, Vec<u8>), DispatchError> {
+	let (blockchain, symbol) = <(Vec<u8>, Vec<u8>)>::decode(&mut &input[..])
+		.map_err(|_| DispatchError::Other("invalid arguments"))?;
+
+	match func_id {
+		GET_COIN_INFO => Ok(encode_result(Oracle::get_coin_info(blockchain, symbol))),
+		GET_VALUE =>
+			Ok(encode_result(Oracle::get_value(blockchain, symbol).map(|price| price.value))),
+		_ => Err(DispatchError::Other("unknown function")),
+	}
+}
+
+fn encode_result<T: Encode>(result: Result<T, DispatchError>) -> (u32, Vec<u8>) {
+	match result {
+		Ok(value) => (0, Ok::<T, DiaOracleError>(value).encode()),
+		Err(_) => (
+			NO_COIN_INFO_AVAILABLE,
+			Err::<T, _>(DiaOracleError::NoCoinInfoAvailable).encode(),
+		),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use dia_oracle::{CoinInfo, PriceInfo};
+
+	struct MockOracle;
+
+	impl DiaOracle for MockOracle {
+		fn get_coin_info(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<CoinInfo, DispatchError> {
+			if blockchain != b"Bitcoin" || symbol != b"BTC" {
+				return Err(DispatchError::Other("no coin info"))
+			}
+			Ok(CoinInfo {
+				symbol,
+				name: b"Bitcoin".to_vec(),
+				blockchain,
+				supply: 21_000_000,
+				last_update_timestamp: 1_670_000_000,
+				price: 16_826_489_316_709_616_000,
+				price_yesterday: 0,
+				volume_yesterday_usd: 0,
+				market_cap: None,
+				high_24h: None,
+				low_24h: None,
+			})
+		}
+
+		fn get_value(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<PriceInfo, DispatchError> {
+			Self::get_coin_info(blockchain, symbol).map(|info| PriceInfo { value: info.price })
+		}
+	}
+
+	fn input(blockchain: &[u8], symbol: &[u8]) -> Vec<u8> {
+		(blockchain, symbol).encode()
+	}
+
+	#[test]
+	fn get_coin_info_should_write_the_encoded_result() {
+		let (status, output) =
+			call::<MockOracle>(GET_COIN_INFO, &input(b"Bitcoin", b"BTC")).unwrap();
+
+		assert_eq!(status, 0);
+		assert_eq!(
+			Result::<CoinInfo, DiaOracleError>::decode(&mut &output[..]).unwrap(),
+			Ok(MockOracle::get_coin_info(b"Bitcoin".to_vec(), b"BTC".to_vec()).unwrap())
+		);
+	}
+
+	#[test]
+	fn get_value_should_write_the_encoded_result() {
+		let (status, output) = call::<MockOracle>(GET_VALUE, &input(b"Bitcoin", b"BTC")).unwrap();
+
+		assert_eq!(status, 0);
+		assert_eq!(
+			Result::<u128, DiaOracleError>::decode(&mut &output[..]).unwrap(),
+			Ok(16_826_489_316_709_616_000)
+		);
+	}
+
+	#[test]
+	fn call_should_report_missing_prices_and_invalid_input() {
+		let (status, output) = call::<MockOracle>(GET_VALUE, &input(b"Bitcoin", b"ETH")).unwrap();
+
+		assert_eq!(status, NO_COIN_INFO_AVAILABLE);
+		assert_eq!(
+			Result::<u128, DiaOracleError>::decode(&mut &output[..]).unwrap(),
+			Err(DiaOracleError::NoCoinInfoAvailable)
+		);
+		assert_eq!(
+			call::<MockOracle>(3, &input(b"Bitcoin", b"BTC")),
+			Err(DispatchError::Other("unknown function"))
+		);
+		assert_eq!(
+			call::<MockOracle>(GET_VALUE, &[1, 2, 3]),
+			Err(DispatchError::Other("invalid arguments"))
+		);
+	}
+}
@@ -0,0 +1,24 @@
+//! Feeds raw bytes to the pallet's decoders. Errors are fine, panics are not. Whatever serde_json
+//! reads as coin infos has to be read the same way by the pallet's own JSON reader.
+#![no_main]
+use codec::Decode;
+use dia_oracle::json;
+use libfuzzer_sys::fuzz_target;
+use serde::Deserialize;
+
+fuzz_target!(|data: &[u8]| {
+	let parsed = json::parse(data);
+	if let Ok(serde_json::Value::Array(entries)) = serde_json::from_slice(data) {
+		let parsed = match parsed {
+			Ok(json::Value::Array(parsed)) => parsed,
+			other => panic!("serde_json read an array, the pallet {:?}", other),
+		};
+		assert_eq!(parsed.len(), entries.len());
+		for (entry, parsed) in entries.iter().zip(&parsed) {
+			if let Ok(coin_info) = dia_oracle::CoinInfo::deserialize(entry) {
+				assert_eq!(json::coin_info(parsed), Some(coin_info));
+			}
+		}
+	}
+	let _ = Vec::<dia_oracle::CoinInfo>::decode(&mut &data[..]);
+});
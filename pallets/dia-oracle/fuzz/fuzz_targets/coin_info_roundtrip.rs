@@ -0,0 +1,61 @@
+//! Serializes arbitrary coin infos the way the batching server does and checks that the pallet
+//! reads back exactly the same values, both from JSON and through a SCALE round-trip.
+#![no_main]
+use arbitrary::Arbitrary;
+use codec::{Decode, Encode};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+	symbol: String,
+	name: String,
+	blockchain: String,
+	supply: u128,
+	last_update_timestamp: u64,
+	price: u128,
+	price_yesterday: u128,
+	volume_yesterday_usd: u128,
+	market_cap: Option<u128>,
+	high_24h: Option<u128>,
+	low_24h: Option<u128>,
+}
+
+fuzz_target!(|input: Input| {
+	let server_info = dia_batching_client::CoinInfo {
+		symbol: input.symbol.as_str().into(),
+		name: input.name.as_str().into(),
+		blockchain: input.blockchain.as_str().into(),
+		supply: input.supply,
+		last_update_timestamp: input.last_update_timestamp,
+		price: input.price,
+		price_yesterday: input.price_yesterday,
+		volume_yesterday_usd: input.volume_yesterday_usd,
+		market_cap: input.market_cap,
+		high_24h: input.high_24h,
+		low_24h: input.low_24h,
+	};
+	let body = serde_json::to_vec(&vec![server_info]).expect("server serialization failed");
+
+	let entries = match dia_oracle::json::parse(&body) {
+		Ok(dia_oracle::json::Value::Array(entries)) => entries,
+		other => panic!("pallet rejected a server response: {:?}", other),
+	};
+	assert_eq!(entries.len(), 1);
+
+	let coin_info = &dia_oracle::json::coin_info(&entries[0]).expect("pallet rejected a coin info");
+	assert_eq!(coin_info.symbol, input.symbol.as_bytes());
+	assert_eq!(coin_info.name, input.name.as_bytes());
+	assert_eq!(coin_info.blockchain, input.blockchain.as_bytes());
+	assert_eq!(coin_info.supply, input.supply);
+	assert_eq!(coin_info.last_update_timestamp, input.last_update_timestamp);
+	assert_eq!(coin_info.price, input.price);
+	assert_eq!(coin_info.price_yesterday, input.price_yesterday);
+	assert_eq!(coin_info.volume_yesterday_usd, input.volume_yesterday_usd);
+	assert_eq!(coin_info.market_cap, input.market_cap);
+	assert_eq!(coin_info.high_24h, input.high_24h);
+	assert_eq!(coin_info.low_24h, input.low_24h);
+
+	let encoded = coin_info.encode();
+	let decoded = dia_oracle::CoinInfo::decode(&mut &encoded[..]).expect("SCALE round-trip failed");
+	assert_eq!(&decoded, coin_info);
+});
@@ -0,0 +1,233 @@
+//! [`DiaOracle`] implementation for unit tests of pallets consuming prices, without a runtime
+//! around the oracle pallet. Set `MockDiaOracle` wherever the pallet under test expects a
+//! `DiaOracle` and configure it from the test:
+//!
+//! ```
+//! use dia_oracle::DiaOracle;
+//! use dia_oracle_mock::MockDiaOracle;
+//!
+//! MockDiaOracle::set_price(b"Bitcoin", b"BTC", 20_000);
+//! let price = MockDiaOracle::get_value(b"Bitcoin".to_vec(), b"BTC".to_vec()).unwrap();
+//! assert_eq!(price.value, 20_000);
+//! ```
+//!
+//! The state is kept per thread, so tests running in parallel do not see each other's prices.
+//! Call [`MockDiaOracle::reset`] between scenarios of the same test.
+use dia_oracle::{error_index, CoinInfo, DiaOracle, PriceInfo, PriceWithMetadata};
+use sp_runtime::{DispatchError, ModuleError};
+use std::{cell::RefCell, collections::BTreeMap};
+
+/// Unix time in seconds the mock starts at
+pub const DEFAULT_NOW: u64 = 1_670_000_000;
+
+/// Age in seconds after which the mock reports a price as stale unless set otherwise
+pub const DEFAULT_MAX_AGE: u64 = 24 * 60 * 60;
+
+/// Index of the oracle pallet in the runtime the mock stands in for, unless set otherwise
+pub const DEFAULT_PALLET_INDEX: u8 = 0;
+
+#[derive(Default)]
+struct State {
+	coin_infos: BTreeMap<(Vec<u8>, Vec<u8>), CoinInfo>,
+	error: Option<DispatchError>,
+	now: Option<u64>,
+	max_age: Option<u64>,
+	pallet_index: Option<u8>,
+}
+
+thread_local! {
+	static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+pub struct MockDiaOracle;
+
+impl MockDiaOracle {
+	/// Stores the coin info of an asset as is
+	pub fn set_coin_info(blockchain: &[u8], symbol: &[u8], coin_info: CoinInfo) {
+		STATE.with(|state| {
+			state.borrow_mut().coin_infos.insert((blockchain.to_vec(), symbol.to_vec()), coin_info)
+		});
+	}
+
+	/// Stores a price for an asset, last updated [`MockDiaOracle::now`]
+	pub fn set_price(blockchain: &[u8], symbol: &[u8], price: u128) {
+		let coin_info = CoinInfo {
+			symbol: symbol.to_vec(),
+			name: symbol.to_vec(),
+			blockchain: blockchain.to_vec(),
+			supply: 0,
+			last_update_timestamp: Self::now(),
+			price,
+			price_yesterday: price,
+			volume_yesterday_usd: 0,
+			market_cap: None,
+			high_24h: None,
+			low_24h: None,
+		};
+		Self::set_coin_info(blockchain, symbol, coin_info);
+	}
+
+	/// Ages the price of an asset, so that it was last updated `age` seconds before
+	/// [`MockDiaOracle::now`]. Does nothing for assets without a price.
+	pub fn set_age(blockchain: &[u8], symbol: &[u8], age: u64) {
+		let now = Self::now();
+		STATE.with(|state| {
+			let mut state = state.borrow_mut();
+			if let Some(coin_info) =
+				state.coin_infos.get_mut(&(blockchain.to_vec(), symbol.to_vec()))
+			{
+				coin_info.last_update_timestamp = now.saturating_sub(age);
+			}
+		});
+	}
+
+	pub fn remove(blockchain: &[u8], symbol: &[u8]) {
+		STATE.with(|state| {
+			state.borrow_mut().coin_infos.remove(&(blockchain.to_vec(), symbol.to_vec()))
+		});
+	}
+
+	/// Makes every lookup fail with `error` until it is cleared with `None`
+	pub fn fail_with(error: Option<DispatchError>) {
+		STATE.with(|state| state.borrow_mut().error = error);
+	}
+
+	/// Current Unix time in seconds as seen by the mock, [`DEFAULT_NOW`] unless set
+	pub fn now() -> u64 {
+		STATE.with(|state| state.borrow().now.unwrap_or(DEFAULT_NOW))
+	}
+
+	/// Moves the clock of the mock, e.g. to let stored prices grow old
+	pub fn set_now(now: u64) {
+		STATE.with(|state| state.borrow_mut().now = Some(now));
+	}
+
+	/// Sets the age in seconds after which prices are reported as stale, [`DEFAULT_MAX_AGE`]
+	/// unless set
+	pub fn set_max_age(max_age: u64) {
+		STATE.with(|state| state.borrow_mut().max_age = Some(max_age));
+	}
+
+	/// Sets the index of the oracle pallet in the runtime of the test, so that
+	/// [`MockDiaOracle::no_coin_info_available`] equals the pallet's error in that runtime
+	pub fn set_pallet_index(index: u8) {
+		STATE.with(|state| state.borrow_mut().pallet_index = Some(index));
+	}
+
+	/// Error returned for assets without a price, the pallet's `NoCoinInfoAvailable` at
+	/// [`DEFAULT_PALLET_INDEX`] unless set otherwise
+	pub fn no_coin_info_available() -> DispatchError {
+		let index = STATE.with(|state| state.borrow().pallet_index.unwrap_or(DEFAULT_PALLET_INDEX));
+		DispatchError::Module(ModuleError {
+			index,
+			error: [error_index::NO_COIN_INFO_AVAILABLE, 0, 0, 0],
+			message: Some("NoCoinInfoAvailable"),
+		})
+	}
+
+	/// Removes all prices, the injected error, the clock, the maximum age and the pallet index
+	pub fn reset() {
+		STATE.with(|state| *state.borrow_mut() = State::default());
+	}
+}
+
+impl DiaOracle for MockDiaOracle {
+	fn get_coin_info(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<CoinInfo, DispatchError> {
+		STATE.with(|state| {
+			let state = state.borrow();
+			if let Some(error) = state.error {
+				return Err(error)
+			}
+			state
+				.coin_infos
+				.get(&(blockchain, symbol))
+				.cloned()
+				.ok_or_else(Self::no_coin_info_available)
+		})
+	}
+
+	fn get_value(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<PriceInfo, DispatchError> {
+		Self::get_coin_info(blockchain, symbol).map(|info| PriceInfo { value: info.price })
+	}
+
+	fn get_value_with_metadata(
+		blockchain: Vec<u8>,
+		symbol: Vec<u8>,
+	) -> Result<PriceWithMetadata, DispatchError> {
+		let info = Self::get_coin_info(blockchain, symbol)?;
+		let max_age = STATE.with(|state| state.borrow().max_age.unwrap_or(DEFAULT_MAX_AGE));
+		Ok(PriceWithMetadata {
+			value: info.price,
+			last_update_timestamp: info.last_update_timestamp,
+			supply: info.supply,
+			is_stale: Self::now().saturating_sub(info.last_update_timestamp) > max_age,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn btc() -> Result<CoinInfo, DispatchError> {
+		MockDiaOracle::get_coin_info(b"Bitcoin".to_vec(), b"BTC".to_vec())
+	}
+
+	#[test]
+	fn set_price_should_be_returned_until_removed() {
+		MockDiaOracle::set_price(b"Bitcoin", b"BTC", 20_000);
+
+		let coin_info = btc().unwrap();
+		assert_eq!((coin_info.price, coin_info.last_update_timestamp), (20_000, DEFAULT_NOW));
+		assert_eq!(
+			MockDiaOracle::get_value(b"Bitcoin".to_vec(), b"BTC".to_vec()),
+			Ok(PriceInfo { value: 20_000 })
+		);
+
+		MockDiaOracle::remove(b"Bitcoin", b"BTC");
+		assert_eq!(btc(), Err(MockDiaOracle::no_coin_info_available()));
+	}
+
+	#[test]
+	fn missing_prices_should_fail_like_the_pallet() {
+		let module_error = |index| {
+			DispatchError::Module(ModuleError {
+				index,
+				error: [error_index::NO_COIN_INFO_AVAILABLE, 0, 0, 0],
+				message: None,
+			})
+		};
+		assert_eq!(btc(), Err(module_error(DEFAULT_PALLET_INDEX)));
+
+		MockDiaOracle::set_pallet_index(7);
+		assert_eq!(btc(), Err(module_error(7)));
+	}
+
+	#[test]
+	fn fail_with_should_override_every_lookup() {
+		MockDiaOracle::set_price(b"Bitcoin", b"BTC", 20_000);
+
+		MockDiaOracle::fail_with(Some(DispatchError::Other("paused")));
+		assert_eq!(btc(), Err(DispatchError::Other("paused")));
+
+		MockDiaOracle::fail_with(None);
+		assert!(btc().is_ok());
+	}
+
+	#[test]
+	fn set_age_should_move_the_timestamp_back_from_now() {
+		MockDiaOracle::set_now(2_000);
+		MockDiaOracle::set_price(b"Bitcoin", b"BTC", 20_000);
+
+		MockDiaOracle::set_age(b"Bitcoin", b"BTC", 500);
+		assert_eq!(btc().map(|info| info.last_update_timestamp), Ok(1_500));
+
+		MockDiaOracle::set_max_age(499);
+		let metadata = MockDiaOracle::get_value_with_metadata(b"Bitcoin".to_vec(), b"BTC".to_vec());
+		assert_eq!(metadata.map(|metadata| metadata.is_stale), Ok(true));
+
+		MockDiaOracle::reset();
+		assert_eq!(MockDiaOracle::now(), DEFAULT_NOW);
+		assert_eq!(btc(), Err(MockDiaOracle::no_coin_info_available()));
+	}
+}
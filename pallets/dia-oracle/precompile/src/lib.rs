@@ -0,0 +1,204 @@
+//! Frontier precompile that lets EVM contracts read the DIA oracle. Register it at an address of
+//! the runtime's precompile set, with the oracle pallet as `Oracle`, and call it through:
+//!
+//! ```solidity
+//! interface DiaOracle {
+//!     function getValue(string memory blockchain, string memory symbol)
+//!         external view returns (uint256 price);
+//!     function getCoinInfo(string memory blockchain, string memory symbol)
+//!         external view returns (
+//!             string memory symbol,
+//!             string memory name,
+//!             string memory blockchain,
+//!             uint256 supply,
+//!             uint256 lastUpdateTimestamp,
+//!             uint256 price
+//!         );
+//! }
+//! ```
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use dia_oracle::DiaOracle;
+use fp_evm::{
+	ExitError, ExitSucceed, Precompile, PrecompileFailure, PrecompileHandle, PrecompileOutput,
+	PrecompileResult,
+};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Selector of `getValue(string,string)`
+pub const GET_VALUE_SELECTOR: [u8; 4] = [0xb4, 0x15, 0x2a, 0x7a];
+
+/// Selector of `getCoinInfo(string,string)`
+pub const GET_COIN_INFO_SELECTOR: [u8; 4] = [0xc6, 0x04, 0xe4, 0xa5];
+
+/// Gas charged for every call, covering the storage read of the coin info
+pub const READ_GAS_COST: u64 = 5_000;
+
+const WORD: usize = 32;
+
+const INVALID_ARGUMENTS: &str = "invalid arguments";
+
+pub struct DiaOraclePrecompile<Oracle>(PhantomData<Oracle>);
+
+impl<Oracle: DiaOracle> Precompile for DiaOraclePrecompile<Oracle> {
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		handle.record_cost(READ_GAS_COST)?;
+		let output = call::<Oracle>(handle.input()).map_err(|e| PrecompileFailure::Error {
+			exit_status: ExitError::Other(e.into()),
+		})?;
+		Ok(PrecompileOutput { exit_status: ExitSucceed::Returned, output })
+	}
+}
+
+/// Runs the ABI encoded call in `input` against the oracle and returns the ABI encoded result
+pub fn call<Oracle: DiaOracle>(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+	let selector = input.get(..4).ok_or("missing selector")?;
+	let arguments = &input[4..];
+
+	if selector == GET_VALUE_SELECTOR {
+		let (blockchain, symbol) = (read_string(arguments, 0)?, read_string(arguments, 1)?);
+		let price = Oracle::get_value(blockchain, symbol).map_err(|_| "no price for the asset")?;
+		Ok(encode_uint(price.value).to_vec())
+	} else if selector == GET_COIN_INFO_SELECTOR {
+		let (blockchain, symbol) = (read_string(arguments, 0)?, read_string(arguments, 1)?);
+		let coin_info =
+			Oracle::get_coin_info(blockchain, symbol).map_err(|_| "no price for the asset")?;
+
+		let strings = [
+			encode_string(&coin_info.symbol),
+			encode_string(&coin_info.name),
+			encode_string(&coin_info.blockchain),
+		];
+		let mut output = Vec::new();
+		let mut offset = 6 * WORD;
+		for string in &strings {
+			output.extend_from_slice(&encode_uint(offset as u128));
+			offset += string.len();
+		}
+		output.extend_from_slice(&encode_uint(coin_info.supply));
+		output.extend_from_slice(&encode_uint(coin_info.last_update_timestamp.into()));
+		output.extend_from_slice(&encode_uint(coin_info.price));
+		for string in &strings {
+			output.extend_from_slice(string);
+		}
+		Ok(output)
+	} else {
+		Err("unknown selector")
+	}
+}
+
+/// Reads the `string` at position `index` of the ABI encoded `arguments`
+fn read_string(arguments: &[u8], index: usize) -> Result<Vec<u8>, &'static str> {
+	let offset = read_usize(arguments, index * WORD)?;
+	let length = read_usize(arguments, offset)?;
+	let start = offset.checked_add(WORD).ok_or(INVALID_ARGUMENTS)?;
+	let end = start.checked_add(length).ok_or(INVALID_ARGUMENTS)?;
+	arguments.get(start..end).map(<[u8]>::to_vec).ok_or(INVALID_ARGUMENTS)
+}
+
+/// Reads the word at `position` as an offset or length, which have to fit into a `u32`
+fn read_usize(arguments: &[u8], position: usize) -> Result<usize, &'static str> {
+	let end = position.checked_add(WORD).ok_or(INVALID_ARGUMENTS)?;
+	let word = arguments.get(position..end).ok_or(INVALID_ARGUMENTS)?;
+	let (high, low) = word.split_at(WORD - 4);
+	if high.iter().any(|byte| *byte != 0) {
+		return Err(INVALID_ARGUMENTS)
+	}
+	Ok(low.iter().fold(0, |value, byte| value << 8 | usize::from(*byte)))
+}
+
+fn encode_uint(value: u128) -> [u8; WORD] {
+	let mut word = [0; WORD];
+	word[WORD - 16..].copy_from_slice(&value.to_be_bytes());
+	word
+}
+
+/// Encodes the length and the zero padded contents of a `string`
+fn encode_string(string: &[u8]) -> Vec<u8> {
+	let mut encoded = encode_uint(string.len() as u128).to_vec();
+	encoded.extend_from_slice(string);
+	encoded.resize(WORD + (string.len() + WORD - 1) / WORD * WORD, 0);
+	encoded
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use dia_oracle::{CoinInfo, PriceInfo};
+	use sp_runtime::DispatchError;
+
+	struct MockOracle;
+
+	impl DiaOracle for MockOracle {
+		fn get_coin_info(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<CoinInfo, DispatchError> {
+			if blockchain != b"Bitcoin" || symbol != b"BTC" {
+				return Err(DispatchError::Other("no coin info"))
+			}
+			Ok(CoinInfo {
+				symbol,
+				name: b"Bitcoin".to_vec(),
+				blockchain,
+				supply: 21_000_000,
+				last_update_timestamp: 1_670_000_000,
+				price: 16_826_489_316_709_616_000,
+				price_yesterday: 0,
+				volume_yesterday_usd: 0,
+				market_cap: None,
+				high_24h: None,
+				low_24h: None,
+			})
+		}
+
+		fn get_value(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<PriceInfo, DispatchError> {
+			Self::get_coin_info(blockchain, symbol).map(|info| PriceInfo { value: info.price })
+		}
+	}
+
+	fn encode_call(selector: [u8; 4], blockchain: &[u8], symbol: &[u8]) -> Vec<u8> {
+		let blockchain = encode_string(blockchain);
+		let mut input = selector.to_vec();
+		input.extend_from_slice(&encode_uint(2 * WORD as u128));
+		input.extend_from_slice(&encode_uint((2 * WORD + blockchain.len()) as u128));
+		input.extend(blockchain);
+		input.extend(encode_string(symbol));
+		input
+	}
+
+	#[test]
+	fn get_value_should_return_the_price() {
+		let output =
+			call::<MockOracle>(&encode_call(GET_VALUE_SELECTOR, b"Bitcoin", b"BTC")).unwrap();
+
+		assert_eq!(output, encode_uint(16_826_489_316_709_616_000).to_vec());
+	}
+
+	#[test]
+	fn get_coin_info_should_encode_all_fields() {
+		let output =
+			call::<MockOracle>(&encode_call(GET_COIN_INFO_SELECTOR, b"Bitcoin", b"BTC")).unwrap();
+
+		assert_eq!(read_string(&output, 0).unwrap(), b"BTC".to_vec());
+		assert_eq!(read_string(&output, 1).unwrap(), b"Bitcoin".to_vec());
+		assert_eq!(read_string(&output, 2).unwrap(), b"Bitcoin".to_vec());
+		assert_eq!(&output[3 * WORD..4 * WORD], &encode_uint(21_000_000));
+		assert_eq!(&output[4 * WORD..5 * WORD], &encode_uint(1_670_000_000));
+		assert_eq!(&output[5 * WORD..6 * WORD], &encode_uint(16_826_489_316_709_616_000));
+		assert_eq!(output.len() % WORD, 0);
+	}
+
+	#[test]
+	fn call_should_reject_invalid_input() {
+		let input = encode_call(GET_VALUE_SELECTOR, b"Bitcoin", b"BTC");
+
+		assert_eq!(call::<MockOracle>(&input[..3]), Err("missing selector"));
+		assert_eq!(call::<MockOracle>(&input[..input.len() - WORD]), Err(INVALID_ARGUMENTS));
+		assert_eq!(
+			call::<MockOracle>(&encode_call([0; 4], b"Bitcoin", b"BTC")),
+			Err("unknown selector")
+		);
+		assert_eq!(
+			call::<MockOracle>(&encode_call(GET_VALUE_SELECTOR, b"Bitcoin", b"ETH")),
+			Err("no price for the asset")
+		);
+	}
+}
@@ -1,12 +1,32 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-pub use dia_oracle::{CoinInfo, PriceInfo};
+use codec::Codec;
+pub use dia_oracle::{error_index, AssetOverview, CoinInfo, FeederStats, PriceInfo};
+/// `CoinInfo` as returned by version 1 of the API
+pub use dia_oracle::migrations::v2::OldCoinInfo as CoinInfoV1;
 use frame_support::sp_std::vec::Vec;
-use sp_runtime::DispatchError;
+use sp_runtime::{traits::NumberFor, DispatchError};
 
 sp_api::decl_runtime_apis! {
-	pub trait DiaOracleApi{
+	/// Version 1 only had `get_coin_info` and `get_value`, version 2 added the other methods and
+	/// the 24h and market fields of `CoinInfo`
+	#[api_version(2)]
+	pub trait DiaOracleApi<AccountId> where AccountId: Codec {
 		fn get_coin_info(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<CoinInfo, DispatchError>;
-		fn get_value(lockchain: Vec<u8>, symbol: Vec<u8>) -> Result<PriceInfo,DispatchError>;
+		#[changed_in(2)]
+		fn get_coin_info(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<CoinInfoV1, DispatchError>;
+		fn get_value(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<PriceInfo,DispatchError>;
+		fn get_supply(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<u128, DispatchError>;
+		fn get_asset_overview(blockchain: Vec<u8>, symbol: Vec<u8>) -> AssetOverview;
+		fn get_coin_infos(assets: Vec<(Vec<u8>, Vec<u8>)>) -> Vec<Result<CoinInfo, DispatchError>>;
+		fn list_supported_currencies() -> Vec<(Vec<u8>, Vec<u8>)>;
+		fn list_authorized_accounts() -> Vec<AccountId>;
+		fn get_last_updated(blockchain: Vec<u8>, symbol: Vec<u8>) -> Option<u64>;
+		fn is_stale(blockchain: Vec<u8>, symbol: Vec<u8>) -> bool;
+		fn convert(from: (Vec<u8>, Vec<u8>), to: (Vec<u8>, Vec<u8>), amount: u128) -> Result<u128, DispatchError>;
+		fn price_decimals() -> u8;
+		fn get_coins_by_blockchain(blockchain: Vec<u8>) -> Vec<CoinInfo>;
+		fn get_feeder_stats(account: AccountId) -> FeederStats<NumberFor<Block>>;
+		fn get_price_submitter(blockchain: Vec<u8>, symbol: Vec<u8>) -> Option<(AccountId, NumberFor<Block>)>;
 	}
 }
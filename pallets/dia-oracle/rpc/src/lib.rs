@@ -1,47 +1,476 @@
-use dia_oracle_runtime_api::{CoinInfo, PriceInfo};
+use codec::Codec;
+use dia_oracle_runtime_api::{error_index, AssetOverview, CoinInfo, CoinInfoV1, PriceInfo};
+use futures::{future, stream, FutureExt, StreamExt};
 use jsonrpsee::{
 	core::RpcResult,
 	proc_macros::rpc,
-	types::error::{CallError, ErrorObject},
+	types::{
+		error::{CallError, ErrorObject},
+		SubscriptionResult,
+	},
+	SubscriptionSink,
 };
-use sp_api::ProvideRuntimeApi;
+use sc_client_api::BlockchainEvents;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sp_api::{ApiError, ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
-use sp_core::Bytes;
-use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use sp_core::{bytes, traits::SpawnNamed};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, NumberFor},
+	DispatchError, ModuleError, SaturatedConversion,
+};
 
 pub use dia_oracle_runtime_api::DiaOracleApi as DiaOracleRuntimeApi;
 
-use std::sync::Arc;
+use std::{
+	collections::BTreeMap,
+	ops::Deref,
+	sync::Arc,
+	time::{SystemTime, UNIX_EPOCH},
+};
 
 #[rpc(client, server)]
-pub trait DiaOracleApi<BlockHash> {
+pub trait DiaOracleApi<BlockHash, AccountId> {
 	#[method(name = "dia_getCoinInfo")]
 	fn get_coin_info(
 		&self,
-		blockchain: Bytes,
-		symbol: Bytes,
+		blockchain: AssetName,
+		symbol: AssetName,
 		at: Option<BlockHash>,
-	) -> RpcResult<CoinInfo>;
+	) -> RpcResult<CoinInfoResponse>;
 
 	#[method(name = "dia_getValue")]
 	fn get_value(
 		&self,
-		blockchain: Bytes,
-		symbol: Bytes,
+		blockchain: AssetName,
+		symbol: AssetName,
 		at: Option<BlockHash>,
 	) -> RpcResult<PriceInfo>;
+
+	/// Returns the supply of the asset reported with its last price, as a decimal string
+	#[method(name = "dia_getSupply")]
+	fn get_supply(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		at: Option<BlockHash>,
+	) -> RpcResult<String>;
+
+	#[method(name = "dia_getAssetOverview")]
+	fn get_asset_overview(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		at: Option<BlockHash>,
+	) -> RpcResult<AssetOverviewResponse>;
+
+	/// Returns the coin infos of all given `(blockchain, symbol)` pairs in one call, in the same
+	/// order, with `null` for assets without a price
+	#[method(name = "dia_getCoinInfos")]
+	fn get_coin_infos(
+		&self,
+		assets: Vec<(AssetName, AssetName)>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<Option<CoinInfoResponse>>>;
+
+	/// Returns the coin infos of all assets of one blockchain, sorted by symbol
+	#[method(name = "dia_getCoinsByBlockchain")]
+	fn get_coins_by_blockchain(
+		&self,
+		blockchain: AssetName,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<CoinInfoResponse>>;
+
+	/// Returns the `(blockchain, symbol)` pairs of all currencies the oracle feeds
+	#[method(name = "dia_listSupportedCurrencies")]
+	fn list_supported_currencies(&self, at: Option<BlockHash>) -> RpcResult<Vec<(String, String)>>;
+
+	/// Returns the accounts currently allowed to feed prices
+	#[method(name = "dia_getAuthorizedAccounts")]
+	fn get_authorized_accounts(&self, at: Option<BlockHash>) -> RpcResult<Vec<AccountId>>;
+
+	/// Returns the `lastUpdateTimestamp` of the asset's price, `null` if it has none
+	#[method(name = "dia_getLastUpdated")]
+	fn get_last_updated(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<u64>>;
+
+	/// Whether the asset has no price or one older than the runtime's `MaxPriceAge` at the block
+	#[method(name = "dia_isStale")]
+	fn is_stale(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		at: Option<BlockHash>,
+	) -> RpcResult<bool>;
+
+	/// Returns the asset's price at every `step`th block from `from_block` to `to_block`, both
+	/// included. Blocks whose state is not available (pruned or not yet imported) are skipped.
+	#[method(name = "dia_getValueHistory")]
+	fn get_value_history(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		from_block: u64,
+		to_block: u64,
+		step: u64,
+	) -> RpcResult<Vec<HistoryPoint>>;
+
+	/// Converts `amount` of the first asset into the second at their current prices. The amount is
+	/// a decimal string like `"1.5"`, and so is the result. Fails with the `StalePrice` error code
+	/// if either price is older than the pallet's `MaxPriceAge`.
+	#[method(name = "dia_convert")]
+	fn convert(
+		&self,
+		from: (AssetName, AssetName),
+		to: (AssetName, AssetName),
+		amount: String,
+		at: Option<BlockHash>,
+	) -> RpcResult<String>;
+
+	/// Pushes the coin info of every asset whose price changes in a new best block, or in a newly
+	/// finalized block if the node reads finalized blocks by default
+	#[subscription(
+		name = "dia_subscribePrices" => "dia_prices",
+		unsubscribe = "dia_unsubscribePrices",
+		item = PriceUpdate
+	)]
+	fn subscribe_prices(&self);
+}
+
+/// Blockchain or symbol parameter, given either as a plain string (`"BTC"`) or as hex encoded
+/// bytes (`"0x425443"`). Strings starting with `0x` that are not valid hex, like `"0xBTC"`, are
+/// taken as plain strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetName(pub Vec<u8>);
+
+impl Deref for AssetName {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl Serialize for AssetName {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		match std::str::from_utf8(&self.0) {
+			Ok(name) if !name.starts_with("0x") => serializer.serialize_str(name),
+			_ => serializer.serialize_str(&bytes::to_hex(&self.0, false)),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for AssetName {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let name = String::deserialize(deserializer)?;
+		let hex = name.starts_with("0x").then(|| bytes::from_hex(&name).ok()).flatten();
+		Ok(AssetName(hex.unwrap_or_else(|| name.into_bytes())))
+	}
+}
+
+/// Coin info with readable names, as returned by `dia_getCoinInfo`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinInfoResponse {
+	pub symbol: String,
+	pub name: String,
+	pub blockchain: String,
+	pub supply: u128,
+	pub last_update_timestamp: u64,
+	pub price: u128,
+	pub price_yesterday: u128,
+	pub volume_yesterday_usd: u128,
+	pub market_cap: Option<u128>,
+	pub high_24h: Option<u128>,
+	pub low_24h: Option<u128>,
+}
+
+impl From<CoinInfo> for CoinInfoResponse {
+	fn from(coin_info: CoinInfo) -> Self {
+		Self {
+			symbol: to_string(&coin_info.symbol),
+			name: to_string(&coin_info.name),
+			blockchain: to_string(&coin_info.blockchain),
+			supply: coin_info.supply,
+			last_update_timestamp: coin_info.last_update_timestamp,
+			price: coin_info.price,
+			price_yesterday: coin_info.price_yesterday,
+			volume_yesterday_usd: coin_info.volume_yesterday_usd,
+			market_cap: coin_info.market_cap,
+			high_24h: coin_info.high_24h,
+			low_24h: coin_info.low_24h,
+		}
+	}
+}
+
+fn to_string(bytes: &[u8]) -> String {
+	String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Maximum number of blocks queried by one `dia_getValueHistory` call
+const MAX_HISTORY_POINTS: u64 = 1_000;
+
+/// Price of an asset at one block, as returned by `dia_getValueHistory`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPoint {
+	pub block_number: u64,
+	/// `null` if the asset had no price at that block
+	pub value: Option<PriceInfo>,
+	pub last_update_timestamp: Option<u64>,
+}
+
+/// Block numbers queried for a price history, `None` if the range is empty, the step is zero or
+/// there are more than `MAX_HISTORY_POINTS` of them
+fn history_blocks(from_block: u64, to_block: u64, step: u64) -> Option<Vec<u64>> {
+	if step == 0 || from_block > to_block || (to_block - from_block) / step >= MAX_HISTORY_POINTS {
+		return None
+	}
+	Some((from_block..=to_block).step_by(step as usize).collect())
+}
+
+/// Reads a non-negative decimal string as a fixed point number with `decimals` decimals.
+/// Digits beyond `decimals` are cut off.
+fn parse_decimal(amount: &str, decimals: u8) -> Option<u128> {
+	let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+	let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+	if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+		return None
+	}
+	let fraction: String =
+		fraction.chars().chain(std::iter::repeat('0')).take(decimals.into()).collect();
+	format!("{}{}", integer, fraction).parse().ok()
+}
+
+/// Writes a fixed point number with `decimals` decimals as a decimal string, without trailing
+/// zeros
+fn format_decimal(amount: u128, decimals: u8) -> String {
+	let digits = format!("{:0>width$}", amount, width = usize::from(decimals) + 1);
+	let (integer, fraction) = digits.split_at(digits.len() - usize::from(decimals));
+	match fraction.trim_end_matches('0') {
+		"" => integer.to_string(),
+		fraction => format!("{}.{}", integer, fraction),
+	}
+}
+
+/// Item of the `dia_subscribePrices` subscription
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceUpdate {
+	pub blockchain: String,
+	pub symbol: String,
+	pub coin_info: CoinInfoResponse,
+}
+
+type Asset = (Vec<u8>, Vec<u8>);
+
+/// Returns the prices that differ from `last_seen`, and remembers them there
+fn price_updates(
+	prices: Vec<(Asset, CoinInfo)>,
+	last_seen: &mut BTreeMap<Asset, CoinInfo>,
+) -> Vec<PriceUpdate> {
+	let mut updates = Vec::new();
+	for (asset, coin_info) in prices {
+		if last_seen.get(&asset) == Some(&coin_info) {
+			continue
+		}
+		last_seen.insert(asset.clone(), coin_info.clone());
+		updates.push(PriceUpdate {
+			blockchain: to_string(&asset.0),
+			symbol: to_string(&asset.1),
+			coin_info: coin_info.into(),
+		});
+	}
+	updates
+}
+
+/// Reads the prices of all supported currencies at the given block
+fn prices_at<C, Block, AccountId>(client: &C, hash: Block::Hash) -> Vec<(Asset, CoinInfo)>
+where
+	Block: BlockT,
+	AccountId: Codec,
+	C: ProvideRuntimeApi<Block>,
+	C::Api: DiaOracleRuntimeApi<Block, AccountId>,
+{
+	let api = client.runtime_api();
+	let at = BlockId::hash(hash);
+	if !has_api_v2::<_, Block, AccountId>(&*api, &at).unwrap_or(false) {
+		return Vec::new()
+	}
+	let prices = api.list_supported_currencies(&at).and_then(|assets| {
+		let coin_infos = api.get_coin_infos(&at, assets.clone())?;
+		Ok(assets.into_iter().zip(coin_infos).collect::<Vec<_>>())
+	});
+
+	prices
+		.unwrap_or_default()
+		.into_iter()
+		.filter_map(|(asset, coin_info)| Some((asset, coin_info.ok()?)))
+		.collect()
+}
+
+/// Whether the runtime implements version 2 of the `DiaOracleApi` at the block, which added every
+/// method but `get_coin_info` and `get_value`
+fn has_api_v2<Api, Block, AccountId>(api: &Api, at: &BlockId<Block>) -> Result<bool, ApiError>
+where
+	Block: BlockT,
+	AccountId: Codec,
+	Api: ApiExt<Block> + DiaOracleRuntimeApi<Block, AccountId>,
+{
+	api.has_api_with::<dyn DiaOracleRuntimeApi<Block, AccountId>, _>(at, |version| version >= 2)
+}
+
+/// Fails with `UnsupportedRuntime` if the runtime does not implement version 2 of the
+/// `DiaOracleApi` at the block, which `method` needs
+fn ensure_api_v2<Api, Block, AccountId>(
+	api: &Api,
+	at: &BlockId<Block>,
+	method: &str,
+) -> Result<(), CallError>
+where
+	Block: BlockT,
+	AccountId: Codec,
+	Api: ApiExt<Block> + DiaOracleRuntimeApi<Block, AccountId>,
+{
+	let has_api_v2 = has_api_v2::<_, Block, AccountId>(api, at).map_err(|e| {
+		CallError::Custom(ErrorObject::owned(
+			Error::RuntimeError.into(),
+			"Unable to query the runtime api version.",
+			Some(format!("{:?}", e)),
+		))
+	})?;
+	if !has_api_v2 {
+		return Err(CallError::Custom(ErrorObject::owned(
+			Error::UnsupportedRuntime.into(),
+			"The runtime does not support this call.",
+			Some(format!("{} needs version 2 of DiaOracleApi", method)),
+		)))
+	}
+	Ok(())
+}
+
+/// Calls `get_coin_info`, through its version 1 signature on runtimes that predate version 2
+fn coin_info_at<Api, Block, AccountId>(
+	api: &Api,
+	at: &BlockId<Block>,
+	blockchain: &[u8],
+	symbol: &[u8],
+) -> Result<Result<CoinInfo, DispatchError>, ApiError>
+where
+	Block: BlockT,
+	AccountId: Codec,
+	Api: ApiExt<Block> + DiaOracleRuntimeApi<Block, AccountId>,
+{
+	let version = api.api_version::<dyn DiaOracleRuntimeApi<Block, AccountId>>(at)?;
+	if version == Some(1) {
+		#[allow(deprecated)]
+		let coin_info =
+			api.get_coin_info_before_version_2(at, blockchain.to_vec(), symbol.to_vec())?;
+		return Ok(coin_info.map(from_v1))
+	}
+	api.get_coin_info(at, blockchain.to_vec(), symbol.to_vec())
+}
+
+/// Coin info of version 1 of the API, without the 24h and market fields
+fn from_v1(coin_info: CoinInfoV1) -> CoinInfo {
+	CoinInfo {
+		symbol: coin_info.symbol,
+		name: coin_info.name,
+		blockchain: coin_info.blockchain,
+		supply: coin_info.supply,
+		last_update_timestamp: coin_info.last_update_timestamp,
+		price: coin_info.price,
+		..Default::default()
+	}
+}
+
+/// Human-readable summary of one asset, as returned by `dia_getAssetOverview`.
+///
+/// Amounts are strings because they do not fit into JavaScript numbers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetOverviewResponse {
+	pub blockchain: String,
+	pub symbol: String,
+	pub name: Option<String>,
+	pub supported: bool,
+	pub price: Option<String>,
+	pub supply: Option<String>,
+	pub last_update_timestamp: Option<u64>,
+	/// Seconds between the last update and the time of the RPC call
+	pub seconds_since_update: Option<u64>,
+	/// Block of the most recent update batch from any feeder
+	pub last_batch_block: Option<u64>,
+}
+
+impl AssetOverviewResponse {
+	fn new(blockchain: &[u8], symbol: &[u8], overview: AssetOverview, now: u64) -> Self {
+		let AssetOverview { supported, coin_info, last_batch_block } = overview;
+		Self {
+			blockchain: to_string(blockchain),
+			symbol: to_string(symbol),
+			name: coin_info.as_ref().map(|c| to_string(&c.name)),
+			supported,
+			price: coin_info.as_ref().map(|c| c.price.to_string()),
+			supply: coin_info.as_ref().map(|c| c.supply.to_string()),
+			last_update_timestamp: coin_info.as_ref().map(|c| c.last_update_timestamp),
+			seconds_since_update: coin_info
+				.as_ref()
+				.map(|c| now.saturating_sub(c.last_update_timestamp)),
+			last_batch_block,
+		}
+	}
 }
 
 /// A struct that implements the [`DiaOracleApi`].
 pub struct DiaOracleRpc<C, P> {
 	client: Arc<C>,
+	executor: Arc<dyn SpawnNamed>,
+	finalized: bool,
 	_marker: std::marker::PhantomData<P>,
 }
 
 impl<C, P> DiaOracleRpc<C, P> {
-	/// Create new `TransactionPayment` with the given reference to the client.
-	pub fn new(client: Arc<C>) -> Self {
-		Self { client, _marker: Default::default() }
+	/// Create new `DiaOracleRpc` with the given reference to the client and the executor running
+	/// subscriptions.
+	pub fn new(client: Arc<C>, executor: Arc<dyn SpawnNamed>) -> Self {
+		Self { client, executor, finalized: false, _marker: Default::default() }
+	}
+
+	/// Read from the latest finalized block instead of the best block when a call does not name a
+	/// block, so that unfinalized prices are never returned.
+	pub fn finalized(mut self, finalized: bool) -> Self {
+		self.finalized = finalized;
+		self
+	}
+}
+
+impl<C, Block> DiaOracleRpc<C, Block>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block>,
+{
+	/// Hash of the block a call reads from: `at` if given, the best or finalized block otherwise
+	fn block_hash(&self, at: Option<Block::Hash>) -> Block::Hash {
+		at.unwrap_or_else(|| {
+			let info = self.client.info();
+			if self.finalized {
+				info.finalized_hash
+			} else {
+				info.best_hash
+			}
+		})
 	}
 }
 
@@ -51,6 +480,22 @@ pub enum Error {
 	DecodeError,
 	/// The call to runtime failed.
 	RuntimeError,
+	/// The requested block range is empty or too large.
+	InvalidRange,
+	/// The amount is not a decimal number.
+	InvalidAmount,
+	/// The oracle has no price for the asset.
+	UnknownAsset,
+	/// The price of the asset is too old to convert with.
+	StalePrice,
+	/// The price of the asset is zero.
+	ZeroPrice,
+	/// The result does not fit into the range of the runtime.
+	Overflow,
+	/// The runtime returned any other error.
+	DispatchError,
+	/// The runtime does not implement the version of the runtime api the call needs.
+	UnsupportedRuntime,
 }
 
 impl From<Error> for i32 {
@@ -58,29 +503,57 @@ impl From<Error> for i32 {
 		match e {
 			Error::RuntimeError => 1,
 			Error::DecodeError => 2,
+			Error::InvalidRange => 3,
+			Error::InvalidAmount => 4,
+			Error::UnknownAsset => 5,
+			Error::StalePrice => 6,
+			Error::ZeroPrice => 7,
+			Error::Overflow => 8,
+			Error::DispatchError => 9,
+			Error::UnsupportedRuntime => 10,
 		}
 	}
 }
 
-impl<C, Block> DiaOracleApiServer<<Block as BlockT>::Hash> for DiaOracleRpc<C, Block>
+/// Maps an error returned by the runtime API to its own error code. The runtime API only returns
+/// errors of the oracle pallet, so its module errors are told apart by their index alone.
+fn dispatch_error(method: &str, e: DispatchError) -> CallError {
+	let (code, message) = match e {
+		DispatchError::Module(ModuleError { error: [index, ..], .. }) => match index {
+			error_index::NO_COIN_INFO_AVAILABLE => (Error::UnknownAsset, "No price for the asset."),
+			error_index::STALE_PRICE => (Error::StalePrice, "The price of the asset is stale."),
+			error_index::ZERO_PRICE => (Error::ZeroPrice, "The price of the asset is zero."),
+			_ => (Error::DispatchError, "Unable to query the oracle."),
+		},
+		DispatchError::Arithmetic(_) => (Error::Overflow, "The result is out of range."),
+		_ => (Error::DispatchError, "Unable to query the oracle."),
+	};
+	CallError::Custom(ErrorObject::owned(
+		code.into(),
+		message,
+		Some(format!("{}: {:?}", method, e)),
+	))
+}
+
+impl<C, Block, AccountId> DiaOracleApiServer<<Block as BlockT>::Hash, AccountId>
+	for DiaOracleRpc<C, Block>
 where
 	Block: BlockT,
-	C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-	C::Api: DiaOracleRuntimeApi<Block>,
+	AccountId: Codec + Serialize + Send + Sync + 'static,
+	C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: Send + Sync,
+	C::Api: DiaOracleRuntimeApi<Block, AccountId>,
 {
 	fn get_coin_info(
 		&self,
-		blockchain: Bytes,
-		symbol: Bytes,
+		blockchain: AssetName,
+		symbol: AssetName,
 		at: Option<<Block as BlockT>::Hash>,
-	) -> RpcResult<CoinInfo> {
+	) -> RpcResult<CoinInfoResponse> {
 		let api = self.client.runtime_api();
-		let at = BlockId::hash(at.unwrap_or_else(||
-			// If the block hash is not supplied assume the best block.
-			self.client.info().best_hash));
+		let at = BlockId::hash(self.block_hash(at));
 
-		let r = api
-			.get_coin_info(&at, blockchain.to_vec(), symbol.to_vec())
+		let r = coin_info_at::<_, Block, AccountId>(&*api, &at, &blockchain, &symbol)
 			.map_err(|e| {
 				CallError::Custom(ErrorObject::owned(
 					Error::RuntimeError.into(),
@@ -88,27 +561,19 @@ where
 					Some(format!("{:?}", e)),
 				))
 			})?
-			.map_err(|e| {
-				CallError::Custom(ErrorObject::owned(
-					Error::RuntimeError.into(),
-					"Unable to query get_coin_info.",
-					Some(format!("{:?}", e)),
-				))
-			})?;
+			.map_err(|e| dispatch_error("get_coin_info", e))?;
 
-		Ok(r)
+		Ok(r.into())
 	}
 
 	fn get_value(
 		&self,
-		blockchain: Bytes,
-		symbol: Bytes,
+		blockchain: AssetName,
+		symbol: AssetName,
 		at: Option<<Block as BlockT>::Hash>,
 	) -> RpcResult<PriceInfo> {
 		let api = self.client.runtime_api();
-		let at = BlockId::hash(at.unwrap_or_else(||
-			// If the block hash is not supplied assume the best block.
-			self.client.info().best_hash));
+		let at = BlockId::hash(self.block_hash(at));
 
 		let r = api
 			.get_value(&at, blockchain.to_vec(), symbol.to_vec())
@@ -119,13 +584,424 @@ where
 					Some(format!("{:?}", e)),
 				))
 			})?
+			.map_err(|e| dispatch_error("get_value", e))?;
+		Ok(r)
+	}
+
+	fn get_supply(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<String> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(self.block_hash(at));
+		ensure_api_v2::<_, Block, AccountId>(&*api, &at, "get_supply")?;
+
+		let supply = api
+			.get_supply(&at, blockchain.to_vec(), symbol.to_vec())
 			.map_err(|e| {
 				CallError::Custom(ErrorObject::owned(
 					Error::RuntimeError.into(),
-					"Unable to query get_value.",
+					"Unable to query get_supply.",
+					Some(format!("{:?}", e)),
+				))
+			})?
+			.map_err(|e| dispatch_error("get_supply", e))?;
+		Ok(supply.to_string())
+	}
+
+	fn get_asset_overview(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<AssetOverviewResponse> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(self.block_hash(at));
+		ensure_api_v2::<_, Block, AccountId>(&*api, &at, "get_asset_overview")?;
+
+		let overview =
+			api.get_asset_overview(&at, blockchain.to_vec(), symbol.to_vec()).map_err(|e| {
+				CallError::Custom(ErrorObject::owned(
+					Error::RuntimeError.into(),
+					"Unable to query get_asset_overview.",
 					Some(format!("{:?}", e)),
 				))
 			})?;
-		Ok(r)
+
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+		Ok(AssetOverviewResponse::new(&blockchain, &symbol, overview, now))
+	}
+
+	fn get_coin_infos(
+		&self,
+		assets: Vec<(AssetName, AssetName)>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<Option<CoinInfoResponse>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(self.block_hash(at));
+		ensure_api_v2::<_, Block, AccountId>(&*api, &at, "get_coin_infos")?;
+
+		let assets = assets
+			.into_iter()
+			.map(|(blockchain, symbol)| (blockchain.to_vec(), symbol.to_vec()))
+			.collect();
+		let coin_infos = api.get_coin_infos(&at, assets).map_err(|e| {
+			CallError::Custom(ErrorObject::owned(
+				Error::RuntimeError.into(),
+				"Unable to query get_coin_infos.",
+				Some(format!("{:?}", e)),
+			))
+		})?;
+
+		Ok(coin_infos.into_iter().map(|c| c.ok().map(Into::into)).collect())
+	}
+
+	fn get_coins_by_blockchain(
+		&self,
+		blockchain: AssetName,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<CoinInfoResponse>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(self.block_hash(at));
+		ensure_api_v2::<_, Block, AccountId>(&*api, &at, "get_coins_by_blockchain")?;
+
+		let coin_infos = api.get_coins_by_blockchain(&at, blockchain.to_vec()).map_err(|e| {
+			CallError::Custom(ErrorObject::owned(
+				Error::RuntimeError.into(),
+				"Unable to query get_coins_by_blockchain.",
+				Some(format!("{:?}", e)),
+			))
+		})?;
+
+		Ok(coin_infos.into_iter().map(Into::into).collect())
+	}
+
+	fn list_supported_currencies(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<(String, String)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(self.block_hash(at));
+		ensure_api_v2::<_, Block, AccountId>(&*api, &at, "list_supported_currencies")?;
+
+		let currencies = api.list_supported_currencies(&at).map_err(|e| {
+			CallError::Custom(ErrorObject::owned(
+				Error::RuntimeError.into(),
+				"Unable to query list_supported_currencies.",
+				Some(format!("{:?}", e)),
+			))
+		})?;
+
+		Ok(currencies
+			.into_iter()
+			.map(|(blockchain, symbol)| (to_string(&blockchain), to_string(&symbol)))
+			.collect())
+	}
+
+	fn get_authorized_accounts(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(self.block_hash(at));
+		ensure_api_v2::<_, Block, AccountId>(&*api, &at, "get_authorized_accounts")?;
+
+		let accounts = api.list_authorized_accounts(&at).map_err(|e| {
+			CallError::Custom(ErrorObject::owned(
+				Error::RuntimeError.into(),
+				"Unable to query list_authorized_accounts.",
+				Some(format!("{:?}", e)),
+			))
+		})?;
+
+		Ok(accounts)
+	}
+
+	fn get_last_updated(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<u64>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(self.block_hash(at));
+		ensure_api_v2::<_, Block, AccountId>(&*api, &at, "get_last_updated")?;
+
+		let updated =
+			api.get_last_updated(&at, blockchain.to_vec(), symbol.to_vec()).map_err(|e| {
+				CallError::Custom(ErrorObject::owned(
+					Error::RuntimeError.into(),
+					"Unable to query get_last_updated.",
+					Some(format!("{:?}", e)),
+				))
+			})?;
+
+		Ok(updated)
+	}
+
+	fn is_stale(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<bool> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(self.block_hash(at));
+		ensure_api_v2::<_, Block, AccountId>(&*api, &at, "is_stale")?;
+
+		let stale = api.is_stale(&at, blockchain.to_vec(), symbol.to_vec()).map_err(|e| {
+			CallError::Custom(ErrorObject::owned(
+				Error::RuntimeError.into(),
+				"Unable to query is_stale.",
+				Some(format!("{:?}", e)),
+			))
+		})?;
+
+		Ok(stale)
+	}
+
+	fn get_value_history(
+		&self,
+		blockchain: AssetName,
+		symbol: AssetName,
+		from_block: u64,
+		to_block: u64,
+		step: u64,
+	) -> RpcResult<Vec<HistoryPoint>> {
+		let blocks = history_blocks(from_block, to_block, step).ok_or_else(|| {
+			CallError::Custom(ErrorObject::owned(
+				Error::InvalidRange.into(),
+				"Invalid block range.",
+				Some(format!("at most {} blocks with a step above zero", MAX_HISTORY_POINTS)),
+			))
+		})?;
+
+		let api = self.client.runtime_api();
+		let mut history = Vec::new();
+		for block_number in blocks {
+			let number: NumberFor<Block> = block_number.saturated_into();
+			let hash = match self.client.hash(number) {
+				Ok(Some(hash)) => hash,
+				_ => continue,
+			};
+			let at = BlockId::hash(hash);
+			let coin_info = coin_info_at::<_, Block, AccountId>(&*api, &at, &blockchain, &symbol);
+			let coin_info = match coin_info {
+				Ok(coin_info) => coin_info.ok(),
+				Err(_) => continue,
+			};
+			history.push(HistoryPoint {
+				block_number,
+				value: coin_info.as_ref().map(|c| PriceInfo { value: c.price }),
+				last_update_timestamp: coin_info.map(|c| c.last_update_timestamp),
+			});
+		}
+
+		Ok(history)
+	}
+
+	fn convert(
+		&self,
+		from: (AssetName, AssetName),
+		to: (AssetName, AssetName),
+		amount: String,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<String> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(self.block_hash(at));
+		ensure_api_v2::<_, Block, AccountId>(&*api, &at, "convert")?;
+		let runtime_error = |e: String| {
+			CallError::Custom(ErrorObject::owned(
+				Error::RuntimeError.into(),
+				"Unable to query convert.",
+				Some(e),
+			))
+		};
+
+		let decimals = api.price_decimals(&at).map_err(|e| runtime_error(format!("{:?}", e)))?;
+		let amount = parse_decimal(&amount, decimals).ok_or_else(|| {
+			CallError::Custom(ErrorObject::owned(
+				Error::InvalidAmount.into(),
+				"Invalid amount.",
+				Some(amount.clone()),
+			))
+		})?;
+		let from = (from.0.to_vec(), from.1.to_vec());
+		let to = (to.0.to_vec(), to.1.to_vec());
+		let converted = api
+			.convert(&at, from, to, amount)
+			.map_err(|e| runtime_error(format!("{:?}", e)))?
+			.map_err(|e| dispatch_error("convert", e))?;
+
+		Ok(format_decimal(converted, decimals))
+	}
+
+	fn subscribe_prices(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
+		let client = self.client.clone();
+		let mut last_seen: BTreeMap<Asset, CoinInfo> =
+			prices_at::<_, Block, AccountId>(&*client, self.block_hash(None))
+				.into_iter()
+				.collect();
+
+		let blocks = if self.finalized {
+			self.client.finality_notification_stream().map(|notification| notification.hash).boxed()
+		} else {
+			self.client
+				.import_notification_stream()
+				.filter(|notification| future::ready(notification.is_new_best))
+				.map(|notification| notification.hash)
+				.boxed()
+		};
+		let stream = blocks.flat_map(move |hash| {
+			let prices = prices_at::<_, Block, AccountId>(&*client, hash);
+			stream::iter(price_updates(prices, &mut last_seen))
+		});
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+		self.executor.spawn("dia-oracle-price-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn asset_overview_response_should_be_human_readable() {
+		let overview = AssetOverview {
+			supported: true,
+			coin_info: Some(CoinInfo {
+				symbol: b"BTC".to_vec(),
+				name: b"Bitcoin".to_vec(),
+				blockchain: b"Bitcoin".to_vec(),
+				supply: 21_000_000,
+				last_update_timestamp: 100,
+				price: 16_826_489_316_709_616_000,
+				price_yesterday: 0,
+				volume_yesterday_usd: 0,
+				market_cap: None,
+				high_24h: None,
+				low_24h: None,
+			}),
+			last_batch_block: Some(7),
+		};
+
+		let response = AssetOverviewResponse::new(b"Bitcoin", b"BTC", overview, 160);
+
+		assert_eq!(response.name.as_deref(), Some("Bitcoin"));
+		assert_eq!(response.price.as_deref(), Some("16826489316709616000"));
+		assert_eq!(response.seconds_since_update, Some(60));
+		assert_eq!(response.last_batch_block, Some(7));
+	}
+
+	#[test]
+	fn price_updates_should_only_contain_changed_prices() {
+		let btc = (b"Bitcoin".to_vec(), b"BTC".to_vec());
+		let dot = (b"Polkadot".to_vec(), b"DOT".to_vec());
+		let priced = |price| CoinInfo { price, ..Default::default() };
+		let mut last_seen = BTreeMap::new();
+		last_seen.insert(btc.clone(), priced(1));
+
+		let updates = price_updates(
+			vec![(btc.clone(), priced(1)), (dot.clone(), priced(2))],
+			&mut last_seen,
+		);
+		assert_eq!(updates.len(), 1);
+		assert_eq!(updates[0].symbol, "DOT");
+		assert_eq!(updates[0].coin_info, priced(2).into());
+
+		let updates =
+			price_updates(vec![(btc.clone(), priced(3)), (dot, priced(2))], &mut last_seen);
+		assert_eq!(updates.len(), 1);
+		assert_eq!(updates[0].coin_info, priced(3).into());
+		assert_eq!(last_seen.get(&btc), Some(&priced(3)));
+	}
+
+	#[test]
+	fn history_blocks_should_be_bounded() {
+		assert_eq!(history_blocks(10, 20, 5), Some(vec![10, 15, 20]));
+		assert_eq!(history_blocks(10, 22, 5), Some(vec![10, 15, 20]));
+		assert_eq!(history_blocks(10, 10, 1), Some(vec![10]));
+		assert_eq!(history_blocks(10, 9, 1), None);
+		assert_eq!(history_blocks(10, 20, 0), None);
+		assert_eq!(history_blocks(0, MAX_HISTORY_POINTS - 1, 1).map(|b| b.len()), Some(1_000));
+		assert_eq!(history_blocks(0, MAX_HISTORY_POINTS, 1), None);
+	}
+
+	#[test]
+	fn dispatch_errors_should_have_distinct_codes() {
+		let code = |e| match dispatch_error("get_value", e) {
+			CallError::Custom(error) => error.code(),
+			_ => unreachable!(),
+		};
+		let module_error = |index| {
+			DispatchError::Module(ModuleError { index: 7, error: [index, 0, 0, 0], message: None })
+		};
+
+		assert_eq!(code(module_error(error_index::NO_COIN_INFO_AVAILABLE)), 5);
+		assert_eq!(code(module_error(error_index::STALE_PRICE)), 6);
+		assert_eq!(code(module_error(error_index::ZERO_PRICE)), 7);
+		assert_eq!(code(module_error(1)), 9);
+		assert_eq!(code(DispatchError::Arithmetic(sp_runtime::ArithmeticError::Overflow)), 8);
+		assert_eq!(code(DispatchError::BadOrigin), 9);
+	}
+
+	#[test]
+	fn coin_infos_of_api_version_1_should_convert() {
+		let coin_info = from_v1(CoinInfoV1 {
+			symbol: b"BTC".to_vec(),
+			name: b"Bitcoin".to_vec(),
+			blockchain: b"Bitcoin".to_vec(),
+			supply: 21_000_000,
+			last_update_timestamp: 100,
+			price: 5,
+		});
+
+		assert_eq!((coin_info.symbol.as_slice(), coin_info.price), (&b"BTC"[..], 5));
+		assert_eq!(coin_info.last_update_timestamp, 100);
+		assert_eq!((coin_info.price_yesterday, coin_info.market_cap), (0, None));
+	}
+
+	#[test]
+	fn decimals_should_round_trip() {
+		assert_eq!(parse_decimal("1.5", 3), Some(1_500));
+		assert_eq!(parse_decimal("12", 3), Some(12_000));
+		assert_eq!(parse_decimal("0.12345", 3), Some(123));
+		assert_eq!(parse_decimal("7.", 0), Some(7));
+		assert_eq!(parse_decimal(".5", 3), None);
+		assert_eq!(parse_decimal("-1", 3), None);
+		assert_eq!(parse_decimal("1e3", 3), None);
+
+		assert_eq!(format_decimal(1_500, 3), "1.5");
+		assert_eq!(format_decimal(12_000, 3), "12");
+		assert_eq!(format_decimal(5, 3), "0.005");
+		assert_eq!(format_decimal(5, 0), "5");
+	}
+
+	#[test]
+	fn asset_name_should_accept_strings_and_hex() {
+		let parse = |json| serde_json::from_str::<AssetName>(json).unwrap().0;
+
+		assert_eq!(parse(r#""BTC""#), b"BTC".to_vec());
+		assert_eq!(parse(r#""0x425443""#), b"BTC".to_vec());
+		assert_eq!(parse(r#""0xBTC""#), b"0xBTC".to_vec());
+		assert_eq!(serde_json::to_string(&AssetName(b"BTC".to_vec())).unwrap(), r#""BTC""#);
+		assert_eq!(serde_json::to_string(&AssetName(vec![0xff])).unwrap(), r#""0xff""#);
+	}
+
+	#[test]
+	fn asset_overview_response_without_coin_info() {
+		let response =
+			AssetOverviewResponse::new(b"Bitcoin", b"BTC", AssetOverview::default(), 160);
+
+		assert_eq!(response.symbol, "BTC");
+		assert!(!response.supported);
+		assert_eq!(response.price, None);
+		assert_eq!(response.seconds_since_update, None);
 	}
 }
@@ -0,0 +1,49 @@
+use crate::{CoinInfo, DiaOracle};
+use frame_support::{
+	sp_runtime::traits::Convert,
+	sp_std::{marker::PhantomData, vec::Vec},
+};
+use orml_traits::{DataProvider, DataProviderExtended};
+
+/// Serves the oracle's prices as an orml `DataProvider`, keyed by the oracle keys of another
+/// pallet such as Spacewalk's `OracleKey::ExchangeRate(CurrencyId)`.
+///
+/// `KeyConvert` maps a key to the `(blockchain, symbol)` it is quoted under, or `None` for keys
+/// this oracle does not serve. `ValueConvert` turns the coin info into the value the consumer
+/// expects, e.g. a timestamped fixed point price, and may reject it with `None`.
+pub struct OracleKeyAdapter<Oracle, Key, Value, KeyConvert, ValueConvert>(
+	PhantomData<(Oracle, Key, Value, KeyConvert, ValueConvert)>,
+);
+
+impl<Oracle, Key, Value, KeyConvert, ValueConvert> DataProvider<Key, Value>
+	for OracleKeyAdapter<Oracle, Key, Value, KeyConvert, ValueConvert>
+where
+	Oracle: DiaOracle,
+	Key: Clone,
+	KeyConvert: Convert<Key, Option<(Vec<u8>, Vec<u8>)>>,
+	ValueConvert: Convert<CoinInfo, Option<Value>>,
+{
+	fn get(key: &Key) -> Option<Value> {
+		let (blockchain, symbol) = KeyConvert::convert(key.clone())?;
+		let coin_info = Oracle::get_coin_info(blockchain, symbol).ok()?;
+		ValueConvert::convert(coin_info)
+	}
+}
+
+impl<Oracle, Key, Value, KeyConvert, ValueConvert> DataProviderExtended<Key, Value>
+	for OracleKeyAdapter<Oracle, Key, Value, KeyConvert, ValueConvert>
+where
+	Oracle: DiaOracle,
+	Key: Clone,
+	KeyConvert: Convert<Key, Option<(Vec<u8>, Vec<u8>)>>,
+	ValueConvert: Convert<CoinInfo, Option<Value>>,
+{
+	fn get_no_op(key: &Key) -> Option<Value> {
+		<Self as DataProvider<Key, Value>>::get(key)
+	}
+
+	/// Always empty, keys can only be mapped to assets and not the other way round
+	fn get_all_values() -> Vec<(Key, Option<Value>)> {
+		Vec::new()
+	}
+}
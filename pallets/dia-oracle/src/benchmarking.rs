@@ -5,30 +5,51 @@ use super::*;
 #[allow(unused)]
 use crate::Pallet as DiaOracle;
 use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
-use frame_support::sp_std::{vec, vec::Vec};
+use frame_support::{
+	sp_std::{vec, vec::Vec},
+	traits::{Currency, UnixTime},
+};
 use frame_system::RawOrigin;
+use sp_runtime::{traits::Bounded, Permill};
+
+fn authorized_caller<T: Config>() -> Result<T::AccountId, &'static str> {
+	let caller: T::AccountId = whitelisted_caller();
+	DiaOracle::<T>::authorize_account(RawOrigin::Root.into(), caller.clone())?;
+	Ok(caller)
+}
 
 benchmarks! {
 	add_currency {
-		let caller: T::AccountId = whitelisted_caller();
-		let caller_origin = <T as frame_system::Config>::Origin::from(RawOrigin::Signed(caller.clone()));
-		DiaOracle::<T>::authorize_account(<T as frame_system::Config>::Origin::from(RawOrigin::Root), caller.clone())?;
-	}: _(RawOrigin::Signed(caller), vec![1,2,3])
+		let caller = authorized_caller::<T>()?;
+		T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 2u32.into());
+	}: _(RawOrigin::Signed(caller), vec![1,2,3], vec![1,2,3])
 
 	remove_currency {
-		let caller: T::AccountId = whitelisted_caller();
-		let caller_origin = <T as frame_system::Config>::Origin::from(RawOrigin::Signed(caller.clone()));
-		DiaOracle::<T>::authorize_account(<T as frame_system::Config>::Origin::from(RawOrigin::Root), caller.clone())?;
-	} : _(RawOrigin::Signed(caller), vec![1,2,3])
+		let caller = authorized_caller::<T>()?;
+		DiaOracle::<T>::add_currency(RawOrigin::Root.into(), vec![1,2,3], vec![1,2,3])?;
+	} : _(RawOrigin::Signed(caller), vec![1,2,3], vec![1,2,3])
+
+	cancel_currency_addition {
+		let asset_id = AssetId::new(vec![1,2,3], vec![1,2,3]);
+		<PendingActivations<T>>::insert(T::BlockNumber::from(10u32), &asset_id, ());
+		<PendingCurrencies<T>>::insert(asset_id, T::BlockNumber::from(10u32));
+	} : _(RawOrigin::Root, vec![1,2,3], vec![1,2,3])
+
+	block_currency {
+		DiaOracle::<T>::add_currency(RawOrigin::Root.into(), vec![1,2,3], vec![1,2,3])?;
+	} : _(RawOrigin::Root, vec![1,2,3], vec![1,2,3])
+
+	unblock_currency {
+		<BlockedCurrencies<T>>::insert(AssetId::new(vec![1,2,3], vec![1,2,3]), ());
+	} : _(RawOrigin::Root, vec![1,2,3], vec![1,2,3])
 
 	authorize_account {
 		let account: T::AccountId = whitelisted_caller();
 	} : _(RawOrigin::Root, account)
 
 	authorize_account_signed {
-		let caller: T::AccountId = whitelisted_caller();
+		let caller = authorized_caller::<T>()?;
 		let account: T::AccountId = account("test",2,2);
-		DiaOracle::<T>::authorize_account(<T as frame_system::Config>::Origin::from(RawOrigin::Root), caller.clone())?;
 	} : authorize_account(RawOrigin::Signed(caller), account)
 
 	deauthorize_account {
@@ -36,28 +57,117 @@ benchmarks! {
 	} : _(RawOrigin::Root, account)
 
 	deauthorize_account_signed {
-		let caller: T::AccountId = whitelisted_caller();
+		let caller = authorized_caller::<T>()?;
 		let account: T::AccountId = account("test",2,2);
-		DiaOracle::<T>::authorize_account(<T as frame_system::Config>::Origin::from(RawOrigin::Root), caller.clone())?;
-	} : authorize_account(RawOrigin::Signed(caller), account)
+		DiaOracle::<T>::authorize_account(RawOrigin::Root.into(), account.clone())?;
+	} : deauthorize_account(RawOrigin::Signed(caller), account)
 
 	set_updated_coin_infos {
-		let example_info: CoinInfo = CoinInfo {
-			symbol: vec![2, 2, 2],
-			name: vec![2, 2, 2],
-			supply: 9,
-			last_update_timestamp: 9,
-			price: 9,
+		let a in 1 .. 1_000;
+		let d in 0 .. T::MaxDerivedFeeds::get();
+		let now = T::UnixTime::now().as_secs();
+		let coin_infos = (0..a).map(|i| {
+			let symbol = i.to_be_bytes().to_vec();
+			<SupportedCurrencies<T>>::insert(AssetId::new(b"Chain".to_vec(), symbol.clone()), ());
+			let coin_info = CoinInfo {
+				symbol: symbol.clone(),
+				blockchain: b"Chain".to_vec(),
+				last_update_timestamp: now,
+				price: 9,
+				..Default::default()
+			};
+			((b"Chain".to_vec(), symbol), coin_info)
+		}).collect::<Vec<_>>();
+		// Every derived feed is a cross rate of two submitted assets, so all are recomputed
+		let feeds = (0..d).map(|i| {
+			let base = coin_infos[(i % a) as usize].0.clone();
+			let quote = coin_infos[((i + 1) % a) as usize].0.clone();
+			((b"Derived".to_vec(), i.to_be_bytes().to_vec()), DerivedFeed::Cross { base, quote })
+		}).collect::<Vec<_>>();
+		<DerivedFeeds<T>>::put(feeds);
+		let caller = authorized_caller::<T>()?;
+	}: _(RawOrigin::Signed(caller), coin_infos)
+
+	set_batching_api {
+		let caller = authorized_caller::<T>()?;
+	} : _(RawOrigin::Signed(caller), b"http://localhost:8070/currencies".to_vec())
+
+	set_submitter_selection {
+		let account: T::AccountId = whitelisted_caller();
+	} : _(RawOrigin::Root, SubmitterSelection::Pinned(account))
+
+	rotate_feeder_key {
+		let caller = authorized_caller::<T>()?;
+		let account: T::AccountId = account("test",2,2);
+	} : _(RawOrigin::Signed(caller), account)
+
+	reset_authorized_accounts {
+		let a in 1 .. 100;
+		let r in 0 .. 100;
+		for i in 0..r {
+			<AuthorizedAccounts<T>>::insert(account::<T::AccountId>("old", i, 2), ());
+		}
+		let accounts = (0..a).map(|i| account("test", i, 2)).collect::<Vec<T::AccountId>>();
+	} : _(RawOrigin::Root, accounts, r)
+
+	set_update_interval {
+	} : _(RawOrigin::Root, 5u32.into())
+
+	set_batching_server_key {
+	} : _(RawOrigin::Root, Some(sp_core::sr25519::Public::from_raw([1u8; 32])))
+
+	feeder_heartbeat {
+		let caller = authorized_caller::<T>()?;
+	} : _(RawOrigin::Signed(caller))
+
+	set_extra_batching_apis {
+		let a in 0 .. T::MaxExtraBatchingApis::get();
+		let caller = authorized_caller::<T>()?;
+		let apis = (0..a).map(|_| b"http://localhost:8070/currencies".to_vec()).collect::<Vec<_>>();
+	} : _(RawOrigin::Signed(caller), apis)
+
+	set_batching_api_encoding {
+	} : _(RawOrigin::Root, BatchingApiEncoding::Scale)
+
+	set_ocw_config {
+		let config = OcwConfig {
+			max_http_retries: 3,
+			http_request_timeout: 5_000,
+			max_currencies_per_request: 50,
+			min_change_threshold: Permill::from_percent(1),
 		};
-		let coin_infos = (0..=5000).map(|_|{
-			(vec![2, 2, 2], example_info.clone())
+	} : _(RawOrigin::Root, Some(config))
+
+	set_min_change_threshold {
+	} : _(RawOrigin::Root, vec![1,2,3], vec![1,2,3], Some(Permill::from_parts(100)))
+
+	set_derived_feeds {
+		let a in 0 .. T::MaxDerivedFeeds::get();
+		let feeds = (0..a).map(|i| {
+			let asset = (b"Fiat".to_vec(), i.to_be_bytes().to_vec());
+			(asset, DerivedFeed::Inverse((b"Fiat".to_vec(), b"MXN".to_vec())))
 		}).collect::<Vec<_>>();
+	} : _(RawOrigin::Root, feeds)
 
-		let caller: T::AccountId = whitelisted_caller();
-		let caller_origin = <T as frame_system::Config>::Origin::from(RawOrigin::Signed(caller.clone()));
-		DiaOracle::<T>::authorize_account(<T as frame_system::Config>::Origin::from(RawOrigin::Root), caller.clone())?;
+	clear_all_prices {
+		let a in 1 .. 100;
+		for i in 0..a {
+			let symbol = i.to_be_bytes().to_vec();
+			let submitter = (whitelisted_caller::<T::AccountId>(), T::BlockNumber::from(1u32));
+			<PriceSubmitters<T>>::insert(b"Stellar".to_vec(), symbol.clone(), submitter);
+			<CoinInfosMap<T>>::insert(b"Stellar".to_vec(), symbol, CoinInfo::default());
+		}
+	} : _(RawOrigin::Root, a)
 
-	}: _(RawOrigin::Signed(caller), coin_infos)
+	purge_blockchain {
+		let a in 1 .. 100;
+		for i in 0..a {
+			let symbol = i.to_be_bytes().to_vec();
+			let submitter = (whitelisted_caller::<T::AccountId>(), T::BlockNumber::from(1u32));
+			<PriceSubmitters<T>>::insert(b"Stellar".to_vec(), symbol.clone(), submitter);
+			<CoinInfosMap<T>>::insert(b"Stellar".to_vec(), symbol, CoinInfo::default());
+		}
+	} : _(RawOrigin::Root, b"Stellar".to_vec(), a)
 
 }
 
@@ -0,0 +1,47 @@
+use crate::{Config, Pallet};
+use frame_support::{
+	sp_runtime::{traits::Convert, ArithmeticError, DispatchError},
+	sp_std::{convert::TryFrom, marker::PhantomData, vec::Vec},
+	traits::Get,
+};
+
+/// Conversion of an asset balance into another balance, with the signature of
+/// `frame_support::traits::tokens::ConversionFromAssetBalance` of later Substrate releases.
+/// Runtimes on those releases forward their implementation of it to this one.
+pub trait ConversionFromAssetBalance<AssetBalance, AssetId, OutBalance> {
+	type Error;
+
+	fn from_asset_balance(
+		balance: AssetBalance,
+		asset_id: AssetId,
+	) -> Result<OutBalance, Self::Error>;
+}
+
+/// Converts asset balances into the native balance at the current oracle prices, rounding down.
+/// Conversions at a price older than `MaxPriceAge` fail with `StalePrice`.
+///
+/// `AssetConvert` maps an asset id to the `(blockchain, symbol)` it is quoted under and `Native`
+/// names the native token. Both balances have to use the same number of decimals.
+pub struct OracleConversionRate<T, AssetConvert, Native>(PhantomData<(T, AssetConvert, Native)>);
+
+impl<T, AssetId, AssetBalance, OutBalance, AssetConvert, Native>
+	ConversionFromAssetBalance<AssetBalance, AssetId, OutBalance>
+	for OracleConversionRate<T, AssetConvert, Native>
+where
+	T: Config,
+	AssetBalance: Into<u128>,
+	OutBalance: TryFrom<u128>,
+	AssetConvert: Convert<AssetId, Option<(Vec<u8>, Vec<u8>)>>,
+	Native: Get<(Vec<u8>, Vec<u8>)>,
+{
+	type Error = DispatchError;
+
+	fn from_asset_balance(
+		balance: AssetBalance,
+		asset_id: AssetId,
+	) -> Result<OutBalance, DispatchError> {
+		let asset = AssetConvert::convert(asset_id).ok_or(DispatchError::CannotLookup)?;
+		let native = Pallet::<T>::convert(asset, Native::get(), balance.into())?;
+		OutBalance::try_from(native).map_err(|_| ArithmeticError::Overflow.into())
+	}
+}
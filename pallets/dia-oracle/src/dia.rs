@@ -1,9 +1,12 @@
 use codec::{Decode, Encode};
-use frame_support::{sp_runtime::DispatchError, sp_std::vec::Vec};
-use serde::{Deserialize, Deserializer, Serialize};
-
+use frame_support::{
+	sp_runtime::{DispatchError, Permill},
+	sp_std::{marker::PhantomData, vec, vec::Vec},
+	traits::Currency,
+	weights::Weight,
+};
 #[cfg(feature = "std")]
-use serde::Serializer;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 // TODO: Maybe it should be moved to it's own crate
 pub trait DiaOracle {
@@ -12,6 +15,36 @@ pub trait DiaOracle {
 
 	/// Returns the price by given name
 	fn get_value(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<PriceInfo, DispatchError>;
+
+	/// Returns the price by given name with its timestamp, the supply and whether it is stale.
+	/// The default knows no maximum price age and reports every price as fresh, oracles that
+	/// have one override it.
+	fn get_value_with_metadata(
+		blockchain: Vec<u8>,
+		symbol: Vec<u8>,
+	) -> Result<PriceWithMetadata, DispatchError> {
+		Self::get_coin_info(blockchain, symbol).map(|info| PriceWithMetadata {
+			value: info.price,
+			last_update_timestamp: info.last_update_timestamp,
+			supply: info.supply,
+			is_stale: false,
+		})
+	}
+}
+
+/// Notified of every batch of coin infos stored by `set_updated_coin_infos`, e.g. to forward the
+/// prices to other chains
+pub trait OnPriceUpdate {
+	fn on_price_update(coin_infos: &[((Vec<u8>, Vec<u8>), CoinInfo)]);
+
+	/// Weight of `on_price_update` for the batch, charged on top of `set_updated_coin_infos`
+	fn on_price_update_weight(_coin_infos: &[((Vec<u8>, Vec<u8>), CoinInfo)]) -> Weight {
+		Weight::zero()
+	}
+}
+
+impl OnPriceUpdate for () {
+	fn on_price_update(_: &[((Vec<u8>, Vec<u8>), CoinInfo)]) {}
 }
 
 #[derive(
@@ -23,22 +56,36 @@ pub trait DiaOracle {
 	PartialEq,
 	Eq,
 	Default,
-	Deserialize,
-	Serialize,
 )]
-#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "std", derive(Deserialize, Serialize), serde(rename_all = "camelCase"))]
 pub struct CoinInfo {
-	#[serde(deserialize_with = "de_string_to_bytes")]
+	#[cfg_attr(feature = "std", serde(deserialize_with = "de_string_to_bytes"))]
 	pub symbol: Vec<u8>,
-	#[serde(deserialize_with = "de_string_to_bytes")]
+	#[cfg_attr(feature = "std", serde(deserialize_with = "de_string_to_bytes"))]
 	pub name: Vec<u8>,
-	#[serde(deserialize_with = "de_string_to_bytes")]
+	#[cfg_attr(feature = "std", serde(deserialize_with = "de_string_to_bytes"))]
 	pub blockchain: Vec<u8>,
 	pub supply: u128,
 	pub last_update_timestamp: u64,
 	pub price: u128,
+	/// Price 24 hours before `last_update_timestamp`, zero if the source did not send it
+	#[cfg_attr(feature = "std", serde(default))]
+	pub price_yesterday: u128,
+	/// Trading volume in USD of the last 24 hours, zero if the source did not send it
+	#[cfg_attr(feature = "std", serde(default))]
+	pub volume_yesterday_usd: u128,
+	/// Market capitalization in USD, if the source knows it
+	#[cfg_attr(feature = "std", serde(default))]
+	pub market_cap: Option<u128>,
+	/// Highest price of the last 24 hours, if the source knows it
+	#[cfg_attr(feature = "std", serde(default))]
+	pub high_24h: Option<u128>,
+	/// Lowest price of the last 24 hours, if the source knows it
+	#[cfg_attr(feature = "std", serde(default))]
+	pub low_24h: Option<u128>,
 }
 
+#[cfg(feature = "std")]
 pub fn de_string_to_bytes<'de, D>(de: D) -> Result<Vec<u8>, D::Error>
 where
 	D: Deserializer<'de>,
@@ -47,7 +94,8 @@ where
 	Ok(s.as_bytes().to_vec())
 }
 
-#[derive(Encode, Decode, scale_info::TypeInfo, Debug, Deserialize, Serialize)]
+#[derive(Encode, Decode, scale_info::TypeInfo, Debug)]
+#[cfg_attr(feature = "std", derive(Deserialize, Serialize))]
 pub struct AssetId {
 	pub blockchain: Vec<u8>,
 	pub symbol: Vec<u8>,
@@ -59,12 +107,171 @@ impl AssetId {
 	}
 }
 
+/// Offchain worker settings that governance can change without a runtime upgrade, overriding
+/// the defaults of the pallet `Config`
+#[derive(Encode, Decode, scale_info::TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct OcwConfig {
+	/// Number of times a failed request to the batching server is repeated within one run
+	pub max_http_retries: u32,
+	/// Time in milliseconds to wait for the batching server to respond
+	pub http_request_timeout: u64,
+	/// Maximum number of currencies asked for in one request to the batching server
+	pub max_currencies_per_request: u32,
+	/// Relative price change below which an asset with a recent price is not resubmitted
+	pub min_change_threshold: Permill,
+}
+
+/// Encoding of the requests to and the responses from the batching server
+#[derive(Encode, Decode, scale_info::TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchingApiEncoding {
+	/// JSON, understood by every batching server
+	Json,
+	/// SCALE, with `Vec<AssetId>` requests and `Vec<CoinInfo>` responses
+	Scale,
+}
+
+impl Default for BatchingApiEncoding {
+	fn default() -> Self {
+		BatchingApiEncoding::Json
+	}
+}
+
+/// How the price of a derived asset is computed from the stored prices of other assets
+#[derive(Encode, Decode, scale_info::TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub enum DerivedFeed {
+	/// One divided by the price of the asset, e.g. USD-MXN from MXN-USD
+	Inverse((Vec<u8>, Vec<u8>)),
+	/// Price of `base` divided by the price of `quote`, e.g. BRL-MXN from BRL-USD and MXN-USD
+	Cross { base: (Vec<u8>, Vec<u8>), quote: (Vec<u8>, Vec<u8>) },
+}
+
+impl DerivedFeed {
+	/// `(blockchain, symbol)` of the assets the feed is computed from
+	pub fn sources(&self) -> Vec<&(Vec<u8>, Vec<u8>)> {
+		match self {
+			DerivedFeed::Inverse(asset) => vec![asset],
+			DerivedFeed::Cross { base, quote } => vec![base, quote],
+		}
+	}
+}
+
+/// How the offchain worker picks the local key it submits prices with
+#[derive(Encode, Decode, scale_info::TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub enum SubmitterSelection<AccountId> {
+	/// Let the signer pick any local key
+	Any,
+	/// Rotate through the local keys, one per submission
+	RoundRobin,
+	/// Use the local key with the highest balance, so the lowest balance is drained last
+	LowestBalanceLast,
+	/// Always use the given account, skipping submission if its key is not available locally
+	Pinned(AccountId),
+}
+
+impl<AccountId> Default for SubmitterSelection<AccountId> {
+	fn default() -> Self {
+		SubmitterSelection::Any
+	}
+}
+
+/// Balance lookup used by [`SubmitterSelection::LowestBalanceLast`]
+pub trait SubmitterBalance<AccountId> {
+	fn balance_of(who: &AccountId) -> u128;
+}
+
+impl<AccountId> SubmitterBalance<AccountId> for () {
+	fn balance_of(_: &AccountId) -> u128 {
+		0
+	}
+}
+
+/// [`SubmitterBalance`] backed by the free balance of a [`Currency`]
+pub struct CurrencyBalance<C>(PhantomData<C>);
+
+impl<AccountId, C> SubmitterBalance<AccountId> for CurrencyBalance<C>
+where
+	C: Currency<AccountId>,
+	C::Balance: Into<u128>,
+{
+	fn balance_of(who: &AccountId) -> u128 {
+		C::free_balance(who).into()
+	}
+}
+
+/// Everything the pallet knows about one asset, combined for front-end consumption
+#[derive(Encode, Decode, scale_info::TypeInfo, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AssetOverview {
+	/// Whether the asset is in the list of supported currencies
+	pub supported: bool,
+	/// Latest coin info, if any was ever submitted
+	pub coin_info: Option<CoinInfo>,
+	/// Block of the most recent update batch from any feeder
+	pub last_batch_block: Option<u64>,
+}
+
+/// Last failed price update of the offchain worker, kept in offchain storage
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct UpdateFailure {
+	/// Unix time in milliseconds when the update was given up
+	pub timestamp: u64,
+	/// Number of requests made before giving up
+	pub attempts: u32,
+	/// Name of the pallet error that ended the update
+	pub error: Vec<u8>,
+}
+
+/// Health of the offchain worker on this node, kept in offchain storage under
+/// [`crate::DIAGNOSTICS_KEY`] so operators can read it with `offchain_localStorageGet`
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, Default)]
+pub struct FeederDiagnostics<Hash> {
+	/// Unix time in milliseconds of the last successful request to the batching server
+	pub last_success: Option<u64>,
+	/// Last failed price update
+	pub last_failure: Option<UpdateFailure>,
+	/// Number of failed price updates since the last successful request
+	pub consecutive_failures: u32,
+	/// Hash of the coin infos last submitted, matching the `payload_hash` of its batch record
+	pub last_batch_hash: Option<Hash>,
+}
+
+/// Summary of one `set_updated_coin_infos` call, kept so that off-chain logs of the batching
+/// server can be cross-checked against what landed on chain.
+#[derive(Encode, Decode, scale_info::TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct BatchRecord<AccountId, BlockNumber, Hash> {
+	pub submitter: AccountId,
+	pub block: BlockNumber,
+	pub entries: u32,
+	pub payload_hash: Hash,
+}
+
+/// Running totals of the price submissions of one feeder
+#[derive(Encode, Decode, scale_info::TypeInfo, Debug, Clone, PartialEq, Eq, Default)]
+pub struct FeederStats<BlockNumber> {
+	/// Number of `set_updated_coin_infos` calls accepted from the feeder
+	pub accepted_batches: u32,
+	/// Number of coin infos stored from those calls
+	pub accepted_entries: u32,
+	/// Block of the last accepted submission
+	pub last_submission: Option<BlockNumber>,
+}
+
 #[derive(Eq, PartialEq, Encode, Decode, Default)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct PriceInfo {
 	pub value: u128,
 }
 
+/// Price of an asset with what consumers need to judge it, in a single lookup
+#[derive(Eq, PartialEq, Encode, Decode, Default, Clone, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct PriceWithMetadata {
+	pub value: u128,
+	pub last_update_timestamp: u64,
+	pub supply: u128,
+	/// Whether the price is older than the oracle accepts
+	pub is_stale: bool,
+}
+
 #[cfg(feature = "std")]
 impl Serialize for PriceInfo {
 	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -0,0 +1,314 @@
+//! Minimal JSON reader for batching server responses, so that the runtime does not have to carry
+//! serde_json. It accepts exactly the documents of RFC 8259 and keeps numbers as their source
+//! text, only integers are ever turned into values.
+
+use crate::CoinInfo;
+use frame_support::sp_std::{convert::TryInto, str, vec::Vec};
+
+/// Deepest nesting of arrays and objects that is read, matching the recursion limit of serde_json
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+	Null,
+	Bool(bool),
+	/// Source text of the number
+	Number(Vec<u8>),
+	/// UTF-8 bytes of the unescaped string
+	String(Vec<u8>),
+	Array(Vec<Value>),
+	/// Members in document order
+	Object(Vec<(Vec<u8>, Value)>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError;
+
+impl Value {
+	/// Returns the member with the given key, if this is an object. Like serde_json, the last one
+	/// counts when a key is given twice.
+	pub fn get(&self, key: &str) -> Option<&Value> {
+		match self {
+			Value::Object(members) =>
+				members.iter().rev().find(|(k, _)| k == key.as_bytes()).map(|(_, value)| value),
+			_ => None,
+		}
+	}
+
+	/// Takes the member returned by [`Value::get`] out of this object
+	pub fn remove(&mut self, key: &str) -> Option<Value> {
+		match self {
+			Value::Object(members) => {
+				let index = members.iter().rposition(|(k, _)| k == key.as_bytes())?;
+				Some(members.remove(index).1)
+			},
+			_ => None,
+		}
+	}
+
+	pub fn as_bytes(&self) -> Option<&[u8]> {
+		match self {
+			Value::String(string) => Some(string),
+			_ => None,
+		}
+	}
+
+	/// Returns the number if it is a non-negative integer that fits into a `u128`
+	pub fn as_u128(&self) -> Option<u128> {
+		match self {
+			Value::Number(digits) if digits.iter().all(u8::is_ascii_digit) =>
+				digits.iter().try_fold(0u128, |number, digit| {
+					number.checked_mul(10)?.checked_add((digit - b'0') as u128)
+				}),
+			_ => None,
+		}
+	}
+
+	pub fn as_u64(&self) -> Option<u64> {
+		self.as_u128()?.try_into().ok()
+	}
+}
+
+/// Parses a complete JSON document, only whitespace may follow the value
+pub fn parse(input: &[u8]) -> Result<Value, ParseError> {
+	let mut parser = Parser { input, position: 0 };
+	let value = parser.value(0)?;
+	parser.skip_whitespace();
+	if parser.position == input.len() {
+		Ok(value)
+	} else {
+		Err(ParseError)
+	}
+}
+
+/// Reads a coin info from an object in the batching server format. Returns `None` if a field is
+/// missing or of the wrong type; unknown fields are ignored. The 24h and market fields are
+/// optional, older servers do not send them.
+pub fn coin_info(value: &Value) -> Option<CoinInfo> {
+	Some(CoinInfo {
+		symbol: value.get("symbol")?.as_bytes()?.to_vec(),
+		name: value.get("name")?.as_bytes()?.to_vec(),
+		blockchain: value.get("blockchain")?.as_bytes()?.to_vec(),
+		supply: value.get("supply")?.as_u128()?,
+		last_update_timestamp: value.get("lastUpdateTimestamp")?.as_u64()?,
+		price: value.get("price")?.as_u128()?,
+		price_yesterday: optional_u128(value.get("priceYesterday"))?,
+		volume_yesterday_usd: optional_u128(value.get("volumeYesterdayUsd"))?,
+		market_cap: nullable_u128(value.get("marketCap"))?,
+		high_24h: nullable_u128(value.get("high24h"))?,
+		low_24h: nullable_u128(value.get("low24h"))?,
+	})
+}
+
+/// `Some(None)` for a missing or `null` member, `None` for one that is not an integer
+fn nullable_u128(value: Option<&Value>) -> Option<Option<u128>> {
+	match value {
+		None | Some(Value::Null) => Some(None),
+		Some(value) => value.as_u128().map(Some),
+	}
+}
+
+/// Zero for a missing member, `None` for one that is not an integer
+fn optional_u128(value: Option<&Value>) -> Option<u128> {
+	value.map_or(Some(0), Value::as_u128)
+}
+
+struct Parser<'a> {
+	input: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn peek(&self) -> Option<u8> {
+		self.input.get(self.position).copied()
+	}
+
+	fn next(&mut self) -> Result<u8, ParseError> {
+		let byte = self.peek().ok_or(ParseError)?;
+		self.position += 1;
+		Ok(byte)
+	}
+
+	fn expect(&mut self, expected: u8) -> Result<(), ParseError> {
+		if self.next()? == expected {
+			Ok(())
+		} else {
+			Err(ParseError)
+		}
+	}
+
+	fn skip_whitespace(&mut self) {
+		while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+			self.position += 1;
+		}
+	}
+
+	fn value(&mut self, depth: usize) -> Result<Value, ParseError> {
+		self.skip_whitespace();
+		match self.peek().ok_or(ParseError)? {
+			b'[' => self.array(depth + 1),
+			b'{' => self.object(depth + 1),
+			b'"' => self.string().map(Value::String),
+			b't' => self.literal(b"true", Value::Bool(true)),
+			b'f' => self.literal(b"false", Value::Bool(false)),
+			b'n' => self.literal(b"null", Value::Null),
+			_ => self.number(),
+		}
+	}
+
+	fn literal(&mut self, text: &[u8], value: Value) -> Result<Value, ParseError> {
+		if self.input[self.position..].starts_with(text) {
+			self.position += text.len();
+			Ok(value)
+		} else {
+			Err(ParseError)
+		}
+	}
+
+	fn array(&mut self, depth: usize) -> Result<Value, ParseError> {
+		if depth > MAX_DEPTH {
+			return Err(ParseError)
+		}
+		self.expect(b'[')?;
+		let mut elements = Vec::new();
+		self.skip_whitespace();
+		if self.peek() == Some(b']') {
+			self.position += 1;
+			return Ok(Value::Array(elements))
+		}
+		loop {
+			elements.push(self.value(depth)?);
+			self.skip_whitespace();
+			match self.next()? {
+				b',' => continue,
+				b']' => return Ok(Value::Array(elements)),
+				_ => return Err(ParseError),
+			}
+		}
+	}
+
+	fn object(&mut self, depth: usize) -> Result<Value, ParseError> {
+		if depth > MAX_DEPTH {
+			return Err(ParseError)
+		}
+		self.expect(b'{')?;
+		let mut members = Vec::new();
+		self.skip_whitespace();
+		if self.peek() == Some(b'}') {
+			self.position += 1;
+			return Ok(Value::Object(members))
+		}
+		loop {
+			self.skip_whitespace();
+			let key = self.string()?;
+			self.skip_whitespace();
+			self.expect(b':')?;
+			members.push((key, self.value(depth)?));
+			self.skip_whitespace();
+			match self.next()? {
+				b',' => continue,
+				b'}' => return Ok(Value::Object(members)),
+				_ => return Err(ParseError),
+			}
+		}
+	}
+
+	fn string(&mut self) -> Result<Vec<u8>, ParseError> {
+		self.expect(b'"')?;
+		let mut string = Vec::new();
+		loop {
+			match self.next()? {
+				b'"' => break,
+				b'\\' => match self.next()? {
+					b'"' => string.push(b'"'),
+					b'\\' => string.push(b'\\'),
+					b'/' => string.push(b'/'),
+					b'b' => string.push(0x08),
+					b'f' => string.push(0x0c),
+					b'n' => string.push(b'\n'),
+					b'r' => string.push(b'\r'),
+					b't' => string.push(b'\t'),
+					b'u' => {
+						let mut buffer = [0u8; 4];
+						let character = self.unicode_escape()?;
+						string.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+					},
+					_ => return Err(ParseError),
+				},
+				// Control characters have to be escaped
+				byte if byte < 0x20 => return Err(ParseError),
+				byte => string.push(byte),
+			}
+		}
+		str::from_utf8(&string).map_err(|_| ParseError)?;
+		Ok(string)
+	}
+
+	/// Reads the digits of a `\u` escape, combining surrogate pairs into one character
+	fn unicode_escape(&mut self) -> Result<char, ParseError> {
+		let high = self.hex_digits()?;
+		let code = if (0xD800..0xDC00).contains(&high) {
+			self.expect(b'\\')?;
+			self.expect(b'u')?;
+			let low = self.hex_digits()?;
+			if !(0xDC00..0xE000).contains(&low) {
+				return Err(ParseError)
+			}
+			0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+		} else {
+			high
+		};
+		// Lone low surrogates are not characters
+		char::from_u32(code).ok_or(ParseError)
+	}
+
+	fn hex_digits(&mut self) -> Result<u32, ParseError> {
+		let mut code = 0;
+		for _ in 0..4 {
+			let digit = (self.next()? as char).to_digit(16).ok_or(ParseError)?;
+			code = code * 16 + digit;
+		}
+		Ok(code)
+	}
+
+	fn number(&mut self) -> Result<Value, ParseError> {
+		let start = self.position;
+		if self.peek() == Some(b'-') {
+			self.position += 1;
+		}
+		match self.next()? {
+			b'0' => {},
+			b'1'..=b'9' => self.skip_digits(),
+			_ => return Err(ParseError),
+		}
+		if self.peek() == Some(b'.') {
+			self.position += 1;
+			self.digits()?;
+		}
+		if matches!(self.peek(), Some(b'e' | b'E')) {
+			self.position += 1;
+			if matches!(self.peek(), Some(b'+' | b'-')) {
+				self.position += 1;
+			}
+			self.digits()?;
+		}
+		Ok(Value::Number(self.input[start..self.position].to_vec()))
+	}
+
+	fn skip_digits(&mut self) {
+		while matches!(self.peek(), Some(b'0'..=b'9')) {
+			self.position += 1;
+		}
+	}
+
+	/// Skips at least one digit
+	fn digits(&mut self) -> Result<(), ParseError> {
+		let start = self.position;
+		self.skip_digits();
+		if self.position > start {
+			Ok(())
+		} else {
+			Err(ParseError)
+		}
+	}
+}
@@ -8,15 +8,49 @@ mod benchmarking;
 #[cfg(test)]
 mod tests;
 
-#[cfg(test)]
-pub(crate) mod mock;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 
+#[cfg(feature = "orml")]
+pub mod adapter;
+pub mod conversion;
+pub use conversion::{ConversionFromAssetBalance, OracleConversionRate};
 pub mod dia;
 pub use dia::*;
+pub mod json;
+pub mod membership;
+pub mod migrations;
+pub use membership::{AuthorizedAccountsMembership, ExternalMembership, FeederSet};
+pub mod price_filter;
+pub mod priority;
+pub use priority::PrioritizeOracleUpdates;
 pub mod weights;
 pub use sp_std::convert::TryInto;
 pub use weights::WeightInfo;
 
+/// Offchain local storage key of the node-local switch enabling price feeding. The offchain
+/// worker only feeds prices on nodes where it holds a SCALE encoded `true`.
+pub const FEEDER_ENABLED_KEY: &[u8] = b"dia-oracle::feeder-enabled";
+
+/// Offchain local storage key of the `Authorization` header value sent to the batching API,
+/// e.g. `Bearer <token>`. The value is stored as raw UTF-8 bytes, not SCALE encoded.
+pub const BATCHING_AUTH_HEADER_KEY: &[u8] = b"dia-oracle::batching-auth-header";
+
+/// Offchain local storage key of the SCALE encoded public key the offchain worker signs with on
+/// this node. For sr25519 keys that is the raw 32 byte public key. When set, it takes precedence
+/// over the on-chain `SubmitterSelectionStrategy`.
+pub const FEEDER_ACCOUNT_KEY: &[u8] = b"dia-oracle::feeder-account";
+
+/// Offchain local storage key of the SCALE encoded [`FeederDiagnostics`] of this node
+pub const DIAGNOSTICS_KEY: &[u8] = b"dia-oracle::diagnostics";
+
+/// Content type of SCALE encoded requests to and responses from the batching server
+pub const SCALE_CONTENT_TYPE: &str = "application/scale";
+
+/// Response header carrying the hex encoded sr25519 signature of the batching server over the
+/// response body. Only checked once a `BatchingServerKey` is set.
+pub const RESPONSE_SIGNATURE_HEADER: &str = "x-dia-signature";
+
 /// Based on the above `KeyTypeId` we need to generate a pallet-specific crypto type wrappers.
 /// We can use from supported crypto kinds (`sr25519`, `ed25519` and `ecdsa`) and augment
 /// the types with this pallet-specific identifier.
@@ -51,28 +85,87 @@ pub mod crypto {
 	}
 }
 
+/// Signing the offchain worker needs from the runtime, none with `no-ocw`
+#[cfg(not(feature = "no-ocw"))]
+pub trait OffchainSigning<LocalCall>:
+	frame_system::offchain::CreateSignedTransaction<LocalCall>
+{
+}
+
+#[cfg(not(feature = "no-ocw"))]
+impl<T, LocalCall> OffchainSigning<LocalCall> for T where
+	T: frame_system::offchain::CreateSignedTransaction<LocalCall>
+{
+}
+
+/// Signing the offchain worker needs from the runtime, none with `no-ocw`
+#[cfg(feature = "no-ocw")]
+pub trait OffchainSigning<LocalCall> {}
+
+#[cfg(feature = "no-ocw")]
+impl<T, LocalCall> OffchainSigning<LocalCall> for T {}
+
+/// Index of `set_updated_coin_infos` among the pallet's calls, for encoding it on other chains
+pub const SET_UPDATED_COIN_INFOS_CALL_INDEX: u8 = 4;
+
+/// Indices of the pallet errors that off-chain code tells apart in a `DispatchError::Module`
+pub mod error_index {
+	pub const NO_COIN_INFO_AVAILABLE: u8 = 0;
+	pub const ZERO_PRICE: u8 = 11;
+	pub const STALE_PRICE: u8 = 17;
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
 
 	use frame_support::{
-		dispatch::DispatchResult,
+		dispatch::{DispatchResult, DispatchResultWithPostInfo},
 		pallet_prelude::*,
-		sp_runtime::offchain,
+		sp_runtime::{
+			helpers_128bit::multiply_by_rational_with_rounding,
+			offchain::storage::StorageValueRef,
+			traits::{Hash, One, Zero},
+			ArithmeticError, Permill, Rounding, SaturatedConversion,
+		},
 		sp_std,
-		sp_std::{vec, vec::Vec},
+		sp_std::{collections::btree_map::BTreeMap, vec, vec::Vec},
+		traits::{Currency, ReservableCurrency, SortedMembers, UnixTime},
 	};
-	use frame_system::{
-		ensure_root, ensure_signed,
-		offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
-		pallet_prelude::*,
+	use frame_system::{ensure_root, ensure_signed, pallet_prelude::*};
+	// The offchain worker, its HTTP requests and its signing are compiled out with `no-ocw`
+	#[cfg(not(feature = "no-ocw"))]
+	use frame_support::{
+		sp_io,
+		sp_runtime::offchain::{
+			self,
+			storage_lock::{StorageLock, Time},
+			Duration,
+		},
 	};
+	#[cfg(not(feature = "no-ocw"))]
+	use frame_system::offchain::{Account, AppCrypto, SendSignedTransaction, Signer};
+	use sp_core::sr25519;
 
 	const BATCHING_ENDPOINT_FALLBACK: [u8; 31] = *b"http://0.0.0.0:8070/currencies/";
 
+	const ROUND_ROBIN_INDEX_KEY: &[u8] = b"dia-oracle::round-robin-index";
+
+	/// Held while prices are updated, so that overlapping offchain worker runs do not send the
+	/// same request and transaction twice. Expires on its own if a run dies while holding it.
+	const UPDATE_LOCK_KEY: &[u8] = b"dia-oracle::update-lock";
+	/// Time the update lock is held beyond the worst case of the requests, for the submission
+	const UPDATE_LOCK_MARGIN_MS: u64 = 60_000;
+
+	/// Hashes of the batches this node submitted within `DuplicateBatchWindow`, with their block
+	const SUBMITTED_BATCHES_KEY: &[u8] = b"dia-oracle::submitted-batches";
+
+	/// Highest version of the batching server response envelope this runtime understands
+	const MAX_RESPONSE_VERSION: u64 = 1;
+
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config: frame_system::Config + CreateSignedTransaction<Call<Self>> {
+	pub trait Config: frame_system::Config + OffchainSigning<Call<Self>> {
 		/// The overarching event type.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
@@ -80,18 +173,134 @@ pub mod pallet {
 		type RuntimeCall: From<Call<Self>>;
 
 		/// The identifier type for an offchain worker.
+		#[cfg(not(feature = "no-ocw"))]
 		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
 
 		/// Weight of pallet
 		type WeightInfo: weights::WeightInfo;
+
+		/// Maximum number of update batches kept in the batch log
+		#[pallet::constant]
+		type MaxBatchLogEntries: Get<u32>;
+
+		/// Source of the accounts allowed to feed prices and manage the pallet. Use
+		/// [`AuthorizedAccountsMembership`] to keep the internal `AuthorizedAccounts` map, or
+		/// [`ExternalMembership`] for a set managed by another pallet.
+		type FeederMembership: FeederSet<Self::AccountId>;
+
+		/// Balance lookup for the `LowestBalanceLast` submitter selection
+		type SubmitterBalance: SubmitterBalance<Self::AccountId>;
+
+		// The offchain worker's HTTP settings below are no `#[pallet::constant]`s, those cannot be
+		// compiled out with `no-ocw`
+
+		/// Time in milliseconds the offchain worker waits for the batching server to respond
+		#[cfg(not(feature = "no-ocw"))]
+		type HttpRequestTimeout: Get<u64>;
+
+		/// Maximum number of currencies asked for in one request to the batching server
+		#[cfg(not(feature = "no-ocw"))]
+		type MaxCurrenciesPerRequest: Get<u32>;
+
+		/// Maximum size in bytes of a response body read from the batching server
+		#[cfg(not(feature = "no-ocw"))]
+		type MaxResponseSize: Get<u32>;
+
+		/// Number of times the offchain worker repeats a failed request within one run
+		#[cfg(not(feature = "no-ocw"))]
+		type MaxHttpRetries: Get<u32>;
+
+		/// Time in milliseconds to wait before the first retry, growing linearly with each retry
+		#[cfg(not(feature = "no-ocw"))]
+		type HttpRetryBackoff: Get<u64>;
+
+		/// Maximum length in bytes of the batching API URL
+		#[pallet::constant]
+		type MaxApiUrlLength: Get<u32>;
+
+		/// Whether coin infos with a price of zero are accepted by `set_updated_coin_infos`
+		#[pallet::constant]
+		type AllowZeroPrices: Get<bool>;
+
+		/// Source of the current time, used to reject coin infos from the future or too old
+		type UnixTime: UnixTime;
+
+		/// Maximum age in seconds of the `last_update_timestamp` of an accepted coin info
+		#[pallet::constant]
+		type MaxPriceAge: Get<u64>;
+
+		/// Time in seconds the `last_update_timestamp` of an accepted coin info may lie ahead of
+		/// `UnixTime`, tolerating clock drift between the feeder and the block author
+		#[pallet::constant]
+		type MaxFutureDrift: Get<u64>;
+
+		/// Priority given to price updates from authorized accounts by [`PrioritizeOracleUpdates`]
+		#[pallet::constant]
+		type UpdatePriority: Get<TransactionPriority>;
+
+		/// Longevity given to price updates from authorized accounts by
+		/// [`PrioritizeOracleUpdates`]
+		#[pallet::constant]
+		type UpdateLongevity: Get<TransactionLongevity>;
+
+		/// Number of blocks after which a feeder without price changes to submit sends a heartbeat
+		#[pallet::constant]
+		type HeartbeatInterval: Get<Self::BlockNumber>;
+
+		/// Maximum number of batching APIs queried in addition to `BatchingApi`
+		#[pallet::constant]
+		type MaxExtraBatchingApis: Get<u32>;
+
+		/// Maximum deviation of any source's price from the median before the asset is skipped
+		#[pallet::constant]
+		type MaxSourceDeviation: Get<Permill>;
+
+		/// Number of decimals of the prices submitted by the offchain worker. The batching server
+		/// sends prices with 12 decimals, which are rescaled before they are compared and
+		/// submitted.
+		#[pallet::constant]
+		type PriceDecimals: Get<u8>;
+
+		/// Notified of every stored price update, `()` if nothing has to happen
+		type OnPriceUpdate: OnPriceUpdate;
+
+		/// Maximum number of derived feeds computed from the stored prices
+		#[pallet::constant]
+		type MaxDerivedFeeds: Get<u32>;
+
+		/// Currency the deposit for supported currencies is reserved in
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Deposit reserved from the account adding a currency, refunded when it is removed.
+		/// Currencies added by root are free.
+		#[pallet::constant]
+		type CurrencyDeposit: Get<BalanceOf<Self>>;
+
+		/// Number of blocks between `add_currency` and the currency becoming supported, giving
+		/// governance time to review or cancel the addition. Zero adds currencies at once.
+		#[pallet::constant]
+		type CurrencyActivationDelay: Get<Self::BlockNumber>;
+
+		/// Number of blocks during which the offchain worker does not submit a batch identical to
+		/// one it already submitted. Zero disables the check.
+		#[pallet::constant]
+		type DuplicateBatchWindow: Get<Self::BlockNumber>;
 	}
 
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// The current storage version
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
-	/// List of all authorized accounts
+	/// List of all authorized accounts. Only consulted when `FeederMembership` is
+	/// [`AuthorizedAccountsMembership`].
 	#[pallet::storage]
 	#[pallet::getter(fn authorized_accounts)]
 	pub type AuthorizedAccounts<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
@@ -101,14 +310,133 @@ pub mod pallet {
 	#[pallet::getter(fn supported_currencies)]
 	pub type SupportedCurrencies<T: Config> = StorageMap<_, Blake2_128Concat, AssetId, ()>;
 
+	/// Currencies added while `CurrencyActivationDelay` is set, with the block they become
+	/// supported at
+	#[pallet::storage]
+	#[pallet::getter(fn pending_currencies)]
+	pub type PendingCurrencies<T: Config> =
+		StorageMap<_, Blake2_128Concat, AssetId, T::BlockNumber>;
+
+	/// The pending currencies keyed by the block they become supported at, so that
+	/// `on_initialize` only touches the currencies due in its block
+	#[pallet::storage]
+	pub type PendingActivations<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::BlockNumber, Blake2_128Concat, AssetId, ()>;
+
+	/// Currencies that can neither be added nor updated
+	#[pallet::storage]
+	#[pallet::getter(fn blocked_currencies)]
+	pub type BlockedCurrencies<T: Config> = StorageMap<_, Blake2_128Concat, AssetId, ()>;
+
+	/// Account that added a currency and the deposit reserved from it
+	#[pallet::storage]
+	#[pallet::getter(fn currency_deposits)]
+	pub type CurrencyDeposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, AssetId, (T::AccountId, BalanceOf<T>)>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn batching_api)]
 	pub type BatchingApi<T: Config> = StorageValue<_, Vec<u8>>;
 
-	/// Map of all the coins names to their respective info and price
+	/// Batching APIs queried in addition to `BatchingApi`, the offchain worker submits the
+	/// median of all of them
+	#[pallet::storage]
+	#[pallet::getter(fn extra_batching_apis)]
+	pub type ExtraBatchingApis<T: Config> = StorageValue<_, Vec<Vec<u8>>, ValueQuery>;
+
+	/// Encoding the offchain worker talks to the batching servers with
+	#[pallet::storage]
+	#[pallet::getter(fn batching_api_encoding)]
+	pub type BatchingEncoding<T: Config> = StorageValue<_, BatchingApiEncoding, ValueQuery>;
+
+	/// Assets whose prices are computed from the prices of other assets whenever those are
+	/// updated, keyed by `(blockchain, symbol)` of the derived asset
+	#[pallet::storage]
+	#[pallet::getter(fn derived_feeds)]
+	pub type DerivedFeeds<T: Config> =
+		StorageValue<_, Vec<((Vec<u8>, Vec<u8>), DerivedFeed)>, ValueQuery>;
+
+	/// Info and price of every coin, keyed by blockchain and then symbol
 	#[pallet::storage]
 	#[pallet::getter(fn prices_map)]
-	pub type CoinInfosMap<T> = StorageMap<_, Blake2_128Concat, AssetId, CoinInfo, ValueQuery>;
+	pub type CoinInfosMap<T> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		CoinInfo,
+		ValueQuery,
+	>;
+
+	/// Feeder that last updated each coin info and the block it did so in, keyed like
+	/// `CoinInfosMap`
+	#[pallet::storage]
+	#[pallet::getter(fn price_submitters)]
+	pub type PriceSubmitters<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		(T::AccountId, T::BlockNumber),
+	>;
+
+	#[pallet::type_value]
+	pub fn DefaultUpdateInterval<T: Config>() -> T::BlockNumber {
+		One::one()
+	}
+
+	/// Number of blocks between two price updates of the offchain worker
+	#[pallet::storage]
+	#[pallet::getter(fn update_interval)]
+	pub type UpdateInterval<T: Config> =
+		StorageValue<_, T::BlockNumber, ValueQuery, DefaultUpdateInterval<T>>;
+
+	/// How the offchain worker picks the account it submits prices with
+	#[pallet::storage]
+	#[pallet::getter(fn submitter_selection)]
+	pub type SubmitterSelectionStrategy<T: Config> =
+		StorageValue<_, SubmitterSelection<T::AccountId>, ValueQuery>;
+
+	/// Log of the most recent update batches, oldest first. Encoded like a `Vec`, so lowering
+	/// `MaxBatchLogEntries` below the number of stored entries empties the log.
+	#[pallet::storage]
+	#[pallet::getter(fn batch_log)]
+	pub type BatchLog<T: Config> = StorageValue<
+		_,
+		BoundedVec<BatchRecord<T::AccountId, T::BlockNumber, T::Hash>, T::MaxBatchLogEntries>,
+		ValueQuery,
+	>;
+
+	/// Key the batching server signs its responses with. Unsigned or wrongly signed responses
+	/// are rejected by the offchain worker while it is set.
+	#[pallet::storage]
+	#[pallet::getter(fn batching_server_key)]
+	pub type BatchingServerKey<T: Config> = StorageValue<_, sr25519::Public>;
+
+	/// Block of the last sign of life of each feeder, a price submission or a heartbeat
+	#[pallet::storage]
+	#[pallet::getter(fn last_heartbeat)]
+	pub type LastHeartbeat<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber>;
+
+	/// Offchain worker settings overriding the `Config` defaults, see [`Pallet::ocw_config`]
+	#[pallet::storage]
+	pub type OcwSettings<T: Config> = StorageValue<_, OcwConfig>;
+
+	/// Relative price change below which the offchain worker does not resubmit an asset with a
+	/// recent price, overriding the `min_change_threshold` of the OCW config, e.g. for
+	/// stablecoins. A basis point is `Permill::from_parts(100)`.
+	#[pallet::storage]
+	#[pallet::getter(fn min_change_thresholds)]
+	pub type MinChangeThresholds<T: Config> = StorageMap<_, Blake2_128Concat, AssetId, Permill>;
+
+	/// Price submission statistics of each feeder
+	#[pallet::storage]
+	#[pallet::getter(fn feeder_stats)]
+	pub type FeederStatistics<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, FeederStats<T::BlockNumber>, ValueQuery>;
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -123,8 +451,46 @@ pub mod pallet {
 		CurrencyAdded(Vec<u8>, Vec<u8>),
 		/// Event is triggered when currency is remove from the list
 		CurrencyRemoved(Vec<u8>, Vec<u8>),
+		/// Event is triggered when currency is scheduled to be added at the given block
+		CurrencyAdditionScheduled(Vec<u8>, Vec<u8>, T::BlockNumber),
+		/// Event is triggered when a scheduled currency addition is cancelled
+		CurrencyAdditionCancelled(Vec<u8>, Vec<u8>),
+		/// Event is triggered when currency is added to the blocklist
+		CurrencyBlocked(Vec<u8>, Vec<u8>),
+		/// Event is triggered when currency is removed from the blocklist
+		CurrencyUnblocked(Vec<u8>, Vec<u8>),
 		/// Event is triggered when batching api route is set from the list
 		BatchingApiRouteSet(Vec<u8>),
+		/// Event is triggered when the submitter selection strategy is changed
+		SubmitterSelectionSet(SubmitterSelection<T::AccountId>),
+		/// Event is triggered with the account that submitted updated prices
+		PricesSubmittedBy(T::AccountId),
+		/// Event is triggered when an authorized account replaces itself with a new account
+		FeederKeyRotated(T::AccountId, T::AccountId),
+		/// Event is triggered when the whole set of authorized accounts is replaced
+		AuthorizedAccountsReset(Vec<T::AccountId>),
+		/// Event is triggered when the offchain worker update interval is changed
+		UpdateIntervalSet(T::BlockNumber),
+		/// Event is triggered when the batching server response signing key is set or removed
+		BatchingServerKeySet(Option<sr25519::Public>),
+		/// Event is triggered when a feeder without price changes to submit reports it is alive
+		FeederHeartbeat(T::AccountId),
+		/// Event is triggered when the additional batching api routes are set
+		ExtraBatchingApisSet(Vec<Vec<u8>>),
+		/// Event is triggered when the batching api encoding is changed
+		BatchingApiEncodingSet(BatchingApiEncoding),
+		/// Event is triggered when the offchain worker settings are changed, `None` restoring the
+		/// defaults
+		OcwConfigSet(Option<OcwConfig>),
+		/// Event is triggered when the minimum price change of an asset is set, `None` falling
+		/// back to the OCW config
+		MinChangeThresholdSet(Vec<u8>, Vec<u8>, Option<Permill>),
+		/// Event is triggered when the derived feeds are set
+		DerivedFeedsSet(Vec<((Vec<u8>, Vec<u8>), DerivedFeed)>),
+		/// Event is triggered when coin infos of all assets are removed, with the number removed
+		PricesCleared(u32),
+		/// Event is triggered when coin infos of a blockchain are removed, with the number removed
+		BlockchainPurged(Vec<u8>, u32),
 	}
 
 	// Errors inform users that something went wrong.
@@ -159,6 +525,63 @@ pub mod pallet {
 
 		/// BadOrigin
 		BadOrigin,
+
+		/// No local key matches the submitter selection strategy
+		NoSubmitterAccount,
+
+		/// A price of zero was submitted while zero prices are not allowed
+		ZeroPrice,
+
+		/// The account is already authorized
+		AccountAlreadyAuthorized,
+
+		/// Batching API URL is not valid UTF-8, has no http(s) scheme or is too long
+		InvalidApiUrl,
+
+		/// Update interval must be at least one block
+		InvalidUpdateInterval,
+
+		/// Batching Server response is unsigned or its signature does not match the server key
+		InvalidResponseSignature,
+
+		/// A coin info was last updated more than `MaxFutureDrift` in the future
+		FutureTimestamp,
+
+		/// A coin info was last updated longer than `MaxPriceAge` ago
+		StalePrice,
+
+		/// A coin info was submitted for a currency that is not supported
+		CurrencyNotSupported,
+
+		/// The currency is on the blocklist
+		CurrencyBlocked,
+
+		/// More additional batching APIs than `MaxExtraBatchingApis`
+		TooManyBatchingApis,
+
+		/// More derived feeds than `MaxDerivedFeeds`
+		TooManyDerivedFeeds,
+
+		/// A derived feed is computed from itself or from another derived feed
+		InvalidDerivedFeed,
+
+		/// Offchain worker settings with a zero request timeout or request size
+		InvalidOcwConfig,
+
+		/// The currency is not scheduled to be added
+		NoPendingCurrency,
+
+		/// Batching Server response body is larger than `MaxResponseSize`
+		ResponseTooLarge,
+
+		/// Batching Server did not respond in time
+		HttpTimeout,
+
+		/// The feeders are managed outside of the pallet, through its `FeederMembership`
+		ExternalFeederMembership,
+
+		/// More accounts are authorized than `reset_authorized_accounts` may remove
+		TooManyAuthorizedAccounts,
 	}
 
 	#[pallet::genesis_config]
@@ -195,9 +618,98 @@ pub mod pallet {
 		}
 	}
 
+	/// Identifier of the development genesis preset
+	pub const DEV_PRESET: &str = "development";
+	/// Identifier of the Pendulum genesis preset
+	pub const PENDULUM_PRESET: &str = "pendulum";
+	/// Identifier of the Amplitude genesis preset
+	pub const AMPLITUDE_PRESET: &str = "amplitude";
+
+	#[cfg(feature = "std")]
+	impl<T: Config> GenesisConfig<T> {
+		/// Returns the named genesis preset with the given accounts authorized to feed prices,
+		/// or `None` if no preset with that identifier exists.
+		pub fn preset(id: &str, authorized_accounts: Vec<T::AccountId>) -> Option<Self> {
+			let currencies: &[(&[u8], &[u8])] = match id {
+				DEV_PRESET => &[(b"Bitcoin", b"BTC")],
+				PENDULUM_PRESET => &[
+					(b"Polkadot", b"DOT"),
+					(b"Bitcoin", b"BTC"),
+					(b"Ethereum", b"ETH"),
+					(b"Ethereum", b"USDT"),
+				],
+				AMPLITUDE_PRESET => &[
+					(b"Kusama", b"KSM"),
+					(b"Bitcoin", b"BTC"),
+					(b"Ethereum", b"ETH"),
+					(b"Ethereum", b"USDT"),
+				],
+				_ => return None,
+			};
+
+			Some(Self {
+				authorized_accounts,
+				supported_currencies: currencies
+					.iter()
+					.map(|(blockchain, symbol)| AssetId::new(blockchain.to_vec(), symbol.to_vec()))
+					.collect(),
+				batching_api: b"http://localhost:8070/currencies".to_vec(),
+				coin_infos_map: Default::default(),
+			})
+		}
+
+		/// Identifiers of all the available genesis presets
+		pub fn preset_names() -> Vec<&'static str> {
+			vec![DEV_PRESET, PENDULUM_PRESET, AMPLITUDE_PRESET]
+		}
+	}
+
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-		fn offchain_worker(_n: T::BlockNumber) {
+		/// Adds the pending currencies whose activation block is reached
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			let mut activated = 0u64;
+			for (asset_id, ()) in <PendingActivations<T>>::drain_prefix(n) {
+				<PendingCurrencies<T>>::remove(&asset_id);
+				Self::deposit_event(Event::<T>::CurrencyAdded(
+					asset_id.blockchain.clone(),
+					asset_id.symbol.clone(),
+				));
+				<SupportedCurrencies<T>>::insert(asset_id, ());
+				activated += 1;
+			}
+
+			T::DbWeight::get().reads_writes(activated + 1, 3 * activated)
+		}
+
+		#[cfg(not(feature = "no-ocw"))]
+		fn offchain_worker(n: T::BlockNumber) {
+			if !Self::is_update_block(n) {
+				log::debug!("Skipping price update at block {:?}", n);
+				return
+			}
+
+			if !Self::is_feeder_enabled() {
+				log::debug!("Price feeding is not enabled on this node, skipping price update");
+				return
+			}
+
+			// Nodes without a `dia!` key are not feeders, nothing to do for them
+			if !Signer::<T, T::AuthorityId>::any_account().can_sign() {
+				log::debug!("No oracle signing key available, skipping price update");
+				return
+			}
+
+			let mut lock =
+				StorageLock::<Time>::with_deadline(UPDATE_LOCK_KEY, Self::update_lock_expiration());
+			let _guard = match lock.try_lock() {
+				Ok(guard) => guard,
+				Err(_) => {
+					log::info!("Price update already in progress, skipping block {:?}", n);
+					return
+				},
+			};
+
 			match Self::update_prices() {
 				Ok(_) => log::info!("Updated Prices"),
 				Err(e) => log::error!("Failed to Update Prices {:?}", e),
@@ -207,9 +719,11 @@ pub mod pallet {
 
 	impl<T: Config> DiaOracle for Pallet<T> {
 		fn get_coin_info(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<CoinInfo, DispatchError> {
-			let asset_id = AssetId { blockchain, symbol };
-			ensure!(<CoinInfosMap<T>>::contains_key(&asset_id), Error::<T>::NoCoinInfoAvailable);
-			let result = <CoinInfosMap<T>>::get(&asset_id);
+			ensure!(
+				<CoinInfosMap<T>>::contains_key(&blockchain, &symbol),
+				Error::<T>::NoCoinInfoAvailable
+			);
+			let result = <CoinInfosMap<T>>::get(&blockchain, &symbol);
 			Ok(result)
 		}
 
@@ -217,13 +731,45 @@ pub mod pallet {
 			<Pallet<T> as DiaOracle>::get_coin_info(blockchain, symbol)
 				.map(|info| PriceInfo { value: info.price })
 		}
+
+		fn get_value_with_metadata(
+			blockchain: Vec<u8>,
+			symbol: Vec<u8>,
+		) -> Result<PriceWithMetadata, DispatchError> {
+			let info = <Pallet<T> as DiaOracle>::get_coin_info(blockchain, symbol)?;
+			let age = T::UnixTime::now().as_secs().saturating_sub(info.last_update_timestamp);
+			Ok(PriceWithMetadata {
+				value: info.price,
+				last_update_timestamp: info.last_update_timestamp,
+				supply: info.supply,
+				is_stale: age > T::MaxPriceAge::get(),
+			})
+		}
 	}
 
 	impl<T: Config> Pallet<T> {
-		fn update_prices() -> Result<(), Error<T>> {
-			// Expected contract for the API with the server is supported currencies in URL path and
-			// json encoded Vec<CoinInfo> as a result from the server
-			let supported_currencies = <SupportedCurrencies<T>>::iter_keys()
+		/// Bodies listing the supported currencies as sent to the batching server, at most
+		/// `MaxCurrenciesPerRequest` currencies each, in the configured `BatchingEncoding`. Empty
+		/// if no currency is supported.
+		#[cfg(not(feature = "no-ocw"))]
+		pub fn supported_currencies_request_bodies() -> Vec<Vec<u8>> {
+			let supported_currencies = <SupportedCurrencies<T>>::iter_keys().collect::<Vec<_>>();
+			let encoding = Self::batching_api_encoding();
+
+			let chunk_size = (Self::ocw_config().max_currencies_per_request as usize).max(1);
+			supported_currencies
+				.chunks(chunk_size)
+				.map(|chunk| match encoding {
+					BatchingApiEncoding::Json => Self::json_request_body(chunk),
+					BatchingApiEncoding::Scale => chunk.encode(),
+				})
+				.collect()
+		}
+
+		#[cfg(not(feature = "no-ocw"))]
+		fn json_request_body(currencies: &[AssetId]) -> Vec<u8> {
+			let currencies = currencies
+				.iter()
 				.map(|AssetId { blockchain, symbol }| {
 					[
 						&b"{\"blockchain\":\""[..],
@@ -234,37 +780,458 @@ pub mod pallet {
 					]
 					.concat()
 				})
-				.collect::<Vec<_>>()
-				.join(&b',');
+				.collect::<Vec<_>>();
+			[&b"["[..], &currencies.join(&b',')[..], &b"]"[..]].concat()
+		}
 
-			if supported_currencies.len() == 0 {
-				return Ok(());
+		#[cfg(not(feature = "no-ocw"))]
+		fn update_prices() -> Result<(), Error<T>> {
+			// Expected contract for the API with the server is supported currencies in URL path and
+			// json encoded Vec<CoinInfo> as a result from the server
+			let request_bodies = Self::supported_currencies_request_bodies();
+			if request_bodies.is_empty() {
+				return Ok(())
 			}
 
-			let supported_currencies: Vec<_> =
-				[&b"["[..], &supported_currencies[..], &b"]"[..]].concat();
-
 			let api = Self::batching_api()
 				.ok_or(<Error<T>>::NoBatchingApiEndPoint) // Error Redundant but Explains Error Reason
 				.unwrap_or(BATCHING_ENDPOINT_FALLBACK.to_vec());
+			let mut apis = vec![api];
+			apis.extend(Self::extra_batching_apis());
+
+			let now = sp_io::offchain::timestamp().unix_millis() / 1_000;
+			let mut sources = Vec::new();
+			let mut last_error = None;
+			for api in &apis {
+				let api =
+					sp_std::str::from_utf8(api).map_err(|_| <Error<T>>::DeserializeStrError)?;
+				let mut prices = Vec::new();
+				for body in &request_bodies {
+					match Self::fetch_coin_infos_with_retry(api, body) {
+						Ok(coin_infos) => prices.extend(coin_infos),
+						Err(e) => {
+							log::error!("Failed to fetch a chunk of prices from {} {:?}", api, e);
+							last_error = Some(e);
+						},
+					}
+				}
+				if !prices.is_empty() {
+					sources.push(Self::plausible_coin_infos(prices, now));
+				}
+			}
 
-			let api = sp_std::str::from_utf8(&api).map_err(|_| <Error<T>>::DeserializeStrError)?;
-			let request = offchain::http::Request::post(api, vec![supported_currencies])
-				.add_header("content-type", "application/json");
-
-			let pending = request.send().map_err(|_| <Error<T>>::HttpRequestSendFailed)?;
-			let response = pending.wait().map_err(|_| <Error<T>>::HttpRequestFailed)?;
-			let body = response.body().collect::<Vec<u8>>();
+			if let Some(e) = last_error.filter(|_| sources.is_empty()) {
+				return Err(e)
+			}
 
-			let prices: Vec<CoinInfo> =
-				serde_json::from_slice(&body).map_err(|_| <Error<T>>::DeserializeError)?;
+			let prices = Self::aggregate_sources(sources, apis.len());
+			if prices.is_empty() {
+				return Ok(())
+			}
 
 			let prices: Vec<((Vec<u8>, Vec<u8>), CoinInfo)> = prices
 				.into_iter()
+				.filter_map(Self::scale_coin_info)
 				.map(|p| ((p.blockchain.clone(), p.symbol.clone()), p))
 				.collect();
 
-			let signer = Signer::<T, T::AuthorityId>::any_account();
+			let prices = Self::supported_coin_infos(prices);
+			let prices = Self::changed_coin_infos(prices);
+			if prices.is_empty() {
+				log::debug!("No price changed since the last update");
+				return Self::send_heartbeat_if_due()
+			}
+
+			Self::submit_coin_infos(prices)
+		}
+
+		/// Converts the price of a coin info from the batching server's decimals to
+		/// `PriceDecimals`, truncating the digits that are cut off. Coin infos whose price does not
+		/// fit into a `u128` any more are dropped, as are those truncated to a zero price while
+		/// zero prices are not allowed.
+		pub(crate) fn scale_coin_info(mut coin_info: CoinInfo) -> Option<CoinInfo> {
+			let decimals = T::PriceDecimals::get();
+			coin_info.price = match price_filter::scale_price(coin_info.price, decimals) {
+				Some(price) => price,
+				None => {
+					log::warn!(
+						"Skipping {:?}, its price overflows with {} decimals",
+						coin_info.symbol,
+						decimals
+					);
+					return None
+				},
+			};
+			if coin_info.price == 0 && !T::AllowZeroPrices::get() {
+				log::warn!(
+					"Skipping {:?}, its price is zero with {} decimals",
+					coin_info.symbol,
+					decimals
+				);
+				return None
+			}
+			Some(coin_info)
+		}
+
+		/// Combines the coin infos fetched from `source_count` batching servers into one per asset,
+		/// the one with the median price (the lower one for an even number of quotes). Assets
+		/// quoted by no more than half of the sources, or with a quote deviating from the median
+		/// by more than `MaxSourceDeviation`, are dropped.
+		pub(crate) fn aggregate_sources(
+			sources: Vec<Vec<CoinInfo>>,
+			source_count: usize,
+		) -> Vec<CoinInfo> {
+			if source_count <= 1 {
+				return sources.into_iter().flatten().collect()
+			}
+
+			let mut quotes_by_asset: BTreeMap<(Vec<u8>, Vec<u8>), Vec<CoinInfo>> = BTreeMap::new();
+			for coin_info in sources.into_iter().flatten() {
+				quotes_by_asset
+					.entry((coin_info.blockchain.clone(), coin_info.symbol.clone()))
+					.or_default()
+					.push(coin_info);
+			}
+
+			quotes_by_asset
+				.into_iter()
+				.filter_map(|((_, symbol), mut quotes)| {
+					if quotes.len() * 2 <= source_count {
+						log::warn!("Too few sources quote {:?}, skipping it", symbol);
+						return None
+					}
+
+					quotes.sort_by_key(|quote| quote.price);
+					let median = quotes.swap_remove((quotes.len() - 1) / 2);
+					let tolerance = T::MaxSourceDeviation::get().mul_floor(median.price);
+					if quotes.iter().any(|quote| quote.price.abs_diff(median.price) > tolerance) {
+						log::warn!("Sources disagree on the price of {:?}, skipping it", symbol);
+						return None
+					}
+					Some(median)
+				})
+				.collect()
+		}
+
+		/// Drops the coin infos `set_updated_coin_infos` would reject, so that one implausible
+		/// entry does not fail the whole submission.
+		pub(crate) fn plausible_coin_infos(coin_infos: Vec<CoinInfo>, now: u64) -> Vec<CoinInfo> {
+			coin_infos
+				.into_iter()
+				.filter(|coin_info| match Self::check_coin_info(coin_info, now) {
+					Ok(()) => true,
+					Err(e) => {
+						log::warn!("Dropping coin info {:?}: {:?}", coin_info.symbol, e);
+						false
+					},
+				})
+				.collect()
+		}
+
+		/// Checks the price and the timestamp of a coin info against `now` in seconds.
+		fn check_coin_info(coin_info: &CoinInfo, now: u64) -> Result<(), Error<T>> {
+			let limits = Self::price_limits();
+			price_filter::check_coin_info(coin_info, now, &limits).map_err(|e| match e {
+				price_filter::Implausible::ZeroPrice => Error::<T>::ZeroPrice,
+				price_filter::Implausible::FutureTimestamp => Error::<T>::FutureTimestamp,
+				price_filter::Implausible::StalePrice => Error::<T>::StalePrice,
+			})
+		}
+
+		/// Limits `set_updated_coin_infos` enforces on every coin info
+		pub fn price_limits() -> price_filter::PriceLimits {
+			price_filter::PriceLimits {
+				allow_zero_prices: T::AllowZeroPrices::get(),
+				max_future_drift: T::MaxFutureDrift::get(),
+				max_price_age: T::MaxPriceAge::get(),
+			}
+		}
+
+		/// Drops the coin infos of currencies that are not supported, whatever the batching
+		/// server returned for them.
+		pub(crate) fn supported_coin_infos(
+			coin_infos: Vec<((Vec<u8>, Vec<u8>), CoinInfo)>,
+		) -> Vec<((Vec<u8>, Vec<u8>), CoinInfo)> {
+			coin_infos
+				.into_iter()
+				.filter(|((blockchain, symbol), _)| {
+					let supported = Self::is_supported(blockchain, symbol);
+					if !supported {
+						log::warn!("Dropping coin info of unsupported currency {:?}", symbol);
+					}
+					supported
+				})
+				.collect()
+		}
+
+		/// Unreserves the deposit taken when the currency was added, if any
+		fn refund_currency_deposit(asset_id: &AssetId) {
+			if let Some((depositor, deposit)) = <CurrencyDeposits<T>>::take(asset_id) {
+				T::Currency::unreserve(&depositor, deposit);
+			}
+		}
+
+		fn is_supported(blockchain: &[u8], symbol: &[u8]) -> bool {
+			let asset_id = AssetId::new(blockchain.to_vec(), symbol.to_vec());
+			<SupportedCurrencies<T>>::contains_key(asset_id)
+		}
+
+		/// Offchain worker settings in effect, the stored ones or else the `Config` defaults
+		#[cfg(not(feature = "no-ocw"))]
+		pub fn ocw_config() -> OcwConfig {
+			<OcwSettings<T>>::get().unwrap_or_else(|| OcwConfig {
+				max_http_retries: T::MaxHttpRetries::get(),
+				http_request_timeout: T::HttpRequestTimeout::get(),
+				max_currencies_per_request: T::MaxCurrenciesPerRequest::get(),
+				min_change_threshold: Permill::zero(),
+			})
+		}
+
+		/// Drops the coin infos that are already stored on chain unchanged, or whose price moved
+		/// by less than the asset's `MinChangeThresholds` entry, or else the `min_change_threshold`
+		/// of the OCW config, since a stored price that is younger than half of `MaxPriceAge`.
+		#[cfg(not(feature = "no-ocw"))]
+		pub(crate) fn changed_coin_infos(
+			coin_infos: Vec<((Vec<u8>, Vec<u8>), CoinInfo)>,
+		) -> Vec<((Vec<u8>, Vec<u8>), CoinInfo)> {
+			let default_threshold = Self::ocw_config().min_change_threshold;
+			coin_infos
+				.into_iter()
+				.filter(|((blockchain, symbol), coin_info)| {
+					let stored = match <CoinInfosMap<T>>::try_get(blockchain, symbol) {
+						Ok(stored) => stored,
+						Err(_) => return true,
+					};
+					let threshold = <MinChangeThresholds<T>>::get(AssetId::new(
+						blockchain.clone(),
+						symbol.clone(),
+					))
+					.unwrap_or(default_threshold);
+					price_filter::has_changed(&stored, coin_info, threshold, T::MaxPriceAge::get())
+				})
+				.collect()
+		}
+
+		/// Sends one request for the given currencies to the batching server and parses the
+		/// response.
+		#[cfg(not(feature = "no-ocw"))]
+		fn fetch_coin_infos(api: &str, body: &[u8]) -> Result<Vec<CoinInfo>, Error<T>> {
+			let deadline = sp_io::offchain::timestamp()
+				.add(Duration::from_millis(Self::ocw_config().http_request_timeout));
+			let auth_header = sp_io::offchain::local_storage_get(
+				offchain::StorageKind::PERSISTENT,
+				BATCHING_AUTH_HEADER_KEY,
+			);
+			let mut request = match Self::batching_api_encoding() {
+				BatchingApiEncoding::Json => offchain::http::Request::post(api, vec![body])
+					.add_header("content-type", "application/json"),
+				BatchingApiEncoding::Scale => offchain::http::Request::post(api, vec![body])
+					.add_header("content-type", SCALE_CONTENT_TYPE)
+					.add_header("accept", SCALE_CONTENT_TYPE),
+			}
+			.deadline(deadline);
+			if let Some(auth_header) = auth_header.as_deref() {
+				match sp_std::str::from_utf8(auth_header) {
+					Ok(auth_header) => request = request.add_header("authorization", auth_header),
+					Err(_) =>
+						log::warn!("Ignoring batching API auth header, it is not valid UTF-8"),
+				}
+			}
+
+			let pending = request.send().map_err(|_| <Error<T>>::HttpRequestSendFailed)?;
+			let response = pending
+				.try_wait(deadline)
+				.map_err(|_| <Error<T>>::HttpTimeout)?
+				.map_err(|e| match e {
+					offchain::http::Error::DeadlineReached => <Error<T>>::HttpTimeout,
+					_ => <Error<T>>::HttpRequestFailed,
+				})?;
+			let signature =
+				Self::decode_response_signature(response.headers().find(RESPONSE_SIGNATURE_HEADER));
+			// Servers not supporting SCALE ignore the `accept` header and answer with JSON
+			let scale_response = response
+				.headers()
+				.find("content-type")
+				.map_or(false, |content_type| content_type.starts_with(SCALE_CONTENT_TYPE));
+			let max_size = T::MaxResponseSize::get() as usize;
+			let mut body_reader = response.body().deadline(deadline);
+			let body = body_reader.by_ref().take(max_size.saturating_add(1)).collect::<Vec<u8>>();
+			if let Some(offchain::http::Error::DeadlineReached) = body_reader.error() {
+				return Err(<Error<T>>::HttpTimeout)
+			}
+			if body.len() > max_size {
+				return Err(<Error<T>>::ResponseTooLarge)
+			}
+
+			if let Some(server_key) = Self::batching_server_key() {
+				let valid = signature.map_or(false, |signature| {
+					sp_io::crypto::sr25519_verify(&signature, &body, &server_key)
+				});
+				if !valid {
+					return Err(<Error<T>>::InvalidResponseSignature)
+				}
+			}
+
+			if scale_response {
+				Self::decode_coin_infos(&body)
+			} else {
+				Self::parse_coin_infos(&body)
+			}
+		}
+
+		pub(crate) fn decode_coin_infos(body: &[u8]) -> Result<Vec<CoinInfo>, Error<T>> {
+			Vec::<CoinInfo>::decode(&mut &body[..]).map_err(|_| <Error<T>>::DeserializeError)
+		}
+
+		#[cfg(not(feature = "no-ocw"))]
+		fn decode_response_signature(header: Option<&str>) -> Option<sr25519::Signature> {
+			let mut signature = [0u8; 64];
+			hex::decode_to_slice(header?.trim_start_matches("0x"), &mut signature).ok()?;
+			Some(sr25519::Signature::from_raw(signature))
+		}
+
+		/// Whether a failed request is worth repeating within the same offchain worker run
+		#[cfg(not(feature = "no-ocw"))]
+		fn is_transient_failure(error: &Error<T>) -> bool {
+			matches!(
+				error,
+				Error::<T>::HttpRequestSendFailed |
+					Error::<T>::HttpRequestFailed |
+					Error::<T>::HttpTimeout |
+					Error::<T>::DeserializeError
+			)
+		}
+
+		/// Time a price update may take at most: every request to every batching API timing out
+		/// on each of its retries, with the backoffs in between, plus `UPDATE_LOCK_MARGIN_MS`
+		#[cfg(not(feature = "no-ocw"))]
+		pub(crate) fn update_lock_expiration() -> Duration {
+			let config = Self::ocw_config();
+			let retries = u64::from(config.max_http_retries);
+			let backoffs = T::HttpRetryBackoff::get().saturating_mul(retries * (retries + 1) / 2);
+			let per_request =
+				config.http_request_timeout.saturating_mul(retries + 1).saturating_add(backoffs);
+			let requests = Self::supported_currencies_request_bodies()
+				.len()
+				.saturating_mul(1 + Self::extra_batching_apis().len()) as u64;
+			Duration::from_millis(
+				per_request.saturating_mul(requests).saturating_add(UPDATE_LOCK_MARGIN_MS),
+			)
+		}
+
+		#[cfg(not(feature = "no-ocw"))]
+		pub(crate) fn fetch_coin_infos_with_retry(
+			api: &str,
+			body: &[u8],
+		) -> Result<Vec<CoinInfo>, Error<T>> {
+			let max_retries = Self::ocw_config().max_http_retries;
+			let mut attempt = 0;
+			loop {
+				match Self::fetch_coin_infos(api, body) {
+					Ok(coin_infos) => {
+						Self::update_diagnostics(|diagnostics| {
+							diagnostics.last_success =
+								Some(sp_io::offchain::timestamp().unix_millis());
+							diagnostics.consecutive_failures = 0;
+						});
+						return Ok(coin_infos)
+					},
+					Err(e)
+						if attempt < max_retries && Self::is_transient_failure(&e) =>
+					{
+						attempt += 1;
+						log::warn!("Fetching prices failed with {:?}, retry {}", e, attempt);
+						let backoff = T::HttpRetryBackoff::get().saturating_mul(attempt.into());
+						sp_io::offchain::sleep_until(
+							sp_io::offchain::timestamp().add(Duration::from_millis(backoff)),
+						);
+					},
+					Err(e) => {
+						Self::record_update_failure(&e, attempt + 1);
+						return Err(e)
+					},
+				}
+			}
+		}
+
+		pub fn diagnostics() -> FeederDiagnostics<T::Hash> {
+			StorageValueRef::persistent(DIAGNOSTICS_KEY)
+				.get()
+				.ok()
+				.flatten()
+				.unwrap_or_default()
+		}
+
+		pub fn last_update_failure() -> Option<UpdateFailure> {
+			Self::diagnostics().last_failure
+		}
+
+		#[cfg(not(feature = "no-ocw"))]
+		fn update_diagnostics(f: impl FnOnce(&mut FeederDiagnostics<T::Hash>)) {
+			let mut diagnostics = Self::diagnostics();
+			f(&mut diagnostics);
+			StorageValueRef::persistent(DIAGNOSTICS_KEY).set(&diagnostics);
+		}
+
+		#[cfg(not(feature = "no-ocw"))]
+		fn record_update_failure(error: &Error<T>, attempts: u32) {
+			let failure = UpdateFailure {
+				timestamp: sp_io::offchain::timestamp().unix_millis(),
+				attempts,
+				error: error.as_str().as_bytes().to_vec(),
+			};
+			Self::update_diagnostics(|diagnostics| {
+				diagnostics.last_failure = Some(failure);
+				diagnostics.consecutive_failures =
+					diagnostics.consecutive_failures.saturating_add(1);
+			});
+		}
+
+		/// Hashes of the batches submitted within `DuplicateBatchWindow` blocks, with their block
+		#[cfg(not(feature = "no-ocw"))]
+		fn recently_submitted_batches() -> Vec<(T::Hash, T::BlockNumber)> {
+			let now = <frame_system::Pallet<T>>::block_number();
+			let window = T::DuplicateBatchWindow::get();
+			let mut batches = StorageValueRef::persistent(SUBMITTED_BATCHES_KEY)
+				.get::<Vec<(T::Hash, T::BlockNumber)>>()
+				.ok()
+				.flatten()
+				.unwrap_or_default();
+			batches.retain(|(_, block)| now.saturating_sub(*block) < window);
+			batches
+		}
+
+		/// Whether the same batch was already submitted within `DuplicateBatchWindow` blocks
+		#[cfg(not(feature = "no-ocw"))]
+		pub(crate) fn is_recently_submitted(hash: T::Hash) -> bool {
+			Self::recently_submitted_batches().iter().any(|(submitted, _)| *submitted == hash)
+		}
+
+		/// Records the hash of a batch that was sent, so that it is not sent again within
+		/// `DuplicateBatchWindow` blocks
+		#[cfg(not(feature = "no-ocw"))]
+		pub(crate) fn remember_submitted_batch(hash: T::Hash) {
+			if T::DuplicateBatchWindow::get().is_zero() {
+				return
+			}
+			let mut batches = Self::recently_submitted_batches();
+			batches.push((hash, <frame_system::Pallet<T>>::block_number()));
+			StorageValueRef::persistent(SUBMITTED_BATCHES_KEY).set(&batches);
+		}
+
+		#[cfg(not(feature = "no-ocw"))]
+		pub(crate) fn submit_coin_infos(
+			prices: Vec<((Vec<u8>, Vec<u8>), CoinInfo)>,
+		) -> Result<(), Error<T>> {
+			let hash = T::Hashing::hash_of(&prices);
+			if Self::is_recently_submitted(hash) {
+				log::debug!("Skipping batch {:?}, it was submitted recently", hash);
+				return Ok(())
+			}
+
+			let submitter = Self::select_submitter().ok_or(<Error<T>>::NoSubmitterAccount)?;
+			let signer = Signer::<T, T::AuthorityId>::any_account()
+				.with_filter(vec![submitter.public.clone()]);
 
 			log::info!("Signers, {:?}", signer.can_sign());
 
@@ -282,38 +1249,394 @@ pub mod pallet {
 				.1
 				.map_err(|_| <Error<T>>::FailedSignedTransaction)?;
 
+			// Only batches that were sent count as submitted, failed ones are retried next run
+			Self::remember_submitted_batch(hash);
+			Self::update_diagnostics(|diagnostics| {
+				diagnostics.last_batch_hash = Some(hash);
+			});
 			Ok(())
 		}
 
+		/// Sends a `feeder_heartbeat` once `HeartbeatInterval` blocks passed since the last sign of
+		/// life of the submitter, so that stable prices are not mistaken for a dead feeder.
+		#[cfg(not(feature = "no-ocw"))]
+		fn send_heartbeat_if_due() -> Result<(), Error<T>> {
+			let submitter = Self::select_submitter().ok_or(<Error<T>>::NoSubmitterAccount)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			let due = Self::last_heartbeat(&submitter.id)
+				.map_or(true, |last| now.saturating_sub(last) >= T::HeartbeatInterval::get());
+			if !due {
+				return Ok(())
+			}
+
+			Signer::<T, T::AuthorityId>::any_account()
+				.with_filter(vec![submitter.public])
+				.send_signed_transaction(|_| Call::<T>::feeder_heartbeat {})
+				.ok_or(<Error<T>>::FailedSignedTransaction)?
+				.1
+				.map_err(|_| <Error<T>>::FailedSignedTransaction)
+		}
+
+		/// Picks the local account to sign with, the one configured in [`FEEDER_ACCOUNT_KEY`] if
+		/// any, otherwise according to [`SubmitterSelectionStrategy`].
+		#[cfg(not(feature = "no-ocw"))]
+		pub(crate) fn select_submitter() -> Option<Account<T>> {
+			let mut accounts: Vec<Account<T>> =
+				Signer::<T, T::AuthorityId>::all_accounts().accounts_from_keys().collect();
+			if accounts.is_empty() {
+				return None
+			}
+
+			let configured = StorageValueRef::persistent(FEEDER_ACCOUNT_KEY)
+				.get::<T::Public>()
+				.ok()
+				.flatten();
+			if let Some(public) = configured {
+				let account = accounts.into_iter().find(|account| account.public == public);
+				if account.is_none() {
+					log::warn!("Configured feeder account {:?} has no local key", public);
+				}
+				return account
+			}
+
+			match Self::submitter_selection() {
+				SubmitterSelection::Any => accounts.into_iter().next(),
+				SubmitterSelection::RoundRobin => {
+					let last_index = StorageValueRef::persistent(ROUND_ROBIN_INDEX_KEY);
+					let index = last_index
+						.get::<u32>()
+						.ok()
+						.flatten()
+						.map_or(0, |i| i.wrapping_add(1) as usize) %
+						accounts.len();
+					last_index.set(&(index as u32));
+					Some(accounts.swap_remove(index))
+				},
+				SubmitterSelection::LowestBalanceLast => accounts
+					.into_iter()
+					.max_by_key(|account| T::SubmitterBalance::balance_of(&account.id)),
+				SubmitterSelection::Pinned(account_id) =>
+					accounts.into_iter().find(|account| account.id == account_id),
+			}
+		}
+
+		/// Parses every element of the response on its own, so that one malformed entry does not
+		/// discard the whole batch. The body is either a plain JSON array of coin infos or a
+		/// `{"version": .., "data": [..]}` envelope. Unknown fields are ignored, so the batching
+		/// server can add fields without breaking older runtimes.
+		pub(crate) fn parse_coin_infos(body: &[u8]) -> Result<Vec<CoinInfo>, Error<T>> {
+			let response = json::parse(body).map_err(|_| <Error<T>>::DeserializeError)?;
+			let entries = match response {
+				json::Value::Array(entries) => entries,
+				mut envelope @ json::Value::Object(_) => {
+					let version = envelope
+						.get("version")
+						.and_then(json::Value::as_u64)
+						.ok_or(<Error<T>>::DeserializeError)?;
+					if version > MAX_RESPONSE_VERSION {
+						log::warn!("Unsupported batching server response version {}", version);
+						return Err(<Error<T>>::DeserializeError)
+					}
+					match envelope.remove("data") {
+						Some(json::Value::Array(entries)) => entries,
+						_ => return Err(<Error<T>>::DeserializeError),
+					}
+				},
+				_ => return Err(<Error<T>>::DeserializeError),
+			};
+
+			let coin_infos: Vec<CoinInfo> = entries.iter().filter_map(json::coin_info).collect();
+
+			let skipped = entries.len() - coin_infos.len();
+			if skipped > 0 {
+				log::warn!("Skipped {} of {} coin infos in response", skipped, entries.len());
+			}
+
+			Ok(coin_infos)
+		}
+
 		fn check_origin_rights(origin_account_id: &T::AccountId) -> DispatchResult {
 			ensure!(
-				<AuthorizedAccounts<T>>::contains_key(origin_account_id),
+				Self::is_authorized(origin_account_id),
 				Error::<T>::ThisAccountIdIsNotAuthorized
 			);
 			Ok(())
 		}
+
+		/// Fails unless the feeders are the pallet's own `AuthorizedAccounts`, which the calls
+		/// changing the feeders write to
+		fn ensure_authorized_accounts_membership() -> DispatchResult {
+			ensure!(
+				T::FeederMembership::is_authorized_accounts(),
+				Error::<T>::ExternalFeederMembership
+			);
+			Ok(())
+		}
+
+		pub fn is_feeder_enabled() -> bool {
+			StorageValueRef::persistent(FEEDER_ENABLED_KEY).get::<bool>().ok().flatten() ==
+				Some(true)
+		}
+
+		pub(crate) fn is_update_block(n: T::BlockNumber) -> bool {
+			let interval = Self::update_interval();
+			interval.is_zero() || (n % interval).is_zero()
+		}
+
+		fn is_valid_api_url(api: &[u8]) -> bool {
+			if api.len() > T::MaxApiUrlLength::get() as usize {
+				return false
+			}
+
+			match sp_std::str::from_utf8(api) {
+				Ok(api) => ["http://", "https://"].iter().any(|scheme| {
+					api.strip_prefix(scheme).map_or(false, |rest| {
+						!rest.is_empty() &&
+							!rest.starts_with('/') && !rest.contains(char::is_whitespace)
+					})
+				}),
+				Err(_) => false,
+			}
+		}
+
+		pub fn is_authorized(account_id: &T::AccountId) -> bool {
+			T::FeederMembership::contains(account_id)
+		}
+
+		/// Returns the accounts currently allowed to feed prices, sorted
+		pub fn list_authorized_accounts() -> Vec<T::AccountId> {
+			T::FeederMembership::sorted_members()
+		}
+
+		/// Looks up the coin infos of several assets at once, in the order they are given
+		pub fn get_coin_infos(
+			assets: Vec<(Vec<u8>, Vec<u8>)>,
+		) -> Vec<Result<CoinInfo, DispatchError>> {
+			assets
+				.into_iter()
+				.map(|(blockchain, symbol)| <Self as DiaOracle>::get_coin_info(blockchain, symbol))
+				.collect()
+		}
+
+		/// Returns the supply of the asset reported with its last price
+		pub fn get_supply(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<u128, DispatchError> {
+			<Self as DiaOracle>::get_coin_info(blockchain, symbol).map(|info| info.supply)
+		}
+
+		/// Returns the `last_update_timestamp` of the asset's price, if it has one
+		pub fn get_last_updated(blockchain: Vec<u8>, symbol: Vec<u8>) -> Option<u64> {
+			<CoinInfosMap<T>>::try_get(&blockchain, &symbol)
+				.ok()
+				.map(|coin_info| coin_info.last_update_timestamp)
+		}
+
+		/// Whether the asset has no price, or one that is older than `MaxPriceAge` by now
+		pub fn is_stale(blockchain: Vec<u8>, symbol: Vec<u8>) -> bool {
+			let now = T::UnixTime::now().as_secs();
+			Self::get_last_updated(blockchain, symbol)
+				.map_or(true, |updated| now.saturating_sub(updated) > T::MaxPriceAge::get())
+		}
+
+		/// Converts `amount` of the first asset into the second at their current prices, rounding
+		/// down. Both amounts have the same number of decimals. Fails with `StalePrice` if either
+		/// price is older than `MaxPriceAge`.
+		pub fn convert(
+			from: (Vec<u8>, Vec<u8>),
+			to: (Vec<u8>, Vec<u8>),
+			amount: u128,
+		) -> Result<u128, DispatchError> {
+			let from_price = Self::fresh_price(from.0, from.1)?;
+			let to_price = Self::fresh_price(to.0, to.1)?;
+			ensure!(to_price != 0, Error::<T>::ZeroPrice);
+			multiply_by_rational_with_rounding(amount, from_price, to_price, Rounding::Down)
+				.ok_or_else(|| ArithmeticError::Overflow.into())
+		}
+
+		/// Price of the asset, unless it is older than `MaxPriceAge`
+		fn fresh_price(blockchain: Vec<u8>, symbol: Vec<u8>) -> Result<u128, DispatchError> {
+			let coin_info = <Self as DiaOracle>::get_coin_info(blockchain, symbol)?;
+			let age = T::UnixTime::now().as_secs().saturating_sub(coin_info.last_update_timestamp);
+			ensure!(age <= T::MaxPriceAge::get(), Error::<T>::StalePrice);
+			Ok(coin_info.price)
+		}
+
+		pub fn price_decimals() -> u8 {
+			T::PriceDecimals::get()
+		}
+
+		/// Returns the price submission statistics of a feeder
+		pub fn get_feeder_stats(account_id: T::AccountId) -> FeederStats<T::BlockNumber> {
+			<FeederStatistics<T>>::get(account_id)
+		}
+
+		/// Returns the feeder that last updated the asset's price and the block it did so in
+		pub fn get_price_submitter(
+			blockchain: Vec<u8>,
+			symbol: Vec<u8>,
+		) -> Option<(T::AccountId, T::BlockNumber)> {
+			<PriceSubmitters<T>>::get(blockchain, symbol)
+		}
+
+		/// Recomputes and stores the derived feeds computed from any of the `updated` assets
+		pub(crate) fn update_derived_feeds(updated: &[((Vec<u8>, Vec<u8>), CoinInfo)]) {
+			for ((blockchain, symbol), feed) in <DerivedFeeds<T>>::get() {
+				let sources = feed.sources();
+				if !updated.iter().any(|(asset, _)| sources.contains(&asset)) {
+					continue
+				}
+				match Self::derive_coin_info(&feed) {
+					Some(coin_info) => {
+						let coin_info = CoinInfo {
+							symbol: symbol.clone(),
+							name: symbol.clone(),
+							blockchain: blockchain.clone(),
+							..coin_info
+						};
+						<CoinInfosMap<T>>::insert(blockchain, symbol, coin_info);
+					},
+					None => log::warn!("Could not derive the price of {:?}", symbol),
+				}
+			}
+		}
+
+		/// Coin info with the price of a derived feed, last updated when its oldest source was.
+		/// `None` if a source has no price, a price of zero or the result overflows.
+		pub(crate) fn derive_coin_info(feed: &DerivedFeed) -> Option<CoinInfo> {
+			let one = 10u128.checked_pow(T::PriceDecimals::get().into())?;
+			let stored = |(blockchain, symbol): &(Vec<u8>, Vec<u8>)| {
+				<CoinInfosMap<T>>::try_get(blockchain, symbol).ok()
+			};
+			let (numerator, denominator, last_update_timestamp) = match feed {
+				DerivedFeed::Inverse(asset) => {
+					let coin_info = stored(asset)?;
+					(one, coin_info.price, coin_info.last_update_timestamp)
+				},
+				DerivedFeed::Cross { base, quote } => {
+					let (base, quote) = (stored(base)?, stored(quote)?);
+					let last_update_timestamp =
+						base.last_update_timestamp.min(quote.last_update_timestamp);
+					(base.price, quote.price, last_update_timestamp)
+				},
+			};
+			if denominator == 0 {
+				return None
+			}
+			let price =
+				multiply_by_rational_with_rounding(numerator, one, denominator, Rounding::Down)?;
+			Some(CoinInfo { price, last_update_timestamp, ..Default::default() })
+		}
+
+		/// Returns the `(blockchain, symbol)` pairs of all supported currencies, sorted
+		pub fn list_supported_currencies() -> Vec<(Vec<u8>, Vec<u8>)> {
+			let mut currencies: Vec<(Vec<u8>, Vec<u8>)> = <SupportedCurrencies<T>>::iter_keys()
+				.map(|asset_id| (asset_id.blockchain, asset_id.symbol))
+				.collect();
+			currencies.sort();
+			currencies
+		}
+
+		/// Returns the coin infos of all assets of one blockchain, sorted by symbol
+		pub fn get_coins_by_blockchain(blockchain: Vec<u8>) -> Vec<CoinInfo> {
+			let mut coin_infos: Vec<(Vec<u8>, CoinInfo)> =
+				<CoinInfosMap<T>>::iter_prefix(blockchain).collect();
+			coin_infos.sort_by(|(a, _), (b, _)| a.cmp(b));
+			coin_infos.into_iter().map(|(_, coin_info)| coin_info).collect()
+		}
+
+		pub fn asset_overview(blockchain: Vec<u8>, symbol: Vec<u8>) -> AssetOverview {
+			let coin_info = <CoinInfosMap<T>>::try_get(&blockchain, &symbol).ok();
+			let asset_id = AssetId { blockchain, symbol };
+			AssetOverview {
+				supported: <SupportedCurrencies<T>>::contains_key(&asset_id),
+				coin_info,
+				last_batch_block: Self::batch_log()
+					.last()
+					.map(|record| record.block.saturated_into()),
+			}
+		}
+
+		fn record_batch(
+			submitter: T::AccountId,
+			coin_infos: &Vec<((Vec<u8>, Vec<u8>), CoinInfo)>,
+		) {
+			let record = BatchRecord {
+				submitter,
+				block: <frame_system::Pallet<T>>::block_number(),
+				entries: coin_infos.len() as u32,
+				payload_hash: T::Hashing::hash_of(coin_infos),
+			};
+
+			<FeederStatistics<T>>::mutate(&record.submitter, |stats| {
+				stats.accepted_batches = stats.accepted_batches.saturating_add(1);
+				stats.accepted_entries = stats.accepted_entries.saturating_add(record.entries);
+				stats.last_submission = Some(record.block);
+			});
+
+			<BatchLog<T>>::mutate(|log| {
+				if !log.is_empty() && log.len() >= T::MaxBatchLogEntries::get() as usize {
+					log.remove(0);
+				}
+				// Only fails with a `MaxBatchLogEntries` of zero, which keeps no log
+				let _ = log.try_push(record);
+			});
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
+		// Calls are pinned to their index, new calls have to take the next free one so that
+		// encoded calls of existing clients and other chains keep dispatching the same call
+
+		/// Adds a currency, reserving `CurrencyDeposit` from a signed caller until it is removed
+		#[pallet::call_index(0)]
 		#[pallet::weight(<T as Config>::WeightInfo::add_currency())]
 		pub fn add_currency(
 			origin: OriginFor<T>,
 			blockchain: Vec<u8>,
 			symbol: Vec<u8>,
 		) -> DispatchResult {
-			let origin_account_id = ensure_signed(origin)?;
-			Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			let depositor = match ensure_signed(origin.clone()) {
+				Ok(origin_account_id) => {
+					Pallet::<T>::check_origin_rights(&origin_account_id)?;
+					Some(origin_account_id)
+				},
+				Err(_) => {
+					ensure_root(origin)?;
+					None
+				},
+			};
 
 			let asset_id = AssetId { blockchain: blockchain.clone(), symbol: symbol.clone() };
-			if !<SupportedCurrencies<T>>::contains_key(&asset_id) {
+			ensure!(!<BlockedCurrencies<T>>::contains_key(&asset_id), Error::<T>::CurrencyBlocked);
+			if <SupportedCurrencies<T>>::contains_key(&asset_id) ||
+				<PendingCurrencies<T>>::contains_key(&asset_id)
+			{
+				return Ok(())
+			}
+
+			if let Some(depositor) = depositor {
+				let deposit = T::CurrencyDeposit::get();
+				T::Currency::reserve(&depositor, deposit)?;
+				<CurrencyDeposits<T>>::insert(&asset_id, (depositor, deposit));
+			}
+
+			let delay = T::CurrencyActivationDelay::get();
+			if delay.is_zero() {
 				Self::deposit_event(Event::<T>::CurrencyAdded(blockchain, symbol));
 				<SupportedCurrencies<T>>::insert(asset_id, ());
+			} else {
+				let activation = <frame_system::Pallet<T>>::block_number().saturating_add(delay);
+				Self::deposit_event(Event::<T>::CurrencyAdditionScheduled(
+					blockchain, symbol, activation,
+				));
+				<PendingActivations<T>>::insert(activation, &asset_id, ());
+				<PendingCurrencies<T>>::insert(asset_id, activation);
 			}
 
 			Ok(())
 		}
 
+		#[pallet::call_index(1)]
 		#[pallet::weight(<T as Config>::WeightInfo::remove_currency())]
 		pub fn remove_currency(
 			origin: OriginFor<T>,
@@ -326,12 +1649,14 @@ pub mod pallet {
 			let asset_id = AssetId { blockchain: blockchain.clone(), symbol: symbol.clone() };
 			if <SupportedCurrencies<T>>::contains_key(&asset_id) {
 				Self::deposit_event(Event::<T>::CurrencyRemoved(blockchain, symbol));
+				Self::refund_currency_deposit(&asset_id);
 				<SupportedCurrencies<T>>::remove(asset_id);
 			}
 
 			Ok(())
 		}
 
+		#[pallet::call_index(2)]
 		#[pallet::weight(<T as Config>::WeightInfo::authorize_account())]
 		pub fn authorize_account(origin: OriginFor<T>, account_id: T::AccountId) -> DispatchResult {
 			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
@@ -339,6 +1664,7 @@ pub mod pallet {
 			} else {
 				ensure_root(origin)?;
 			}
+			Self::ensure_authorized_accounts_membership()?;
 
 			if !<AuthorizedAccounts<T>>::contains_key(&account_id) {
 				Self::deposit_event(Event::<T>::AccountIdAuthorized(account_id.clone()));
@@ -348,6 +1674,7 @@ pub mod pallet {
 			Ok(())
 		}
 
+		#[pallet::call_index(3)]
 		#[pallet::weight(<T as Config>::WeightInfo::deauthorize_account())]
 		pub fn deauthorize_account(
 			origin: OriginFor<T>,
@@ -362,6 +1689,7 @@ pub mod pallet {
 			} else {
 				ensure_root(origin)?;
 			}
+			Self::ensure_authorized_accounts_membership()?;
 
 			if <AuthorizedAccounts<T>>::contains_key(&account_id) {
 				Self::deposit_event(Event::<T>::AccountIdDeauthorized(account_id.clone()));
@@ -371,27 +1699,398 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(<T as Config>::WeightInfo::set_updated_coin_infos())]
+		#[pallet::call_index(4)]
+		#[pallet::weight(
+			<T as Config>::WeightInfo::set_updated_coin_infos(
+				coin_infos.len() as u32,
+				T::MaxDerivedFeeds::get(),
+			)
+			.saturating_add(T::OnPriceUpdate::on_price_update_weight(coin_infos))
+		)]
 		pub fn set_updated_coin_infos(
 			origin: OriginFor<T>,
 			coin_infos: Vec<((Vec<u8>, Vec<u8>), CoinInfo)>,
 		) -> DispatchResult {
 			let origin_account_id = ensure_signed(origin)?;
 			Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			let now = T::UnixTime::now().as_secs();
+			for ((blockchain, symbol), coin_info) in &coin_infos {
+				ensure!(
+					!<BlockedCurrencies<T>>::contains_key(AssetId::new(
+						blockchain.clone(),
+						symbol.clone()
+					)),
+					Error::<T>::CurrencyBlocked
+				);
+				ensure!(Self::is_supported(blockchain, symbol), Error::<T>::CurrencyNotSupported);
+				Self::check_coin_info(coin_info, now)?;
+			}
+			let block_number = <frame_system::Pallet<T>>::block_number();
+			<LastHeartbeat<T>>::insert(&origin_account_id, block_number);
+			Self::deposit_event(Event::<T>::PricesSubmittedBy(origin_account_id.clone()));
+			Self::record_batch(origin_account_id, &coin_infos);
 			Self::deposit_event(Event::<T>::UpdatedPrices(coin_infos.clone()));
-			for ((blockchain, symbol), c) in coin_infos {
-				<CoinInfosMap<T>>::insert(AssetId { blockchain, symbol }, c);
+			T::OnPriceUpdate::on_price_update(&coin_infos);
+			for ((blockchain, symbol), c) in &coin_infos {
+				<CoinInfosMap<T>>::insert(blockchain, symbol, c);
+				<PriceSubmitters<T>>::insert(
+					blockchain,
+					symbol,
+					(origin_account_id.clone(), block_number),
+				);
 			}
+			Self::update_derived_feeds(&coin_infos);
 			Ok(())
 		}
 
+		#[pallet::call_index(5)]
 		#[pallet::weight(<T as Config>::WeightInfo::set_batching_api())]
 		pub fn set_batching_api(origin: OriginFor<T>, api: Vec<u8>) -> DispatchResult {
 			let origin_account_id = ensure_signed(origin)?;
 			Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			ensure!(Self::is_valid_api_url(&api), Error::<T>::InvalidApiUrl);
 			<BatchingApi<T>>::put(api.clone());
 			Self::deposit_event(Event::<T>::BatchingApiRouteSet(api));
 			Ok(())
 		}
+
+		#[pallet::call_index(6)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_submitter_selection())]
+		pub fn set_submitter_selection(
+			origin: OriginFor<T>,
+			selection: SubmitterSelection<T::AccountId>,
+		) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+
+			<SubmitterSelectionStrategy<T>>::put(selection.clone());
+			Self::deposit_event(Event::<T>::SubmitterSelectionSet(selection));
+			Ok(())
+		}
+
+		/// Replaces the calling authorized account with `new_account_id` in one step, so the
+		/// feeder never has zero or two active keys.
+		#[pallet::call_index(7)]
+		#[pallet::weight(<T as Config>::WeightInfo::rotate_feeder_key())]
+		pub fn rotate_feeder_key(
+			origin: OriginFor<T>,
+			new_account_id: T::AccountId,
+		) -> DispatchResult {
+			let origin_account_id = ensure_signed(origin)?;
+			Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			Self::ensure_authorized_accounts_membership()?;
+			ensure!(
+				!<AuthorizedAccounts<T>>::contains_key(&new_account_id),
+				Error::<T>::AccountAlreadyAuthorized
+			);
+
+			<AuthorizedAccounts<T>>::remove(&origin_account_id);
+			<AuthorizedAccounts<T>>::insert(new_account_id.clone(), ());
+			Self::deposit_event(Event::<T>::FeederKeyRotated(origin_account_id, new_account_id));
+			Ok(())
+		}
+
+		/// Replaces all authorized accounts with `accounts` at once, e.g. after a key compromise.
+		/// Fails with `TooManyAuthorizedAccounts` if more than `limit` accounts are authorized.
+		#[pallet::call_index(8)]
+		#[pallet::weight(
+			<T as Config>::WeightInfo::reset_authorized_accounts(accounts.len() as u32, *limit)
+		)]
+		pub fn reset_authorized_accounts(
+			origin: OriginFor<T>,
+			accounts: Vec<T::AccountId>,
+			limit: u32,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::ensure_authorized_accounts_membership()?;
+
+			ensure!(
+				<AuthorizedAccounts<T>>::iter_keys().nth(limit as usize).is_none(),
+				Error::<T>::TooManyAuthorizedAccounts
+			);
+			let removed = <AuthorizedAccounts<T>>::clear(limit, None).unique;
+			for account_id in &accounts {
+				<AuthorizedAccounts<T>>::insert(account_id.clone(), ());
+			}
+
+			let weight = <T as Config>::WeightInfo::reset_authorized_accounts(
+				accounts.len() as u32,
+				removed,
+			);
+			Self::deposit_event(Event::<T>::AuthorizedAccountsReset(accounts));
+			Ok(Some(weight).into())
+		}
+
+		#[pallet::call_index(9)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_update_interval())]
+		pub fn set_update_interval(
+			origin: OriginFor<T>,
+			interval: T::BlockNumber,
+		) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+			ensure!(!interval.is_zero(), Error::<T>::InvalidUpdateInterval);
+
+			<UpdateInterval<T>>::put(interval);
+			Self::deposit_event(Event::<T>::UpdateIntervalSet(interval));
+			Ok(())
+		}
+
+		/// Sets the key the batching server signs its responses with, or removes it to accept
+		/// unsigned responses again.
+		#[pallet::call_index(10)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_batching_server_key())]
+		pub fn set_batching_server_key(
+			origin: OriginFor<T>,
+			key: Option<sr25519::Public>,
+		) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+
+			<BatchingServerKey<T>>::set(key);
+			Self::deposit_event(Event::<T>::BatchingServerKeySet(key));
+			Ok(())
+		}
+
+		/// Records that the calling feeder is alive while it has no price changes to submit.
+		#[pallet::call_index(11)]
+		#[pallet::weight(<T as Config>::WeightInfo::feeder_heartbeat())]
+		pub fn feeder_heartbeat(origin: OriginFor<T>) -> DispatchResult {
+			let origin_account_id = ensure_signed(origin)?;
+			Pallet::<T>::check_origin_rights(&origin_account_id)?;
+
+			<LastHeartbeat<T>>::insert(
+				&origin_account_id,
+				<frame_system::Pallet<T>>::block_number(),
+			);
+			Self::deposit_event(Event::<T>::FeederHeartbeat(origin_account_id));
+			Ok(())
+		}
+
+		/// Sets the batching APIs queried in addition to `BatchingApi`. With more than one API,
+		/// only prices the sources agree on are submitted.
+		#[pallet::call_index(12)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_extra_batching_apis(apis.len() as u32))]
+		pub fn set_extra_batching_apis(origin: OriginFor<T>, apis: Vec<Vec<u8>>) -> DispatchResult {
+			let origin_account_id = ensure_signed(origin)?;
+			Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			ensure!(
+				apis.len() <= T::MaxExtraBatchingApis::get() as usize,
+				Error::<T>::TooManyBatchingApis
+			);
+			ensure!(apis.iter().all(|api| Self::is_valid_api_url(api)), Error::<T>::InvalidApiUrl);
+
+			<ExtraBatchingApis<T>>::put(apis.clone());
+			Self::deposit_event(Event::<T>::ExtraBatchingApisSet(apis));
+			Ok(())
+		}
+
+		/// Sets the assets whose prices are computed by the pallet from the prices of other
+		/// assets. A derived feed can not be computed from another derived feed.
+		#[pallet::call_index(13)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_derived_feeds(feeds.len() as u32))]
+		pub fn set_derived_feeds(
+			origin: OriginFor<T>,
+			feeds: Vec<((Vec<u8>, Vec<u8>), DerivedFeed)>,
+		) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+			ensure!(
+				feeds.len() <= T::MaxDerivedFeeds::get() as usize,
+				Error::<T>::TooManyDerivedFeeds
+			);
+			let is_derived =
+				|source: &(Vec<u8>, Vec<u8>)| feeds.iter().any(|(asset, _)| asset == source);
+			ensure!(
+				feeds.iter().all(|(_, feed)| !feed.sources().into_iter().any(is_derived)),
+				Error::<T>::InvalidDerivedFeed
+			);
+
+			<DerivedFeeds<T>>::put(feeds.clone());
+			Self::deposit_event(Event::<T>::DerivedFeedsSet(feeds));
+			Ok(())
+		}
+
+		#[pallet::call_index(14)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_batching_api_encoding())]
+		pub fn set_batching_api_encoding(
+			origin: OriginFor<T>,
+			encoding: BatchingApiEncoding,
+		) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+
+			<BatchingEncoding<T>>::put(encoding);
+			Self::deposit_event(Event::<T>::BatchingApiEncodingSet(encoding));
+			Ok(())
+		}
+
+		/// Removes the coin infos of up to `limit` assets, e.g. to recover from a poisoned feed.
+		/// Supported currencies are kept. Call again while `PricesCleared` reports `limit`
+		/// removals.
+		#[pallet::call_index(15)]
+		#[pallet::weight(<T as Config>::WeightInfo::clear_all_prices(*limit))]
+		pub fn clear_all_prices(origin: OriginFor<T>, limit: u32) -> DispatchResult {
+			ensure_root(origin)?;
+
+			// Draining makes progress on repeated calls in one block, and removes exactly the
+			// submitters of the removed coin infos
+			let mut removed = 0;
+			for (blockchain, symbol, _) in <CoinInfosMap<T>>::drain().take(limit as usize) {
+				<PriceSubmitters<T>>::remove(blockchain, symbol);
+				removed += 1;
+			}
+			Self::deposit_event(Event::<T>::PricesCleared(removed));
+			Ok(())
+		}
+
+		/// Removes the coin infos of up to `limit` assets of `blockchain`, e.g. after an
+		/// integration is decommissioned. Call again while `BlockchainPurged` reports `limit`
+		/// removals.
+		#[pallet::call_index(16)]
+		#[pallet::weight(<T as Config>::WeightInfo::purge_blockchain(*limit))]
+		pub fn purge_blockchain(
+			origin: OriginFor<T>,
+			blockchain: Vec<u8>,
+			limit: u32,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			let mut removed = 0;
+			for (symbol, _) in <CoinInfosMap<T>>::drain_prefix(&blockchain).take(limit as usize) {
+				<PriceSubmitters<T>>::remove(&blockchain, symbol);
+				removed += 1;
+			}
+			Self::deposit_event(Event::<T>::BlockchainPurged(blockchain, removed));
+			Ok(())
+		}
+
+		/// Cancels the addition of a currency that is not supported yet
+		#[pallet::call_index(17)]
+		#[pallet::weight(<T as Config>::WeightInfo::cancel_currency_addition())]
+		pub fn cancel_currency_addition(
+			origin: OriginFor<T>,
+			blockchain: Vec<u8>,
+			symbol: Vec<u8>,
+		) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+
+			let asset_id = AssetId { blockchain: blockchain.clone(), symbol: symbol.clone() };
+			let activation =
+				<PendingCurrencies<T>>::take(&asset_id).ok_or(Error::<T>::NoPendingCurrency)?;
+			<PendingActivations<T>>::remove(activation, &asset_id);
+			Self::refund_currency_deposit(&asset_id);
+			Self::deposit_event(Event::<T>::CurrencyAdditionCancelled(blockchain, symbol));
+			Ok(())
+		}
+
+		/// Adds a currency to the blocklist, dropping it from the supported and pending
+		/// currencies
+		#[pallet::call_index(18)]
+		#[pallet::weight(<T as Config>::WeightInfo::block_currency())]
+		pub fn block_currency(
+			origin: OriginFor<T>,
+			blockchain: Vec<u8>,
+			symbol: Vec<u8>,
+		) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+
+			let asset_id = AssetId { blockchain: blockchain.clone(), symbol: symbol.clone() };
+			<SupportedCurrencies<T>>::remove(&asset_id);
+			if let Some(activation) = <PendingCurrencies<T>>::take(&asset_id) {
+				<PendingActivations<T>>::remove(activation, &asset_id);
+			}
+			Self::refund_currency_deposit(&asset_id);
+			<BlockedCurrencies<T>>::insert(asset_id, ());
+			Self::deposit_event(Event::<T>::CurrencyBlocked(blockchain, symbol));
+			Ok(())
+		}
+
+		/// Removes a currency from the blocklist, it has to be added again to be supported
+		#[pallet::call_index(19)]
+		#[pallet::weight(<T as Config>::WeightInfo::unblock_currency())]
+		pub fn unblock_currency(
+			origin: OriginFor<T>,
+			blockchain: Vec<u8>,
+			symbol: Vec<u8>,
+		) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+
+			let asset_id = AssetId { blockchain: blockchain.clone(), symbol: symbol.clone() };
+			if <BlockedCurrencies<T>>::take(asset_id).is_some() {
+				Self::deposit_event(Event::<T>::CurrencyUnblocked(blockchain, symbol));
+			}
+			Ok(())
+		}
+
+		/// Overrides the offchain worker settings of the `Config`, or restores them with `None`
+		#[pallet::call_index(20)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_ocw_config())]
+		pub fn set_ocw_config(origin: OriginFor<T>, config: Option<OcwConfig>) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+
+			if let Some(config) = &config {
+				ensure!(
+					config.http_request_timeout > 0 && config.max_currencies_per_request > 0,
+					Error::<T>::InvalidOcwConfig
+				);
+			}
+			<OcwSettings<T>>::set(config.clone());
+			Self::deposit_event(Event::<T>::OcwConfigSet(config));
+			Ok(())
+		}
+
+		/// Sets the minimum relative price change for the offchain worker to resubmit an asset, or
+		/// falls back to the OCW config with `None`
+		#[pallet::call_index(21)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_min_change_threshold())]
+		pub fn set_min_change_threshold(
+			origin: OriginFor<T>,
+			blockchain: Vec<u8>,
+			symbol: Vec<u8>,
+			threshold: Option<Permill>,
+		) -> DispatchResult {
+			if let Ok(origin_account_id) = ensure_signed(origin.clone()) {
+				Pallet::<T>::check_origin_rights(&origin_account_id)?;
+			} else {
+				ensure_root(origin)?;
+			}
+
+			let asset_id = AssetId { blockchain: blockchain.clone(), symbol: symbol.clone() };
+			<MinChangeThresholds<T>>::set(asset_id, threshold);
+			Self::deposit_event(Event::<T>::MinChangeThresholdSet(blockchain, symbol, threshold));
+			Ok(())
+		}
 	}
 }
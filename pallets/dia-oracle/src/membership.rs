@@ -0,0 +1,65 @@
+use crate::{AuthorizedAccounts, Config};
+use frame_support::{
+	sp_std::{marker::PhantomData, vec::Vec},
+	traits::SortedMembers,
+};
+
+/// Feeder set of the pallet. Only the pallet's own [`AuthorizedAccountsMembership`] can be changed
+/// through the pallet's calls, which fail with `ExternalFeederMembership` for any other set.
+pub trait FeederSet<AccountId>: SortedMembers<AccountId> {
+	/// Whether the set is the pallet's `AuthorizedAccounts` map
+	fn is_authorized_accounts() -> bool;
+}
+
+/// Feeder set backed by the pallet's own [`AuthorizedAccounts`] map, managed through
+/// `authorize_account` and `deauthorize_account`. Runtimes that manage feeders through
+/// `pallet-membership` or similar configure that instance as `FeederMembership` instead, wrapped
+/// in [`ExternalMembership`].
+pub struct AuthorizedAccountsMembership<T>(PhantomData<T>);
+
+impl<T: Config> SortedMembers<T::AccountId> for AuthorizedAccountsMembership<T> {
+	fn sorted_members() -> Vec<T::AccountId> {
+		let mut members: Vec<T::AccountId> = <AuthorizedAccounts<T>>::iter_keys().collect();
+		members.sort();
+		members
+	}
+
+	fn contains(who: &T::AccountId) -> bool {
+		<AuthorizedAccounts<T>>::contains_key(who)
+	}
+}
+
+impl<T: Config> FeederSet<T::AccountId> for AuthorizedAccountsMembership<T> {
+	fn is_authorized_accounts() -> bool {
+		true
+	}
+}
+
+/// Feeder set managed outside of the pallet, e.g. `ExternalMembership<FeederCommittee>` for a
+/// `pallet-membership` instance `FeederCommittee`
+pub struct ExternalMembership<M>(PhantomData<M>);
+
+impl<AccountId, M: SortedMembers<AccountId>> SortedMembers<AccountId> for ExternalMembership<M> {
+	fn sorted_members() -> Vec<AccountId> {
+		M::sorted_members()
+	}
+
+	fn contains(who: &AccountId) -> bool {
+		M::contains(who)
+	}
+
+	fn count() -> usize {
+		M::count()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn add(who: &AccountId) {
+		M::add(who)
+	}
+}
+
+impl<AccountId, M: SortedMembers<AccountId>> FeederSet<AccountId> for ExternalMembership<M> {
+	fn is_authorized_accounts() -> bool {
+		false
+	}
+}
@@ -0,0 +1,186 @@
+//! Storage migrations, to be listed in the runtime's `Executive` until every chain ran them
+
+pub mod v1 {
+	use super::v2::OldCoinInfo;
+	use crate::{AssetId, Config, Pallet};
+	use frame_support::{
+		pallet_prelude::*,
+		sp_std::{marker::PhantomData, vec::Vec},
+		traits::OnRuntimeUpgrade,
+	};
+
+	/// `CoinInfosMap` as it was keyed by `AssetId` before version 1
+	#[frame_support::storage_alias]
+	pub(crate) type CoinInfosMap<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, AssetId, OldCoinInfo>;
+
+	/// Moves the coin infos from the map keyed by `AssetId` to the double map keyed by blockchain
+	/// and symbol
+	pub struct MigrateToDoubleMap<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToDoubleMap<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() >= 1 {
+				log::info!("dia-oracle storage is already at version 1, skipping migration");
+				return T::DbWeight::get().reads(1)
+			}
+
+			// Collected first, old and new entries share the storage prefix
+			let coin_infos: Vec<(AssetId, OldCoinInfo)> = CoinInfosMap::<T>::drain().collect();
+			let count = coin_infos.len() as u64;
+			for (AssetId { blockchain, symbol }, coin_info) in coin_infos {
+				super::v2::CoinInfosMap::<T>::insert(blockchain, symbol, coin_info);
+			}
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			log::info!("Migrated {} coin infos to the double map", count);
+			T::DbWeight::get().reads_writes(count + 1, 2 * count + 1)
+		}
+	}
+}
+
+pub mod v2 {
+	use super::v3;
+	use crate::{Config, Pallet};
+	use frame_support::{
+		pallet_prelude::*,
+		sp_std::{marker::PhantomData, vec::Vec},
+		storage::StoragePrefixedMap,
+		traits::OnRuntimeUpgrade,
+	};
+
+	/// `CoinInfo` before the 24h fields were added in version 2
+	#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, Default)]
+	pub struct OldCoinInfo {
+		pub symbol: Vec<u8>,
+		pub name: Vec<u8>,
+		pub blockchain: Vec<u8>,
+		pub supply: u128,
+		pub last_update_timestamp: u64,
+		pub price: u128,
+	}
+
+	impl From<OldCoinInfo> for v3::OldCoinInfo {
+		fn from(old: OldCoinInfo) -> Self {
+			v3::OldCoinInfo {
+				symbol: old.symbol,
+				name: old.name,
+				blockchain: old.blockchain,
+				supply: old.supply,
+				last_update_timestamp: old.last_update_timestamp,
+				price: old.price,
+				price_yesterday: 0,
+				volume_yesterday_usd: 0,
+			}
+		}
+	}
+
+	/// `CoinInfosMap` as it held `OldCoinInfo`s before version 2
+	#[frame_support::storage_alias]
+	pub(crate) type CoinInfosMap<T: Config> = StorageDoubleMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		OldCoinInfo,
+	>;
+
+	/// Adds the 24h fields to every stored coin info, as zero until the next update. Has to run
+	/// after [`super::v1::MigrateToDoubleMap`].
+	pub struct AddDailyFields<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for AddDailyFields<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 1 {
+				log::info!("dia-oracle storage is not at version 1, skipping migration");
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut count = 0u64;
+			v3::CoinInfosMap::<T>::translate_values::<OldCoinInfo, _>(|old| {
+				count += 1;
+				Some(old.into())
+			});
+			StorageVersion::new(2).put::<Pallet<T>>();
+
+			log::info!("Added the 24h fields to {} coin infos", count);
+			T::DbWeight::get().reads_writes(count + 1, count + 1)
+		}
+	}
+}
+
+pub mod v3 {
+	use crate::{CoinInfo, Config, Pallet};
+	use frame_support::{
+		pallet_prelude::*,
+		sp_std::{marker::PhantomData, vec::Vec},
+		storage::StoragePrefixedMap,
+		traits::OnRuntimeUpgrade,
+	};
+
+	/// `CoinInfo` before the market fields were added in version 3
+	#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, Default)]
+	pub struct OldCoinInfo {
+		pub symbol: Vec<u8>,
+		pub name: Vec<u8>,
+		pub blockchain: Vec<u8>,
+		pub supply: u128,
+		pub last_update_timestamp: u64,
+		pub price: u128,
+		pub price_yesterday: u128,
+		pub volume_yesterday_usd: u128,
+	}
+
+	impl From<OldCoinInfo> for CoinInfo {
+		fn from(old: OldCoinInfo) -> Self {
+			CoinInfo {
+				symbol: old.symbol,
+				name: old.name,
+				blockchain: old.blockchain,
+				supply: old.supply,
+				last_update_timestamp: old.last_update_timestamp,
+				price: old.price,
+				price_yesterday: old.price_yesterday,
+				volume_yesterday_usd: old.volume_yesterday_usd,
+				market_cap: None,
+				high_24h: None,
+				low_24h: None,
+			}
+		}
+	}
+
+	/// `CoinInfosMap` as it held `OldCoinInfo`s before version 3
+	#[frame_support::storage_alias]
+	pub(crate) type CoinInfosMap<T: Config> = StorageDoubleMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		OldCoinInfo,
+	>;
+
+	/// Adds the market cap and 24h high and low to every stored coin info, as unknown until the
+	/// next update. Has to run after [`super::v2::AddDailyFields`].
+	pub struct AddMarketFields<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for AddMarketFields<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 2 {
+				log::info!("dia-oracle storage is not at version 2, skipping migration");
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut count = 0u64;
+			crate::CoinInfosMap::<T>::translate_values::<OldCoinInfo, _>(|old| {
+				count += 1;
+				Some(old.into())
+			});
+			StorageVersion::new(3).put::<Pallet<T>>();
+
+			log::info!("Added the market fields to {} coin infos", count);
+			T::DbWeight::get().reads_writes(count + 1, count + 1)
+		}
+	}
+}
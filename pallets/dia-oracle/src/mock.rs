@@ -1,10 +1,14 @@
 use crate as dia_oracle;
-use frame_support::parameter_types;
+use frame_support::{
+	parameter_types,
+	traits::{SortedMembers, UnixTime},
+};
 use frame_system as system;
 use sp_core::{sr25519::Signature, H256};
 use sp_runtime::{
 	testing::{Header, TestXt},
 	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentifyAccount, IdentityLookup, Verify},
+	Permill,
 };
 use sp_std::convert::{TryFrom, TryInto};
 
@@ -19,6 +23,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system,
+		Balances: pallet_balances,
 		DOracle: dia_oracle,
 	}
 );
@@ -26,6 +31,30 @@ frame_support::construct_runtime!(
 parameter_types! {
 	pub const BlockHashCount: u64 = 250;
 	pub const SS58Prefix: u8 = 42;
+	pub const MaxBatchLogEntries: u32 = 2;
+	pub const HttpRequestTimeout: u64 = 2_000;
+	pub const MaxCurrenciesPerRequest: u32 = 2;
+	pub static MaxResponseSize: u32 = 1 << 20;
+	pub const MaxHttpRetries: u32 = 2;
+	pub const HttpRetryBackoff: u64 = 10;
+	pub const MaxApiUrlLength: u32 = 64;
+	pub static AllowZeroPrices: bool = true;
+	pub static Now: u64 = 1_000;
+	pub const MaxPriceAge: u64 = 1_000;
+	pub const MaxFutureDrift: u64 = 5;
+	pub const UpdatePriority: u64 = 1_000;
+	pub const UpdateLongevity: u64 = 5;
+	pub const HeartbeatInterval: u64 = 10;
+	pub const MaxExtraBatchingApis: u32 = 2;
+	pub const MaxDerivedFeeds: u32 = 2;
+	pub static CurrencyActivationDelay: u64 = 0;
+	pub const DuplicateBatchWindow: u64 = 5;
+	pub static CurrencyDeposit: u128 = 0;
+	pub const ExistentialDeposit: u128 = 1;
+	pub const MaxSourceDeviation: Permill = Permill::from_percent(5);
+	pub static PriceDecimals: u8 = 12;
+	pub static ExternalFeeders: bool = false;
+	pub static NotifiedPrices: Vec<((Vec<u8>, Vec<u8>), dia_oracle::CoinInfo)> = Vec::new();
 }
 
 impl system::Config for Test {
@@ -48,13 +77,25 @@ impl system::Config for Test {
 	type PalletInfo = PalletInfo;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u128>;
 	type SystemWeightInfo = ();
 	type SS58Prefix = SS58Prefix;
 	type OnSetCode = ();
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
-type Extrinsic = TestXt<RuntimeCall, ()>;
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u128;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
 pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
 
 impl frame_system::offchain::SigningTypes for Test {
@@ -84,11 +125,73 @@ where
 	}
 }
 
+pub struct MockUnixTime;
+
+impl UnixTime for MockUnixTime {
+	fn now() -> core::time::Duration {
+		core::time::Duration::from_secs(Now::get())
+	}
+}
+
+/// The `AuthorizedAccounts` map, posing as an external feeder set while `ExternalFeeders` is set
+pub struct MockFeederMembership;
+
+impl SortedMembers<AccountId> for MockFeederMembership {
+	fn sorted_members() -> Vec<AccountId> {
+		dia_oracle::AuthorizedAccountsMembership::<Test>::sorted_members()
+	}
+}
+
+impl dia_oracle::FeederSet<AccountId> for MockFeederMembership {
+	fn is_authorized_accounts() -> bool {
+		!ExternalFeeders::get()
+	}
+}
+
+pub struct RecordPriceUpdates;
+
+impl dia_oracle::OnPriceUpdate for RecordPriceUpdates {
+	fn on_price_update(coin_infos: &[((Vec<u8>, Vec<u8>), dia_oracle::CoinInfo)]) {
+		NotifiedPrices::set(coin_infos.to_vec());
+	}
+}
+
 impl dia_oracle::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
+	#[cfg(not(feature = "no-ocw"))]
 	type AuthorityId = super::crypto::DiaAuthId;
 	type RuntimeCall = RuntimeCall;
 	type WeightInfo = ();
+	type MaxBatchLogEntries = MaxBatchLogEntries;
+	type FeederMembership = MockFeederMembership;
+	type SubmitterBalance = ();
+	#[cfg(not(feature = "no-ocw"))]
+	type HttpRequestTimeout = HttpRequestTimeout;
+	#[cfg(not(feature = "no-ocw"))]
+	type MaxCurrenciesPerRequest = MaxCurrenciesPerRequest;
+	#[cfg(not(feature = "no-ocw"))]
+	type MaxResponseSize = MaxResponseSize;
+	#[cfg(not(feature = "no-ocw"))]
+	type MaxHttpRetries = MaxHttpRetries;
+	#[cfg(not(feature = "no-ocw"))]
+	type HttpRetryBackoff = HttpRetryBackoff;
+	type MaxApiUrlLength = MaxApiUrlLength;
+	type AllowZeroPrices = AllowZeroPrices;
+	type UnixTime = MockUnixTime;
+	type MaxPriceAge = MaxPriceAge;
+	type MaxFutureDrift = MaxFutureDrift;
+	type UpdatePriority = UpdatePriority;
+	type UpdateLongevity = UpdateLongevity;
+	type HeartbeatInterval = HeartbeatInterval;
+	type MaxExtraBatchingApis = MaxExtraBatchingApis;
+	type MaxSourceDeviation = MaxSourceDeviation;
+	type PriceDecimals = PriceDecimals;
+	type OnPriceUpdate = RecordPriceUpdates;
+	type MaxDerivedFeeds = MaxDerivedFeeds;
+	type Currency = Balances;
+	type CurrencyDeposit = CurrencyDeposit;
+	type CurrencyActivationDelay = CurrencyActivationDelay;
+	type DuplicateBatchWindow = DuplicateBatchWindow;
 }
 
 // Build genesis storage according to the mock runtime.
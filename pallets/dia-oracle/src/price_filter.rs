@@ -0,0 +1,76 @@
+//! Checks applied to the prices of the batching server before they are submitted, shared by the
+//! offchain worker and external feeders such as `dia-feeder`, so that both submit the same prices.
+use crate::CoinInfo;
+pub use frame_support::sp_runtime::Permill;
+
+/// Number of decimals of the prices sent by the batching server
+pub const BATCHING_SERVER_DECIMALS: u8 = 12;
+
+/// Limits `set_updated_coin_infos` enforces on every coin info, the pallet's `AllowZeroPrices`,
+/// `MaxFutureDrift` and `MaxPriceAge`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceLimits {
+	pub allow_zero_prices: bool,
+	/// Seconds a timestamp may lie in the future
+	pub max_future_drift: u64,
+	/// Seconds a timestamp may lie in the past
+	pub max_price_age: u64,
+}
+
+/// Why [`check_coin_info`] rejected a coin info
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Implausible {
+	ZeroPrice,
+	FutureTimestamp,
+	StalePrice,
+}
+
+/// Converts a price from the batching server's decimals to `decimals`, truncating the digits
+/// that are cut off. `None` if the price does not fit into a `u128` any more.
+pub fn scale_price(price: u128, decimals: u8) -> Option<u128> {
+	if decimals >= BATCHING_SERVER_DECIMALS {
+		10u128
+			.checked_pow((decimals - BATCHING_SERVER_DECIMALS).into())
+			.and_then(|factor| price.checked_mul(factor))
+	} else {
+		Some(
+			10u128
+				.checked_pow((BATCHING_SERVER_DECIMALS - decimals).into())
+				.map_or(0, |divisor| price / divisor),
+		)
+	}
+}
+
+/// Checks the price and the timestamp of a coin info against `now` in seconds.
+pub fn check_coin_info(
+	coin_info: &CoinInfo,
+	now: u64,
+	limits: &PriceLimits,
+) -> Result<(), Implausible> {
+	if !limits.allow_zero_prices && coin_info.price == 0 {
+		return Err(Implausible::ZeroPrice)
+	}
+	if coin_info.last_update_timestamp > now.saturating_add(limits.max_future_drift) {
+		return Err(Implausible::FutureTimestamp)
+	}
+	if now.saturating_sub(coin_info.last_update_timestamp) > limits.max_price_age {
+		return Err(Implausible::StalePrice)
+	}
+	Ok(())
+}
+
+/// Whether `coin_info` is worth submitting over the `stored` one: it differs, and either its
+/// price moved by at least `threshold` or the stored price is older than half of `max_price_age`.
+pub fn has_changed(
+	stored: &CoinInfo,
+	coin_info: &CoinInfo,
+	threshold: Permill,
+	max_price_age: u64,
+) -> bool {
+	if stored == coin_info {
+		return false
+	}
+	let age = coin_info.last_update_timestamp.saturating_sub(stored.last_update_timestamp);
+	age > max_price_age / 2 ||
+		coin_info.price.abs_diff(stored.price) >= threshold.mul_ceil(stored.price)
+}
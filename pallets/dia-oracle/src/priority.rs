@@ -0,0 +1,79 @@
+use crate::{Call, Config, Pallet};
+use codec::{Decode, Encode};
+use frame_support::{
+	sp_runtime::{
+		traits::{DispatchInfoOf, SignedExtension},
+		transaction_validity::{TransactionValidity, TransactionValidityError, ValidTransaction},
+	},
+	sp_std::{fmt, marker::PhantomData},
+	traits::{Get, IsSubType},
+};
+
+/// Gives `set_updated_coin_infos` from authorized accounts the priority and longevity configured
+/// in the pallet, so that price updates are not crowded out of full blocks. Every other call
+/// passes through untouched.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, scale_info::TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct PrioritizeOracleUpdates<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> PrioritizeOracleUpdates<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for PrioritizeOracleUpdates<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for PrioritizeOracleUpdates<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "PrioritizeOracleUpdates")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for PrioritizeOracleUpdates<T>
+where
+	<T as frame_system::Config>::RuntimeCall: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "PrioritizeOracleUpdates";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		match call.is_sub_type() {
+			Some(Call::set_updated_coin_infos { .. })
+				if <Pallet<T>>::is_authorized(who) =>
+				Ok(ValidTransaction {
+					priority: T::UpdatePriority::get(),
+					longevity: T::UpdateLongevity::get(),
+					..Default::default()
+				}),
+			_ => Ok(ValidTransaction::default()),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
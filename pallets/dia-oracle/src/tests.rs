@@ -1,11 +1,42 @@
 use crate::mock::*;
 use crate::*;
 
-use frame_support::assert_err;
-use sp_core::sr25519::Public;
+use codec::Encode;
+use frame_support::{
+	assert_err, assert_ok,
+	traits::{GenesisBuild, SortedMembers},
+};
+use parking_lot::RwLock;
+use sp_core::{
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt},
+	sr25519::Public,
+};
+use sp_runtime::{
+	offchain::storage::StorageValueRef,
+	traits::{BlakeTwo256, Hash, SignedExtension},
+	transaction_validity::ValidTransaction,
+	ArithmeticError, DispatchError,
+};
+use std::sync::Arc;
+// Only the offchain worker tests sign or send HTTP requests
+#[cfg(not(feature = "no-ocw"))]
+use sp_core::{
+	offchain::{testing::TestTransactionPoolExt, TransactionPoolExt},
+	sr25519, Pair,
+};
+#[cfg(not(feature = "no-ocw"))]
+use sp_keystore::{testing::KeyStore, KeystoreExt, SyncCryptoStore};
+#[cfg(not(feature = "no-ocw"))]
+use sp_runtime::offchain::{Duration, StorageKind};
 
 pub const ALICE: Public = Public([1u8; 32]);
 
+fn support_currencies(coin_infos: &[((Vec<u8>, Vec<u8>), CoinInfo)]) {
+	for ((blockchain, symbol), _) in coin_infos {
+		<SupportedCurrencies<Test>>::insert(AssetId::new(blockchain.clone(), symbol.clone()), ());
+	}
+}
+
 #[test]
 fn add_currency_should_work() {
 	new_test_ext().execute_with(|| {
@@ -42,6 +73,95 @@ fn remove_currency_should_work() {
 	})
 }
 
+#[test]
+fn add_currency_should_reserve_a_deposit_until_removed() {
+	use frame_support::traits::{Currency, ReservableCurrency};
+
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		CurrencyDeposit::set(100);
+
+		assert!(DOracle::add_currency(RuntimeOrigin::signed(ALICE), vec![1], vec![1]).is_err());
+
+		Balances::make_free_balance_be(&ALICE, 150);
+		assert_ok!(DOracle::add_currency(RuntimeOrigin::signed(ALICE), vec![1], vec![1]));
+		assert_eq!(Balances::reserved_balance(&ALICE), 100);
+		assert_ok!(DOracle::add_currency(RuntimeOrigin::root(), vec![2], vec![2]));
+		assert_eq!(Balances::reserved_balance(&ALICE), 100);
+
+		assert_ok!(DOracle::remove_currency(RuntimeOrigin::signed(ALICE), vec![1], vec![1]));
+		assert_eq!(Balances::reserved_balance(&ALICE), 0);
+		assert_eq!(Balances::free_balance(&ALICE), 150);
+
+		CurrencyDeposit::set(0);
+	})
+}
+
+#[test]
+fn add_currency_should_wait_for_the_activation_delay() {
+	use frame_support::traits::Hooks;
+
+	new_test_ext().execute_with(|| {
+		let supported =
+			|id| <SupportedCurrencies<Test>>::contains_key(AssetId::new(vec![id], vec![id]));
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		CurrencyActivationDelay::set(3);
+		System::set_block_number(1);
+
+		assert_ok!(DOracle::add_currency(RuntimeOrigin::signed(ALICE), vec![1], vec![1]));
+		assert_ok!(DOracle::add_currency(RuntimeOrigin::signed(ALICE), vec![2], vec![2]));
+		System::assert_last_event(
+			Event::<Test>::CurrencyAdditionScheduled(vec![2], vec![2], 4).into(),
+		);
+		assert!(!supported(1));
+
+		assert_ok!(DOracle::cancel_currency_addition(RuntimeOrigin::root(), vec![2], vec![2]));
+		assert_err!(
+			DOracle::cancel_currency_addition(RuntimeOrigin::root(), vec![2], vec![2]),
+			Error::<Test>::NoPendingCurrency
+		);
+
+		DOracle::on_initialize(3);
+		assert!(!supported(1));
+		DOracle::on_initialize(4);
+		assert!(supported(1));
+		assert!(!supported(2));
+		assert_eq!(<PendingCurrencies<Test>>::iter().count(), 0);
+		assert_eq!(<PendingActivations<Test>>::iter().count(), 0);
+
+		CurrencyActivationDelay::set(0);
+	})
+}
+
+#[test]
+fn blocked_currencies_should_not_be_added_or_updated() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		let asset_id = AssetId::new(vec![1], vec![1]);
+		assert_ok!(DOracle::add_currency(RuntimeOrigin::signed(ALICE), vec![1], vec![1]));
+
+		assert_ok!(DOracle::block_currency(RuntimeOrigin::root(), vec![1], vec![1]));
+		assert!(!<SupportedCurrencies<Test>>::contains_key(&asset_id));
+		assert_err!(
+			DOracle::add_currency(RuntimeOrigin::signed(ALICE), vec![1], vec![1]),
+			Error::<Test>::CurrencyBlocked
+		);
+		let coin_info = CoinInfo { price: 1, ..Default::default() };
+		assert_err!(
+			DOracle::set_updated_coin_infos(
+				RuntimeOrigin::signed(ALICE),
+				vec![((vec![1], vec![1]), coin_info)]
+			),
+			Error::<Test>::CurrencyBlocked
+		);
+
+		assert_ok!(DOracle::unblock_currency(RuntimeOrigin::signed(ALICE), vec![1], vec![1]));
+		assert!(!<SupportedCurrencies<Test>>::contains_key(&asset_id));
+		assert_ok!(DOracle::add_currency(RuntimeOrigin::signed(ALICE), vec![1], vec![1]));
+		assert!(<SupportedCurrencies<Test>>::contains_key(&asset_id));
+	})
+}
+
 #[test]
 fn authorize_account_should_work() {
 	new_test_ext().execute_with(|| {
@@ -124,28 +244,69 @@ fn set_updated_coin_infos_should_work() {
 			supply: 9,
 			last_update_timestamp: 9,
 			price: 9,
+			price_yesterday: 0,
+			volume_yesterday_usd: 0,
+			market_cap: None,
+			high_24h: None,
+			low_24h: None,
 		};
 		let coin_infos = vec![
 			((vec![1, 2, 3], vec![1, 2, 3]), CoinInfo::default()),
 			((vec![2, 2, 2], vec![2, 2, 2]), example_info.clone()),
 		];
+		support_currencies(&coin_infos);
 		let _test1 = DOracle::set_updated_coin_infos(RuntimeOrigin::signed(ALICE), coin_infos);
 
+		assert_eq!(<CoinInfosMap<Test>>::contains_key(vec![1, 2, 3], vec![1, 2, 3]), true);
+		assert_eq!(<CoinInfosMap<Test>>::contains_key(vec![2, 2, 2], vec![2, 2, 2]), true);
+		assert_eq!(<CoinInfosMap<Test>>::get(vec![2, 2, 2], vec![2, 2, 2]), example_info);
+		assert_eq!(<CoinInfosMap<Test>>::get(vec![1, 2, 3], vec![1, 2, 3]), CoinInfo::default());
+	})
+}
+
+#[test]
+fn set_updated_coin_infos_should_record_the_submitter() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(7);
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+
+		let coin_info = CoinInfo { price: 9, last_update_timestamp: 9, ..Default::default() };
+		let coin_infos = vec![((vec![1], vec![1]), coin_info)];
+		support_currencies(&coin_infos);
+		assert_eq!(DOracle::get_price_submitter(vec![1], vec![1]), None);
+
+		assert_ok!(DOracle::set_updated_coin_infos(RuntimeOrigin::signed(ALICE), coin_infos));
+		assert_eq!(DOracle::get_price_submitter(vec![1], vec![1]), Some((ALICE, 7)));
+		assert_eq!(DOracle::get_price_submitter(vec![2], vec![2]), None);
+
+		assert_ok!(DOracle::purge_blockchain(RuntimeOrigin::root(), vec![1], 10));
+		assert_eq!(DOracle::get_price_submitter(vec![1], vec![1]), None);
+	})
+}
+
+#[test]
+fn set_updated_coin_infos_should_count_accepted_submissions() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		let coin_info = CoinInfo { price: 9, last_update_timestamp: 9, ..Default::default() };
+		let coin_infos = vec![
+			((vec![1], vec![1]), coin_info.clone()),
+			((vec![2], vec![2]), coin_info.clone()),
+		];
+		support_currencies(&coin_infos);
+		assert_eq!(DOracle::get_feeder_stats(ALICE), FeederStats::default());
+
+		System::set_block_number(3);
+		assert_ok!(DOracle::set_updated_coin_infos(RuntimeOrigin::signed(ALICE), coin_infos));
+		System::set_block_number(5);
+		assert_ok!(DOracle::set_updated_coin_infos(
+			RuntimeOrigin::signed(ALICE),
+			vec![((vec![1], vec![1]), coin_info)]
+		));
+
 		assert_eq!(
-			<CoinInfosMap<Test>>::contains_key(AssetId::new(vec![1, 2, 3], vec![1, 2, 3])),
-			true
-		);
-		assert_eq!(
-			<CoinInfosMap<Test>>::contains_key(AssetId::new(vec![2, 2, 2], vec![2, 2, 2])),
-			true
-		);
-		assert_eq!(
-			<CoinInfosMap<Test>>::get(AssetId::new(vec![2, 2, 2], vec![2, 2, 2])),
-			example_info
-		);
-		assert_eq!(
-			<CoinInfosMap<Test>>::get(AssetId::new(vec![1, 2, 3], vec![1, 2, 3])),
-			CoinInfo::default()
+			DOracle::get_feeder_stats(ALICE),
+			FeederStats { accepted_batches: 2, accepted_entries: 3, last_submission: Some(5) }
 		);
 	})
 }
@@ -178,12 +339,18 @@ fn get_coin_info_should_work() {
 			supply: 9,
 			last_update_timestamp: 9,
 			price: 9,
+			price_yesterday: 0,
+			volume_yesterday_usd: 0,
+			market_cap: None,
+			high_24h: None,
+			low_24h: None,
 		};
 		let coin_infos = vec![
 			((vec![1, 2, 3], vec![1, 2, 3]), CoinInfo::default()),
 			((vec![2, 2, 2], vec![2, 2, 2]), example_info.clone()),
 		];
 
+		support_currencies(&coin_infos);
 		let _test1 = DOracle::set_updated_coin_infos(
 			RuntimeOrigin::signed(get_account_id(1)),
 			coin_infos.clone(),
@@ -208,12 +375,18 @@ fn get_coin_info_should_return_error() {
 			supply: 9,
 			last_update_timestamp: 9,
 			price: 9,
+			price_yesterday: 0,
+			volume_yesterday_usd: 0,
+			market_cap: None,
+			high_24h: None,
+			low_24h: None,
 		};
 		let coin_infos = vec![
 			((vec![1, 2, 3], vec![1, 2, 3]), CoinInfo::default()),
 			((vec![2, 2, 2], vec![2, 2, 2]), example_info.clone()),
 		];
 
+		support_currencies(&coin_infos);
 		let _test1 = DOracle::set_updated_coin_infos(
 			RuntimeOrigin::signed(get_account_id(1)),
 			coin_infos.clone(),
@@ -237,12 +410,18 @@ fn get_value_in_coin_info_should_work() {
 			supply: 9,
 			last_update_timestamp: 9,
 			price: 9,
+			price_yesterday: 0,
+			volume_yesterday_usd: 0,
+			market_cap: None,
+			high_24h: None,
+			low_24h: None,
 		};
 		let coin_infos = vec![
 			((vec![1, 2, 3], vec![1, 2, 3]), CoinInfo::default()),
 			((vec![2, 2, 2], vec![2, 2, 2]), example_info.clone()),
 		];
 
+		support_currencies(&coin_infos);
 		let _test1 = DOracle::set_updated_coin_infos(
 			RuntimeOrigin::signed(get_account_id(1)),
 			coin_infos.clone(),
@@ -263,6 +442,7 @@ fn get_value_in_coin_info_should_return_error() {
 
 		let coin_infos = vec![((vec![1, 2, 3], vec![1, 2, 3]), CoinInfo::default())];
 
+		support_currencies(&coin_infos);
 		let _test1 = DOracle::set_updated_coin_infos(
 			RuntimeOrigin::signed(get_account_id(1)),
 			coin_infos.clone(),
@@ -273,3 +453,1699 @@ fn get_value_in_coin_info_should_return_error() {
 		assert_err!(fail_coin_info, Error::<Test>::NoCoinInfoAvailable);
 	})
 }
+
+#[test]
+fn set_updated_coin_infos_should_record_batch() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		System::set_block_number(5);
+
+		let coin_infos = vec![
+			((vec![1, 2, 3], vec![1, 2, 3]), CoinInfo::default()),
+			((vec![2, 2, 2], vec![2, 2, 2]), CoinInfo::default()),
+		];
+		support_currencies(&coin_infos);
+		let _test1 =
+			DOracle::set_updated_coin_infos(RuntimeOrigin::signed(ALICE), coin_infos.clone());
+
+		let log = DOracle::batch_log();
+		assert_eq!(log.len(), 1);
+		assert_eq!(log[0].submitter, ALICE);
+		assert_eq!(log[0].block, 5);
+		assert_eq!(log[0].entries, 2);
+		assert_eq!(log[0].payload_hash, BlakeTwo256::hash_of(&coin_infos));
+	})
+}
+
+#[test]
+fn batch_log_should_be_bounded() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+
+		for n in 1..=3 {
+			System::set_block_number(n);
+			let coin_infos = vec![((vec![1], vec![1]), CoinInfo::default()); n as usize];
+			support_currencies(&coin_infos);
+			let _test = DOracle::set_updated_coin_infos(RuntimeOrigin::signed(ALICE), coin_infos);
+		}
+
+		let log = DOracle::batch_log();
+		assert_eq!(log.len(), 2);
+		assert_eq!(log[0].block, 2);
+		assert_eq!(log[1].block, 3);
+		assert_eq!(log[1].entries, 3);
+	})
+}
+
+#[test]
+fn parse_coin_infos_should_skip_malformed_entries() {
+	let body = br#"[
+		{"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":1,"lastUpdateTimestamp":2,"price":3},
+		{"symbol":"ETH","name":"Ether","blockchain":"Ethereum","supply":"oops","lastUpdateTimestamp":2,"price":3},
+		42,
+		{"symbol":"DOT","name":"Polkadot","blockchain":"Polkadot","supply":4,"lastUpdateTimestamp":5,"price":6}
+	]"#;
+
+	let coin_infos = DOracle::parse_coin_infos(body).unwrap();
+
+	assert_eq!(coin_infos.len(), 2);
+	assert_eq!(coin_infos[0].symbol, b"BTC".to_vec());
+	assert_eq!(coin_infos[1].symbol, b"DOT".to_vec());
+	assert_eq!(coin_infos[1].price, 6);
+}
+
+#[test]
+fn parse_coin_infos_should_ignore_unknown_fields() {
+	let body = br#"[
+		{"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":1,"lastUpdateTimestamp":2,"price":3,"priceYesterday":4,"volumeYesterdayUSD":"5"}
+	]"#;
+
+	let coin_infos = DOracle::parse_coin_infos(body).unwrap();
+
+	assert_eq!(coin_infos.len(), 1);
+	assert_eq!(coin_infos[0].price, 3);
+	assert_eq!(coin_infos[0].price_yesterday, 4);
+	assert_eq!(coin_infos[0].volume_yesterday_usd, 0);
+}
+
+#[test]
+fn parse_coin_infos_should_read_24h_fields() {
+	let body = br#"[
+		{"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":1,"lastUpdateTimestamp":2,"price":3,"priceYesterday":4,"volumeYesterdayUsd":5}
+	]"#;
+
+	let coin_infos = DOracle::parse_coin_infos(body).unwrap();
+
+	assert_eq!((coin_infos[0].price_yesterday, coin_infos[0].volume_yesterday_usd), (4, 5));
+}
+
+#[test]
+fn parse_coin_infos_should_read_market_fields() {
+	let body = br#"[
+		{"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":1,"lastUpdateTimestamp":2,"price":3,"marketCap":6,"high24h":7,"low24h":null}
+	]"#;
+
+	let coin_infos = DOracle::parse_coin_infos(body).unwrap();
+
+	assert_eq!(coin_infos[0].market_cap, Some(6));
+	assert_eq!((coin_infos[0].high_24h, coin_infos[0].low_24h), (Some(7), None));
+}
+
+#[test]
+fn parse_coin_infos_should_accept_versioned_envelope() {
+	let body = br#"{"version":1,"generatedAt":7,"data":[
+		{"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":1,"lastUpdateTimestamp":2,"price":3}
+	]}"#;
+
+	let coin_infos = DOracle::parse_coin_infos(body).unwrap();
+
+	assert_eq!(coin_infos.len(), 1);
+	assert_eq!(coin_infos[0].symbol, b"BTC".to_vec());
+}
+
+#[test]
+fn parse_coin_infos_should_fail_on_unsupported_version() {
+	assert_err!(
+		DOracle::parse_coin_infos(br#"{"version":2,"data":[]}"#).map_err(DispatchError::from),
+		Error::<Test>::DeserializeError
+	);
+	assert_err!(
+		DOracle::parse_coin_infos(br#"{"version":1,"data":{}}"#).map_err(DispatchError::from),
+		Error::<Test>::DeserializeError
+	);
+}
+
+#[test]
+fn parse_coin_infos_should_fail_on_non_array() {
+	assert_err!(
+		DOracle::parse_coin_infos(b"{}").map_err(DispatchError::from),
+		Error::<Test>::DeserializeError
+	);
+	assert_err!(
+		DOracle::parse_coin_infos(b"not json").map_err(DispatchError::from),
+		Error::<Test>::DeserializeError
+	);
+}
+
+#[test]
+fn parse_coin_infos_should_unescape_strings() {
+	let body = br#"[
+		{"symbol":"B\"TC","name":"Bit\u00e9coin \ud83d\ude80","blockchain":"a\/b\n","supply":1,"lastUpdateTimestamp":2,"price":3}
+	]"#;
+
+	let coin_infos = DOracle::parse_coin_infos(body).unwrap();
+
+	assert_eq!(coin_infos[0].symbol, b"B\"TC".to_vec());
+	assert_eq!(coin_infos[0].name, "Bit\u{e9}coin \u{1f680}".as_bytes().to_vec());
+	assert_eq!(coin_infos[0].blockchain, b"a/b\n".to_vec());
+}
+
+#[test]
+fn parse_coin_infos_should_skip_nested_unknown_fields() {
+	let body = br#"[
+		{"extra":{"a":[1,-2.5e3,null,true,{"b":"]}"}]},"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":340282366920938463463374607431768211455,"lastUpdateTimestamp":2,"price":3}
+	]"#;
+
+	let coin_infos = DOracle::parse_coin_infos(body).unwrap();
+
+	assert_eq!(coin_infos.len(), 1);
+	assert_eq!(coin_infos[0].supply, u128::MAX);
+}
+
+#[test]
+fn parse_coin_infos_should_skip_entries_with_invalid_numbers() {
+	let body = br#"[
+		{"symbol":"A","name":"A","blockchain":"A","supply":340282366920938463463374607431768211456,"lastUpdateTimestamp":2,"price":3},
+		{"symbol":"B","name":"B","blockchain":"B","supply":1,"lastUpdateTimestamp":18446744073709551616,"price":3},
+		{"symbol":"C","name":"C","blockchain":"C","supply":1,"lastUpdateTimestamp":2,"price":3.5},
+		{"symbol":"D","name":"D","blockchain":"D","supply":1,"lastUpdateTimestamp":2,"price":-3}
+	]"#;
+
+	assert!(DOracle::parse_coin_infos(body).unwrap().is_empty());
+}
+
+#[test]
+fn parse_coin_infos_should_fail_on_invalid_json() {
+	let nested = [&[b'['; 200][..], &[b']'; 200][..]].concat();
+	for body in [
+		&br#"[{"symbol":"BTC"}"#[..],
+		br#"[1,]"#,
+		br#"[01]"#,
+		br#"["\ud83d"]"#,
+		b"[\"\x01\"]",
+		b"[\"\xff\"]",
+		br#"[] []"#,
+		&nested[..],
+	] {
+		assert_err!(
+			DOracle::parse_coin_infos(body).map_err(DispatchError::from),
+			Error::<Test>::DeserializeError
+		);
+	}
+}
+
+#[test]
+fn set_submitter_selection_should_work() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+
+		assert_eq!(DOracle::submitter_selection(), SubmitterSelection::Any);
+
+		let _test1 = DOracle::set_submitter_selection(
+			RuntimeOrigin::signed(ALICE),
+			SubmitterSelection::RoundRobin,
+		);
+		assert_eq!(DOracle::submitter_selection(), SubmitterSelection::RoundRobin);
+
+		let _test2 = DOracle::set_submitter_selection(
+			RuntimeOrigin::root(),
+			SubmitterSelection::Pinned(get_account_id(2)),
+		);
+		assert_eq!(DOracle::submitter_selection(), SubmitterSelection::Pinned(get_account_id(2)));
+	})
+}
+
+#[test]
+fn set_submitter_selection_should_fail_for_unauthorized_account() {
+	new_test_ext().execute_with(|| {
+		assert_err!(
+			DOracle::set_submitter_selection(
+				RuntimeOrigin::signed(get_account_id(2)),
+				SubmitterSelection::RoundRobin,
+			),
+			Error::<Test>::ThisAccountIdIsNotAuthorized
+		);
+		assert_eq!(DOracle::submitter_selection(), SubmitterSelection::Any);
+	})
+}
+
+#[test]
+fn set_updated_coin_infos_should_emit_submitter() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		System::set_block_number(1);
+
+		let _test1 = DOracle::set_updated_coin_infos(RuntimeOrigin::signed(ALICE), vec![]);
+
+		System::assert_has_event(Event::<Test>::PricesSubmittedBy(ALICE).into());
+	})
+}
+
+#[test]
+fn prioritize_oracle_updates_should_boost_authorized_updates() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+
+		let update = RuntimeCall::DOracle(Call::set_updated_coin_infos { coin_infos: vec![] });
+		let other = RuntimeCall::DOracle(Call::set_batching_api { api: vec![] });
+		let extension = PrioritizeOracleUpdates::<Test>::new();
+
+		let boosted = extension.validate(&ALICE, &update, &Default::default(), 0).unwrap();
+		assert_eq!(boosted.priority, 1_000);
+		assert_eq!(boosted.longevity, 5);
+
+		let untouched = extension.validate(&ALICE, &other, &Default::default(), 0).unwrap();
+		assert_eq!(untouched, ValidTransaction::default());
+
+		let unauthorized =
+			extension.validate(&get_account_id(2), &update, &Default::default(), 0).unwrap();
+		assert_eq!(unauthorized, ValidTransaction::default());
+	})
+}
+
+#[test]
+fn authorized_accounts_membership_should_follow_authorized_accounts() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(get_account_id(3), ());
+		<AuthorizedAccounts<Test>>::insert(get_account_id(1), ());
+		<AuthorizedAccounts<Test>>::insert(get_account_id(2), ());
+
+		let _test1 =
+			DOracle::deauthorize_account(RuntimeOrigin::signed(get_account_id(1)), get_account_id(2));
+
+		assert_eq!(
+			AuthorizedAccountsMembership::<Test>::sorted_members(),
+			vec![get_account_id(1), get_account_id(3)]
+		);
+		assert!(DOracle::is_authorized(&get_account_id(3)));
+		assert!(!DOracle::is_authorized(&get_account_id(2)));
+	})
+}
+
+#[test]
+fn feeder_changes_should_fail_with_external_membership() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(get_account_id(1), ());
+		ExternalFeeders::set(true);
+
+		assert_err!(
+			DOracle::authorize_account(RuntimeOrigin::root(), get_account_id(2)),
+			Error::<Test>::ExternalFeederMembership
+		);
+		assert_err!(
+			DOracle::deauthorize_account(RuntimeOrigin::root(), get_account_id(1)),
+			Error::<Test>::ExternalFeederMembership
+		);
+		assert_eq!(
+			DOracle::reset_authorized_accounts(RuntimeOrigin::root(), vec![], 10)
+				.unwrap_err()
+				.error,
+			Error::<Test>::ExternalFeederMembership.into()
+		);
+		assert_err!(
+			DOracle::rotate_feeder_key(RuntimeOrigin::signed(get_account_id(1)), get_account_id(2)),
+			Error::<Test>::ExternalFeederMembership
+		);
+		assert!(DOracle::is_authorized(&get_account_id(1)));
+		assert!(!DOracle::is_authorized(&get_account_id(2)));
+
+		ExternalFeeders::set(false);
+	})
+}
+
+#[test]
+fn set_updated_coin_infos_should_reject_zero_prices_when_not_allowed() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		AllowZeroPrices::set(false);
+
+		let priced = CoinInfo { price: 9, ..Default::default() };
+		let coin_infos = vec![
+			((vec![1, 2, 3], vec![1, 2, 3]), priced.clone()),
+			((vec![2, 2, 2], vec![2, 2, 2]), CoinInfo::default()),
+		];
+		support_currencies(&coin_infos);
+
+		assert_err!(
+			DOracle::set_updated_coin_infos(RuntimeOrigin::signed(ALICE), coin_infos),
+			Error::<Test>::ZeroPrice
+		);
+		assert_eq!(<CoinInfosMap<Test>>::contains_key(vec![1, 2, 3], vec![1, 2, 3]), false);
+
+		let _test1 = DOracle::set_updated_coin_infos(
+			RuntimeOrigin::signed(ALICE),
+			vec![((vec![1, 2, 3], vec![1, 2, 3]), priced.clone())],
+		);
+		assert_eq!(<CoinInfosMap<Test>>::get(vec![1, 2, 3], vec![1, 2, 3]), priced);
+
+		AllowZeroPrices::set(true);
+	})
+}
+
+#[test]
+fn set_updated_coin_infos_should_reject_future_and_stale_timestamps() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		Now::set(5_000);
+		<SupportedCurrencies<Test>>::insert(AssetId::new(vec![1], vec![1]), ());
+
+		let future = CoinInfo { price: 9, last_update_timestamp: 5_006, ..Default::default() };
+		assert_err!(
+			DOracle::set_updated_coin_infos(
+				RuntimeOrigin::signed(ALICE),
+				vec![((vec![1], vec![1]), future)]
+			),
+			Error::<Test>::FutureTimestamp
+		);
+
+		let stale = CoinInfo { price: 9, last_update_timestamp: 3_999, ..Default::default() };
+		assert_err!(
+			DOracle::set_updated_coin_infos(
+				RuntimeOrigin::signed(ALICE),
+				vec![((vec![1], vec![1]), stale)]
+			),
+			Error::<Test>::StalePrice
+		);
+
+		let fresh = CoinInfo { price: 9, last_update_timestamp: 4_000, ..Default::default() };
+		assert_ok!(DOracle::set_updated_coin_infos(
+			RuntimeOrigin::signed(ALICE),
+			vec![((vec![1], vec![1]), fresh)]
+		));
+
+		let ahead = CoinInfo { price: 9, last_update_timestamp: 5_005, ..Default::default() };
+		assert_ok!(DOracle::set_updated_coin_infos(
+			RuntimeOrigin::signed(ALICE),
+			vec![((vec![1], vec![1]), ahead)]
+		));
+
+		Now::set(1_000);
+	})
+}
+
+#[test]
+fn set_updated_coin_infos_should_reject_unsupported_currencies() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		<SupportedCurrencies<Test>>::insert(AssetId::new(vec![1], vec![1]), ());
+
+		let coin_infos = vec![
+			((vec![1], vec![1]), CoinInfo::default()),
+			((vec![2], vec![2]), CoinInfo::default()),
+		];
+		assert_err!(
+			DOracle::set_updated_coin_infos(RuntimeOrigin::signed(ALICE), coin_infos),
+			Error::<Test>::CurrencyNotSupported
+		);
+		assert_eq!(<CoinInfosMap<Test>>::contains_key(vec![1], vec![1]), false);
+	})
+}
+
+#[test]
+fn set_updated_coin_infos_should_notify_on_price_update() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		let coin_infos = vec![((vec![1], vec![1]), CoinInfo { price: 9, ..Default::default() })];
+		support_currencies(&coin_infos);
+
+		assert_ok!(DOracle::set_updated_coin_infos(
+			RuntimeOrigin::signed(ALICE),
+			coin_infos.clone()
+		));
+
+		assert_eq!(NotifiedPrices::get(), coin_infos);
+	})
+}
+
+#[test]
+fn set_updated_coin_infos_should_have_the_documented_call_index() {
+	let coin_infos = vec![(
+		(b"Bitcoin".to_vec(), b"BTC".to_vec()),
+		CoinInfo { symbol: b"BTC".to_vec(), price: 1, ..Default::default() },
+	)];
+	let call = Call::<Test>::set_updated_coin_infos { coin_infos: coin_infos.clone() };
+
+	assert_eq!(call.encode(), (SET_UPDATED_COIN_INFOS_CALL_INDEX, coin_infos).encode());
+}
+
+#[test]
+fn calls_should_keep_their_index_when_calls_are_added() {
+	let index = |call: Call<Test>| call.encode()[0];
+
+	assert_eq!(index(Call::authorize_account { account_id: ALICE }), 2);
+	assert_eq!(index(Call::set_batching_api { api: vec![] }), 5);
+	assert_eq!(index(Call::purge_blockchain { blockchain: vec![], limit: 0 }), 16);
+	assert_eq!(
+		index(Call::cancel_currency_addition { blockchain: vec![], symbol: vec![] }),
+		17
+	);
+	assert_eq!(index(Call::unblock_currency { blockchain: vec![], symbol: vec![] }), 19);
+}
+
+#[test]
+fn supported_coin_infos_should_drop_unsupported_assets() {
+	new_test_ext().execute_with(|| {
+		<SupportedCurrencies<Test>>::insert(AssetId::new(vec![1], vec![1]), ());
+
+		let coin_infos = DOracle::supported_coin_infos(vec![
+			((vec![1], vec![1]), CoinInfo::default()),
+			((vec![2], vec![2]), CoinInfo::default()),
+		]);
+
+		assert_eq!(coin_infos, vec![((vec![1], vec![1]), CoinInfo::default())]);
+	})
+}
+
+#[test]
+fn plausible_coin_infos_should_drop_implausible_entries() {
+	new_test_ext().execute_with(|| {
+		AllowZeroPrices::set(false);
+		let coin_info = |symbol: &[u8], price, last_update_timestamp| CoinInfo {
+			symbol: symbol.to_vec(),
+			price,
+			last_update_timestamp,
+			..Default::default()
+		};
+
+		let coin_infos = DOracle::plausible_coin_infos(
+			vec![
+				coin_info(b"OK", 1, 2_000),
+				coin_info(b"ZERO", 0, 2_000),
+				coin_info(b"FUTURE", 1, 2_001),
+				coin_info(b"STALE", 1, 999),
+			],
+			2_000,
+		);
+
+		assert_eq!(coin_infos, vec![coin_info(b"OK", 1, 2_000)]);
+		AllowZeroPrices::set(true);
+	})
+}
+
+#[test]
+fn rotate_feeder_key_should_work() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(get_account_id(1), ());
+
+		let _test1 =
+			DOracle::rotate_feeder_key(RuntimeOrigin::signed(get_account_id(1)), get_account_id(2));
+
+		assert_eq!(<AuthorizedAccounts<Test>>::contains_key(get_account_id(1)), false);
+		assert_eq!(<AuthorizedAccounts<Test>>::contains_key(get_account_id(2)), true);
+	})
+}
+
+#[test]
+fn rotate_feeder_key_should_not_replace_with_authorized_account() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(get_account_id(1), ());
+		<AuthorizedAccounts<Test>>::insert(get_account_id(2), ());
+
+		assert_err!(
+			DOracle::rotate_feeder_key(RuntimeOrigin::signed(get_account_id(1)), get_account_id(2)),
+			Error::<Test>::AccountAlreadyAuthorized
+		);
+		assert_err!(
+			DOracle::rotate_feeder_key(RuntimeOrigin::signed(get_account_id(3)), get_account_id(4)),
+			Error::<Test>::ThisAccountIdIsNotAuthorized
+		);
+		assert_eq!(<AuthorizedAccounts<Test>>::contains_key(get_account_id(1)), true);
+		assert_eq!(<AuthorizedAccounts<Test>>::contains_key(get_account_id(4)), false);
+	})
+}
+
+#[test]
+fn asset_overview_should_combine_support_price_and_activity() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		System::set_block_number(3);
+
+		let _test1 = DOracle::add_currency(RuntimeOrigin::signed(ALICE), vec![1], vec![1]);
+		let priced = CoinInfo { price: 9, ..Default::default() };
+		let _test2 = DOracle::set_updated_coin_infos(
+			RuntimeOrigin::signed(ALICE),
+			vec![((vec![1], vec![1]), priced.clone())],
+		);
+
+		assert_eq!(
+			DOracle::asset_overview(vec![1], vec![1]),
+			AssetOverview { supported: true, coin_info: Some(priced), last_batch_block: Some(3) }
+		);
+		assert_eq!(
+			DOracle::asset_overview(vec![2], vec![2]),
+			AssetOverview { supported: false, coin_info: None, last_batch_block: Some(3) }
+		);
+	})
+}
+
+#[test]
+fn get_coin_infos_should_keep_the_requested_order() {
+	new_test_ext().execute_with(|| {
+		let priced = CoinInfo { price: 9, ..Default::default() };
+		<CoinInfosMap<Test>>::insert(vec![1], vec![1], priced.clone());
+
+		assert_eq!(
+			DOracle::get_coin_infos(vec![(vec![2], vec![2]), (vec![1], vec![1])]),
+			vec![Err(Error::<Test>::NoCoinInfoAvailable.into()), Ok(priced)]
+		);
+	})
+}
+
+#[test]
+fn list_supported_currencies_should_return_sorted_pairs() {
+	new_test_ext().execute_with(|| {
+		let dot = (b"Polkadot".to_vec(), b"DOT".to_vec());
+		let btc = (b"Bitcoin".to_vec(), b"BTC".to_vec());
+		<SupportedCurrencies<Test>>::insert(AssetId::new(dot.0.clone(), dot.1.clone()), ());
+		<SupportedCurrencies<Test>>::insert(AssetId::new(btc.0.clone(), btc.1.clone()), ());
+
+		assert_eq!(DOracle::list_supported_currencies(), vec![btc, dot]);
+	})
+}
+
+#[test]
+fn list_authorized_accounts_should_follow_membership() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(get_account_id(2), ());
+		<AuthorizedAccounts<Test>>::insert(get_account_id(1), ());
+
+		let mut expected = vec![get_account_id(1), get_account_id(2)];
+		expected.sort();
+		assert_eq!(DOracle::list_authorized_accounts(), expected);
+	})
+}
+
+#[test]
+fn get_supply_should_return_the_coin_info_supply() {
+	new_test_ext().execute_with(|| {
+		let coin_info = CoinInfo { supply: 21_000_000, ..Default::default() };
+		<CoinInfosMap<Test>>::insert(vec![1], vec![1], coin_info);
+
+		assert_eq!(DOracle::get_supply(vec![1], vec![1]), Ok(21_000_000));
+		assert_err!(DOracle::get_supply(vec![2], vec![2]), Error::<Test>::NoCoinInfoAvailable);
+	})
+}
+
+#[test]
+fn is_stale_should_follow_max_price_age() {
+	new_test_ext().execute_with(|| {
+		Now::set(5_000);
+		let coin_info = CoinInfo { last_update_timestamp: 4_000, ..Default::default() };
+		<CoinInfosMap<Test>>::insert(vec![1], vec![1], coin_info);
+
+		assert_eq!(DOracle::get_last_updated(vec![1], vec![1]), Some(4_000));
+		assert!(!DOracle::is_stale(vec![1], vec![1]));
+		assert_eq!(DOracle::get_last_updated(vec![2], vec![2]), None);
+		assert!(DOracle::is_stale(vec![2], vec![2]));
+
+		Now::set(5_001);
+		assert!(DOracle::is_stale(vec![1], vec![1]));
+
+		Now::set(1_000);
+	})
+}
+
+#[test]
+fn convert_should_use_the_price_ratio() {
+	new_test_ext().execute_with(|| {
+		let btc = (b"Bitcoin".to_vec(), b"BTC".to_vec());
+		let dot = (b"Polkadot".to_vec(), b"DOT".to_vec());
+		let free = (b"Free".to_vec(), b"FREE".to_vec());
+		let price = |price| CoinInfo { price, ..Default::default() };
+		<CoinInfosMap<Test>>::insert(btc.0.clone(), btc.1.clone(), price(20_000));
+		<CoinInfosMap<Test>>::insert(dot.0.clone(), dot.1.clone(), price(6));
+		<CoinInfosMap<Test>>::insert(free.0.clone(), free.1.clone(), price(0));
+
+		assert_eq!(DOracle::convert(btc.clone(), dot.clone(), 3), Ok(10_000));
+		assert_eq!(DOracle::convert(dot.clone(), btc.clone(), 10_000), Ok(3));
+		assert_eq!(DOracle::convert(dot.clone(), btc.clone(), 1), Ok(0));
+		assert_err!(
+			DOracle::convert(btc.clone(), dot.clone(), u128::MAX),
+			ArithmeticError::Overflow
+		);
+		assert_err!(DOracle::convert(btc.clone(), free, 1), Error::<Test>::ZeroPrice);
+		assert_err!(
+			DOracle::convert(btc.clone(), (vec![1], vec![1]), 1),
+			Error::<Test>::NoCoinInfoAvailable
+		);
+
+		Now::set(1_001);
+		assert_err!(DOracle::convert(btc, dot, 3), Error::<Test>::StalePrice);
+		Now::set(1_000);
+	})
+}
+
+#[test]
+fn get_coins_by_blockchain_should_only_return_that_blockchain() {
+	new_test_ext().execute_with(|| {
+		let coin_info = |symbol: &[u8]| CoinInfo { symbol: symbol.to_vec(), ..Default::default() };
+		<CoinInfosMap<Test>>::insert(b"Stellar".to_vec(), b"XLM".to_vec(), coin_info(b"XLM"));
+		<CoinInfosMap<Test>>::insert(b"Stellar".to_vec(), b"USDC".to_vec(), coin_info(b"USDC"));
+		<CoinInfosMap<Test>>::insert(b"Bitcoin".to_vec(), b"BTC".to_vec(), coin_info(b"BTC"));
+
+		assert_eq!(
+			DOracle::get_coins_by_blockchain(b"Stellar".to_vec()),
+			vec![coin_info(b"USDC"), coin_info(b"XLM")]
+		);
+		assert!(DOracle::get_coins_by_blockchain(b"Polkadot".to_vec()).is_empty());
+	})
+}
+
+#[test]
+fn clear_all_prices_should_remove_coin_infos_up_to_limit() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let coin_info = |symbol: &[u8]| CoinInfo { symbol: symbol.to_vec(), ..Default::default() };
+		<CoinInfosMap<Test>>::insert(b"Stellar".to_vec(), b"XLM".to_vec(), coin_info(b"XLM"));
+		<CoinInfosMap<Test>>::insert(b"Bitcoin".to_vec(), b"BTC".to_vec(), coin_info(b"BTC"));
+		<PriceSubmitters<Test>>::insert(b"Stellar".to_vec(), b"XLM".to_vec(), (ALICE, 1));
+		<PriceSubmitters<Test>>::insert(b"Bitcoin".to_vec(), b"BTC".to_vec(), (ALICE, 1));
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+
+		assert_err!(
+			DOracle::clear_all_prices(RuntimeOrigin::signed(ALICE), 10),
+			sp_runtime::traits::BadOrigin
+		);
+
+		assert_ok!(DOracle::clear_all_prices(RuntimeOrigin::root(), 1));
+		assert_eq!(<CoinInfosMap<Test>>::iter().count(), 1);
+		// Only the submitter of the removed coin info goes with it
+		let (blockchain, symbol, _) = <CoinInfosMap<Test>>::iter().next().unwrap();
+		let submitters = <PriceSubmitters<Test>>::iter_keys().collect::<Vec<_>>();
+		assert_eq!(submitters, vec![(blockchain, symbol)]);
+		System::assert_last_event(Event::<Test>::PricesCleared(1).into());
+
+		assert_ok!(DOracle::clear_all_prices(RuntimeOrigin::root(), 10));
+		assert_eq!(<CoinInfosMap<Test>>::iter().count(), 0);
+		assert_eq!(<PriceSubmitters<Test>>::iter().count(), 0);
+		System::assert_last_event(Event::<Test>::PricesCleared(1).into());
+	})
+}
+
+#[test]
+fn purge_blockchain_should_only_remove_that_blockchain() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let coin_info = |symbol: &[u8]| CoinInfo { symbol: symbol.to_vec(), ..Default::default() };
+		<CoinInfosMap<Test>>::insert(b"Stellar".to_vec(), b"XLM".to_vec(), coin_info(b"XLM"));
+		<CoinInfosMap<Test>>::insert(b"Stellar".to_vec(), b"USDC".to_vec(), coin_info(b"USDC"));
+		<CoinInfosMap<Test>>::insert(b"Bitcoin".to_vec(), b"BTC".to_vec(), coin_info(b"BTC"));
+		<PriceSubmitters<Test>>::insert(b"Stellar".to_vec(), b"XLM".to_vec(), (ALICE, 1));
+		<PriceSubmitters<Test>>::insert(b"Bitcoin".to_vec(), b"BTC".to_vec(), (ALICE, 1));
+
+		assert_ok!(DOracle::purge_blockchain(RuntimeOrigin::root(), b"Stellar".to_vec(), 10));
+
+		assert!(DOracle::get_coins_by_blockchain(b"Stellar".to_vec()).is_empty());
+		assert_eq!(DOracle::get_coins_by_blockchain(b"Bitcoin".to_vec()), vec![coin_info(b"BTC")]);
+		assert_eq!(DOracle::price_submitters(b"Stellar".to_vec(), b"XLM".to_vec()), None);
+		let submitter = DOracle::price_submitters(b"Bitcoin".to_vec(), b"BTC".to_vec());
+		assert_eq!(submitter, Some((ALICE, 1)));
+		System::assert_last_event(Event::<Test>::BlockchainPurged(b"Stellar".to_vec(), 2).into());
+	})
+}
+
+#[test]
+fn get_value_with_metadata_should_flag_stale_prices() {
+	new_test_ext().execute_with(|| {
+		let coin_info =
+			CoinInfo { price: 9, supply: 21, last_update_timestamp: 500, ..Default::default() };
+		<CoinInfosMap<Test>>::insert(b"Bitcoin".to_vec(), b"BTC".to_vec(), coin_info);
+		let metadata = || DOracle::get_value_with_metadata(b"Bitcoin".to_vec(), b"BTC".to_vec());
+
+		assert_eq!(
+			metadata(),
+			Ok(PriceWithMetadata {
+				value: 9,
+				last_update_timestamp: 500,
+				supply: 21,
+				is_stale: false,
+			})
+		);
+
+		Now::set(1_501);
+		assert_eq!(metadata().map(|metadata| metadata.is_stale), Ok(true));
+		assert_eq!(
+			DOracle::get_value_with_metadata(b"Bitcoin".to_vec(), b"ETH".to_vec()),
+			Err(Error::<Test>::NoCoinInfoAvailable.into())
+		);
+		Now::set(1_000);
+	})
+}
+
+#[test]
+fn migration_to_double_map_should_move_coin_infos() {
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<DOracle>();
+		let coin_info = migrations::v2::OldCoinInfo { price: 20_000, ..Default::default() };
+		migrations::v1::CoinInfosMap::<Test>::insert(
+			AssetId::new(b"Bitcoin".to_vec(), b"BTC".to_vec()),
+			coin_info.clone(),
+		);
+
+		migrations::v1::MigrateToDoubleMap::<Test>::on_runtime_upgrade();
+
+		assert_eq!(DOracle::on_chain_storage_version(), StorageVersion::new(1));
+		assert_eq!(migrations::v2::CoinInfosMap::<Test>::iter().count(), 1);
+		assert_eq!(
+			migrations::v2::CoinInfosMap::<Test>::get(b"Bitcoin".to_vec(), b"BTC".to_vec()),
+			Some(coin_info)
+		);
+	})
+}
+
+#[test]
+fn migration_adding_daily_fields_should_keep_coin_infos() {
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(1).put::<DOracle>();
+		let coin_info = migrations::v2::OldCoinInfo { price: 20_000, ..Default::default() };
+		migrations::v2::CoinInfosMap::<Test>::insert(
+			b"Bitcoin".to_vec(),
+			b"BTC".to_vec(),
+			coin_info.clone(),
+		);
+
+		migrations::v2::AddDailyFields::<Test>::on_runtime_upgrade();
+
+		assert_eq!(DOracle::on_chain_storage_version(), StorageVersion::new(2));
+		assert_eq!(
+			migrations::v3::CoinInfosMap::<Test>::get(b"Bitcoin".to_vec(), b"BTC".to_vec()),
+			Some(migrations::v3::OldCoinInfo { price: 20_000, ..Default::default() })
+		);
+	})
+}
+
+#[test]
+fn migration_adding_market_fields_should_keep_coin_infos() {
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(2).put::<DOracle>();
+		let coin_info = migrations::v3::OldCoinInfo {
+			price: 20_000,
+			price_yesterday: 19_000,
+			..Default::default()
+		};
+		migrations::v3::CoinInfosMap::<Test>::insert(
+			b"Bitcoin".to_vec(),
+			b"BTC".to_vec(),
+			coin_info,
+		);
+
+		migrations::v3::AddMarketFields::<Test>::on_runtime_upgrade();
+
+		assert_eq!(DOracle::on_chain_storage_version(), StorageVersion::new(3));
+		assert_eq!(
+			<CoinInfosMap<Test>>::get(b"Bitcoin".to_vec(), b"BTC".to_vec()),
+			CoinInfo { price: 20_000, price_yesterday: 19_000, ..Default::default() }
+		);
+	})
+}
+
+#[test]
+fn oracle_conversion_rate_should_convert_into_the_native_balance() {
+	use sp_runtime::traits::Convert;
+
+	struct AssetConvert;
+	impl Convert<u32, Option<(Vec<u8>, Vec<u8>)>> for AssetConvert {
+		fn convert(asset_id: u32) -> Option<(Vec<u8>, Vec<u8>)> {
+			match asset_id {
+				0 => Some((b"Bitcoin".to_vec(), b"BTC".to_vec())),
+				1 => Some((b"Free".to_vec(), b"FREE".to_vec())),
+				_ => None,
+			}
+		}
+	}
+
+	frame_support::parameter_types! {
+		pub Native: (Vec<u8>, Vec<u8>) = (b"Polkadot".to_vec(), b"DOT".to_vec());
+	}
+
+	type Rate = OracleConversionRate<Test, AssetConvert, Native>;
+
+	new_test_ext().execute_with(|| {
+		let price = |price| CoinInfo { price, ..Default::default() };
+		<CoinInfosMap<Test>>::insert(b"Bitcoin".to_vec(), b"BTC".to_vec(), price(20_000));
+		<CoinInfosMap<Test>>::insert(b"Polkadot".to_vec(), b"DOT".to_vec(), price(6));
+
+		assert_eq!(Rate::from_asset_balance(3u128, 0u32), Ok(10_000u128));
+		assert_eq!(Rate::from_asset_balance(3u64, 0u32), Ok(10_000u64));
+		assert_eq!(
+			<Rate as ConversionFromAssetBalance<u128, u32, u8>>::from_asset_balance(3, 0),
+			Err(ArithmeticError::Overflow.into())
+		);
+		assert_eq!(
+			<Rate as ConversionFromAssetBalance<u128, u32, u128>>::from_asset_balance(3, 2),
+			Err(DispatchError::CannotLookup)
+		);
+		assert_err!(
+			<Rate as ConversionFromAssetBalance<u128, u32, u128>>::from_asset_balance(3, 1),
+			Error::<Test>::NoCoinInfoAvailable
+		);
+
+		Now::set(1_001);
+		assert_err!(
+			<Rate as ConversionFromAssetBalance<u128, u32, u128>>::from_asset_balance(3, 0),
+			Error::<Test>::StalePrice
+		);
+		Now::set(1_000);
+	})
+}
+
+#[cfg(feature = "orml")]
+#[test]
+fn oracle_key_adapter_should_map_keys_to_assets() {
+	use orml_traits::{DataProvider, DataProviderExtended};
+	use sp_runtime::traits::Convert;
+
+	#[derive(Clone)]
+	enum OracleKey {
+		ExchangeRate(u8),
+		FeeEstimation,
+	}
+
+	struct KeyConvert;
+	impl Convert<OracleKey, Option<(Vec<u8>, Vec<u8>)>> for KeyConvert {
+		fn convert(key: OracleKey) -> Option<(Vec<u8>, Vec<u8>)> {
+			match key {
+				OracleKey::ExchangeRate(0) => Some((b"Bitcoin".to_vec(), b"BTC".to_vec())),
+				_ => None,
+			}
+		}
+	}
+
+	struct PriceConvert;
+	impl Convert<CoinInfo, Option<(u128, u64)>> for PriceConvert {
+		fn convert(coin_info: CoinInfo) -> Option<(u128, u64)> {
+			Some((coin_info.price, coin_info.last_update_timestamp))
+		}
+	}
+
+	type Adapter =
+		adapter::OracleKeyAdapter<DOracle, OracleKey, (u128, u64), KeyConvert, PriceConvert>;
+
+	new_test_ext().execute_with(|| {
+		let coin_info =
+			CoinInfo { price: 20_000, last_update_timestamp: 900, ..Default::default() };
+		<CoinInfosMap<Test>>::insert(b"Bitcoin".to_vec(), b"BTC".to_vec(), coin_info);
+
+		assert_eq!(Adapter::get(&OracleKey::ExchangeRate(0)), Some((20_000, 900)));
+		assert_eq!(Adapter::get_no_op(&OracleKey::ExchangeRate(0)), Some((20_000, 900)));
+		assert_eq!(Adapter::get(&OracleKey::ExchangeRate(1)), None);
+		assert_eq!(Adapter::get(&OracleKey::FeeEstimation), None);
+	})
+}
+
+#[test]
+fn error_index_should_match_the_error_enum() {
+	assert_eq!(
+		Error::<Test>::NoCoinInfoAvailable.encode(),
+		vec![error_index::NO_COIN_INFO_AVAILABLE]
+	);
+	assert_eq!(Error::<Test>::ZeroPrice.encode(), vec![error_index::ZERO_PRICE]);
+	assert_eq!(Error::<Test>::StalePrice.encode(), vec![error_index::STALE_PRICE]);
+}
+
+#[test]
+fn reset_authorized_accounts_should_replace_the_set() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(get_account_id(1), ());
+		<AuthorizedAccounts<Test>>::insert(get_account_id(2), ());
+
+		assert_err!(
+			DOracle::reset_authorized_accounts(
+				RuntimeOrigin::signed(get_account_id(1)),
+				vec![get_account_id(3)],
+				10
+			),
+			sp_runtime::traits::BadOrigin
+		);
+		assert_eq!(
+			DOracle::reset_authorized_accounts(RuntimeOrigin::root(), vec![get_account_id(3)], 1)
+				.unwrap_err()
+				.error,
+			Error::<Test>::TooManyAuthorizedAccounts.into()
+		);
+		assert!(DOracle::is_authorized(&get_account_id(1)));
+
+		let _test1 = DOracle::reset_authorized_accounts(
+			RuntimeOrigin::root(),
+			vec![get_account_id(3), get_account_id(4)],
+			10,
+		);
+
+		assert_eq!(<AuthorizedAccounts<Test>>::contains_key(get_account_id(1)), false);
+		assert_eq!(<AuthorizedAccounts<Test>>::contains_key(get_account_id(2)), false);
+		assert_eq!(<AuthorizedAccounts<Test>>::contains_key(get_account_id(3)), true);
+		assert_eq!(<AuthorizedAccounts<Test>>::contains_key(get_account_id(4)), true);
+	})
+}
+
+#[test]
+fn set_batching_api_should_work() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+
+		let api = b"http://localhost:8070/currencies".to_vec();
+		let _test1 = DOracle::set_batching_api(RuntimeOrigin::signed(ALICE), api.clone());
+		assert_eq!(DOracle::batching_api(), Some(api));
+
+		let api = b"https://oracle.example.com/currencies".to_vec();
+		let _test2 = DOracle::set_batching_api(RuntimeOrigin::signed(ALICE), api.clone());
+		assert_eq!(DOracle::batching_api(), Some(api));
+	})
+}
+
+#[test]
+fn set_batching_api_should_reject_invalid_urls() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+
+		for api in [
+			b"localhost:8070/currencies".to_vec(),
+			b"ftp://localhost/currencies".to_vec(),
+			b"http://".to_vec(),
+			b"http:///currencies".to_vec(),
+			b"http://local host/currencies".to_vec(),
+			vec![b'h', b't', b't', b'p', b':', b'/', b'/', 0xff],
+			[&b"http://"[..], &[b'a'; 64][..]].concat(),
+		] {
+			assert_err!(
+				DOracle::set_batching_api(RuntimeOrigin::signed(ALICE), api),
+				Error::<Test>::InvalidApiUrl
+			);
+		}
+		assert_eq!(DOracle::batching_api(), None);
+	})
+}
+
+#[test]
+fn update_interval_should_default_to_every_block() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(DOracle::update_interval(), 1);
+		assert!(DOracle::is_update_block(1));
+		assert!(DOracle::is_update_block(2));
+	})
+}
+
+#[test]
+fn set_update_interval_should_work() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+
+		let _test1 = DOracle::set_update_interval(RuntimeOrigin::signed(ALICE), 5);
+
+		assert_eq!(DOracle::update_interval(), 5);
+		assert!(DOracle::is_update_block(0));
+		assert!(!DOracle::is_update_block(3));
+		assert!(DOracle::is_update_block(10));
+
+		assert_err!(
+			DOracle::set_update_interval(RuntimeOrigin::root(), 0),
+			Error::<Test>::InvalidUpdateInterval
+		);
+		assert_eq!(DOracle::update_interval(), 5);
+	})
+}
+
+const API: &str = "http://localhost:8070/currencies";
+
+fn new_offchain_test_ext() -> (sp_io::TestExternalities, Arc<RwLock<testing::OffchainState>>) {
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	(ext, offchain_state)
+}
+
+fn expect_batching_request(state: &Arc<RwLock<testing::OffchainState>>, response: &[u8]) {
+	state.write().expect_request(testing::PendingRequest {
+		method: "POST".into(),
+		uri: API.into(),
+		headers: vec![("content-type".into(), "application/json".into())],
+		body: b"[]".to_vec(),
+		response: Some(response.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn fetch_coin_infos_should_retry_transient_failures() {
+	let (mut ext, state) = new_offchain_test_ext();
+	expect_batching_request(&state, b"not json");
+	expect_batching_request(
+		&state,
+		br#"[{"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":1,"lastUpdateTimestamp":2,"price":3}]"#,
+	);
+
+	ext.execute_with(|| {
+		let coin_infos = DOracle::fetch_coin_infos_with_retry(API, b"[]").unwrap();
+
+		assert_eq!(coin_infos.len(), 1);
+		assert_eq!(DOracle::last_update_failure(), None);
+		assert_eq!(DOracle::diagnostics().last_success, Some(0));
+		assert_eq!(DOracle::diagnostics().consecutive_failures, 0);
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn fetch_coin_infos_should_send_auth_header_from_local_storage() {
+	let (mut ext, state) = new_offchain_test_ext();
+	state.write().expect_request(testing::PendingRequest {
+		method: "POST".into(),
+		uri: API.into(),
+		headers: vec![
+			("content-type".into(), "application/json".into()),
+			("authorization".into(), "Bearer secret".into()),
+		],
+		body: b"[]".to_vec(),
+		response: Some(b"[]".to_vec()),
+		sent: true,
+		..Default::default()
+	});
+
+	ext.execute_with(|| {
+		sp_io::offchain::local_storage_set(
+			StorageKind::PERSISTENT,
+			BATCHING_AUTH_HEADER_KEY,
+			b"Bearer secret",
+		);
+
+		assert!(DOracle::fetch_coin_infos_with_retry(API, b"[]").unwrap().is_empty());
+	})
+}
+
+fn expect_signed_batching_request(
+	state: &Arc<RwLock<testing::OffchainState>>,
+	response: &[u8],
+	signature: Option<String>,
+) {
+	state.write().expect_request(testing::PendingRequest {
+		method: "POST".into(),
+		uri: API.into(),
+		headers: vec![("content-type".into(), "application/json".into())],
+		body: b"[]".to_vec(),
+		response: Some(response.to_vec()),
+		response_headers: signature
+			.map(|signature| vec![(RESPONSE_SIGNATURE_HEADER.into(), signature)])
+			.unwrap_or_default(),
+		sent: true,
+		..Default::default()
+	});
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn fetch_coin_infos_should_accept_correctly_signed_response() {
+	let (mut ext, state) = new_offchain_test_ext();
+	let server = sr25519::Pair::from_seed(&[7u8; 32]);
+	let response = br#"[{"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":1,"lastUpdateTimestamp":2,"price":3}]"#;
+	expect_signed_batching_request(&state, response, Some(hex::encode(server.sign(response))));
+
+	ext.execute_with(|| {
+		assert_ok!(DOracle::set_batching_server_key(RuntimeOrigin::root(), Some(server.public())));
+
+		assert_eq!(DOracle::fetch_coin_infos_with_retry(API, b"[]").unwrap().len(), 1);
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn fetch_coin_infos_should_reject_unsigned_or_wrongly_signed_response() {
+	let (mut ext, state) = new_offchain_test_ext();
+	let server = sr25519::Pair::from_seed(&[7u8; 32]);
+	let attacker = sr25519::Pair::from_seed(&[8u8; 32]);
+	expect_signed_batching_request(&state, b"[]", None);
+	expect_signed_batching_request(&state, b"[]", Some(hex::encode(attacker.sign(b"[]"))));
+
+	ext.execute_with(|| {
+		assert_ok!(DOracle::set_batching_server_key(RuntimeOrigin::root(), Some(server.public())));
+
+		for _ in 0..2 {
+			assert_err!(
+				DOracle::fetch_coin_infos_with_retry(API, b"[]").map_err(DispatchError::from),
+				Error::<Test>::InvalidResponseSignature
+			);
+		}
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn fetch_coin_infos_should_record_failure_after_retries() {
+	let (mut ext, state) = new_offchain_test_ext();
+	for _ in 0..3 {
+		expect_batching_request(&state, b"not json");
+	}
+
+	ext.execute_with(|| {
+		assert_err!(
+			DOracle::fetch_coin_infos_with_retry(API, b"[]").map_err(DispatchError::from),
+			Error::<Test>::DeserializeError
+		);
+
+		let failure = DOracle::last_update_failure().unwrap();
+		assert_eq!(failure.attempts, 3);
+		assert_eq!(failure.error, b"DeserializeError".to_vec());
+		assert_eq!(DOracle::diagnostics().consecutive_failures, 1);
+		assert_eq!(DOracle::diagnostics().last_success, None);
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn supported_currencies_request_bodies_should_be_chunked() {
+	new_test_ext().execute_with(|| {
+		assert!(DOracle::supported_currencies_request_bodies().is_empty());
+
+		for i in 1..=5 {
+			<SupportedCurrencies<Test>>::insert(AssetId::new(b"Chain".to_vec(), vec![b'A' + i]), ());
+		}
+
+		let bodies = DOracle::supported_currencies_request_bodies();
+		assert_eq!(bodies.len(), 3);
+
+		let currencies: Vec<serde_json::Value> = bodies
+			.iter()
+			.flat_map(|body| serde_json::from_slice::<Vec<serde_json::Value>>(body).unwrap())
+			.collect();
+		assert_eq!(currencies.len(), 5);
+		assert_eq!(currencies[0]["blockchain"], "Chain");
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn update_lock_expiration_should_cover_every_retry() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(DOracle::update_lock_expiration(), Duration::from_millis(60_000));
+
+		for i in 1..=5 {
+			<SupportedCurrencies<Test>>::insert(AssetId::new(b"Chain".to_vec(), vec![b'A' + i]), ());
+		}
+		<ExtraBatchingApis<Test>>::put(vec![b"http://localhost:8071/currencies".to_vec()]);
+
+		// 3 requests to 2 APIs, each timing out 3 times after 2s with 10ms and 20ms backoff
+		assert_eq!(DOracle::update_lock_expiration(), Duration::from_millis(6 * 6_030 + 60_000));
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn changed_coin_infos_should_drop_unchanged_entries() {
+	new_test_ext().execute_with(|| {
+		let stable = CoinInfo { price: 1, ..Default::default() };
+		let moved = CoinInfo { price: 2, ..Default::default() };
+		<CoinInfosMap<Test>>::insert(vec![1], vec![1], stable.clone());
+		<CoinInfosMap<Test>>::insert(vec![2], vec![2], stable.clone());
+
+		let changed = DOracle::changed_coin_infos(vec![
+			((vec![1], vec![1]), stable.clone()),
+			((vec![2], vec![2]), moved.clone()),
+			((vec![3], vec![3]), stable.clone()),
+		]);
+
+		assert_eq!(changed, vec![((vec![2], vec![2]), moved), ((vec![3], vec![3]), stable)]);
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn changed_coin_infos_should_drop_recent_prices_within_the_threshold() {
+	new_test_ext().execute_with(|| {
+		let config = OcwConfig {
+			min_change_threshold: sp_runtime::Permill::from_percent(10),
+			..DOracle::ocw_config()
+		};
+		assert_ok!(DOracle::set_ocw_config(RuntimeOrigin::root(), Some(config)));
+		let quote = |price, last_update_timestamp| CoinInfo {
+			price,
+			last_update_timestamp,
+			..Default::default()
+		};
+		for id in 1..=3 {
+			<CoinInfosMap<Test>>::insert(vec![id], vec![id], quote(100, 100));
+		}
+
+		let changed = DOracle::changed_coin_infos(vec![
+			((vec![1], vec![1]), quote(105, 110)),
+			((vec![2], vec![2]), quote(110, 110)),
+			((vec![3], vec![3]), quote(105, 601)),
+		]);
+
+		assert_eq!(
+			changed,
+			vec![((vec![2], vec![2]), quote(110, 110)), ((vec![3], vec![3]), quote(105, 601))]
+		);
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn changed_coin_infos_should_prefer_the_asset_threshold() {
+	new_test_ext().execute_with(|| {
+		let quote = |price| CoinInfo { price, last_update_timestamp: 100, ..Default::default() };
+		<CoinInfosMap<Test>>::insert(vec![1], vec![1], quote(10_000));
+		<CoinInfosMap<Test>>::insert(vec![2], vec![2], quote(10_000));
+
+		// 50 basis points for the first asset, any change for the second
+		assert_ok!(DOracle::set_min_change_threshold(
+			RuntimeOrigin::root(),
+			vec![1],
+			vec![1],
+			Some(sp_runtime::Permill::from_parts(5_000))
+		));
+
+		let changed = DOracle::changed_coin_infos(vec![
+			((vec![1], vec![1]), quote(10_040)),
+			((vec![2], vec![2]), quote(10_040)),
+		]);
+		assert_eq!(changed, vec![((vec![2], vec![2]), quote(10_040))]);
+
+		assert_ok!(DOracle::set_min_change_threshold(
+			RuntimeOrigin::root(),
+			vec![1],
+			vec![1],
+			None
+		));
+		assert_eq!(DOracle::changed_coin_infos(vec![((vec![1], vec![1]), quote(10_040))]).len(), 1);
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn set_ocw_config_should_override_the_defaults() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let defaults = DOracle::ocw_config();
+		assert_eq!(defaults.max_http_retries, MaxHttpRetries::get());
+		assert_eq!(defaults.max_currencies_per_request, MaxCurrenciesPerRequest::get());
+
+		let config = OcwConfig { max_currencies_per_request: 0, ..defaults.clone() };
+		assert_err!(
+			DOracle::set_ocw_config(RuntimeOrigin::root(), Some(config)),
+			Error::<Test>::InvalidOcwConfig
+		);
+		assert_err!(
+			DOracle::set_ocw_config(RuntimeOrigin::signed(ALICE), None),
+			Error::<Test>::ThisAccountIdIsNotAuthorized
+		);
+
+		let config = OcwConfig { max_http_retries: 0, ..defaults.clone() };
+		assert_ok!(DOracle::set_ocw_config(RuntimeOrigin::root(), Some(config.clone())));
+		assert_eq!(DOracle::ocw_config(), config);
+		System::assert_last_event(Event::<Test>::OcwConfigSet(Some(config)).into());
+
+		assert_ok!(DOracle::set_ocw_config(RuntimeOrigin::root(), None));
+		assert_eq!(DOracle::ocw_config(), defaults);
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn remember_submitted_batch_should_suppress_duplicates_within_the_window() {
+	let (mut ext, _) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		let first = BlakeTwo256::hash(b"first");
+		let second = BlakeTwo256::hash(b"second");
+
+		System::set_block_number(1);
+		assert!(!DOracle::is_recently_submitted(first));
+		DOracle::remember_submitted_batch(first);
+		assert!(DOracle::is_recently_submitted(first));
+		assert!(!DOracle::is_recently_submitted(second));
+		DOracle::remember_submitted_batch(second);
+
+		System::set_block_number(5);
+		assert!(DOracle::is_recently_submitted(first));
+
+		System::set_block_number(6);
+		assert!(!DOracle::is_recently_submitted(first));
+		DOracle::remember_submitted_batch(first);
+		assert!(DOracle::is_recently_submitted(first));
+		assert!(!DOracle::is_recently_submitted(second));
+	});
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn submit_coin_infos_should_resubmit_batches_that_failed_to_send() {
+	let keystore = Arc::new(KeyStore::new());
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let (mut ext, _) = new_offchain_test_ext();
+	ext.register_extension(KeystoreExt(keystore.clone()));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	let prices = vec![((b"Bitcoin".to_vec(), b"BTC".to_vec()), CoinInfo::default())];
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		assert!(matches!(
+			DOracle::submit_coin_infos(prices.clone()),
+			Err(Error::<Test>::NoSubmitterAccount)
+		));
+		assert!(!DOracle::is_recently_submitted(BlakeTwo256::hash_of(&prices)));
+	});
+
+	SyncCryptoStore::sr25519_generate_new(&*keystore, crypto::KEY_TYPE, None).unwrap();
+	ext.execute_with(|| {
+		assert!(DOracle::submit_coin_infos(prices.clone()).is_ok());
+		assert_eq!(pool_state.read().transactions.len(), 1);
+		assert!(DOracle::is_recently_submitted(BlakeTwo256::hash_of(&prices)));
+
+		assert!(DOracle::submit_coin_infos(prices.clone()).is_ok());
+		assert_eq!(pool_state.read().transactions.len(), 1);
+	});
+}
+
+#[test]
+fn is_feeder_enabled_should_follow_offchain_local_storage() {
+	let (mut ext, _) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		let flag = StorageValueRef::persistent(FEEDER_ENABLED_KEY);
+		assert!(!DOracle::is_feeder_enabled());
+
+		flag.set(&true);
+		assert!(DOracle::is_feeder_enabled());
+
+		flag.set(&false);
+		assert!(!DOracle::is_feeder_enabled());
+	});
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn select_submitter_should_prefer_locally_configured_account() {
+	let keystore = KeyStore::new();
+	let _first = SyncCryptoStore::sr25519_generate_new(&keystore, crypto::KEY_TYPE, None).unwrap();
+	let second = SyncCryptoStore::sr25519_generate_new(&keystore, crypto::KEY_TYPE, None).unwrap();
+	let (mut ext, _) = new_offchain_test_ext();
+	ext.register_extension(KeystoreExt(Arc::new(keystore)));
+
+	ext.execute_with(|| {
+		let configured = StorageValueRef::persistent(FEEDER_ACCOUNT_KEY);
+
+		configured.set(&second);
+		assert_eq!(DOracle::select_submitter().map(|account| account.id), Some(second));
+
+		configured.set(&Public([9u8; 32]));
+		assert!(DOracle::select_submitter().is_none());
+	})
+}
+
+#[test]
+fn feeder_heartbeat_should_record_liveness() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		System::set_block_number(7);
+
+		assert_err!(
+			DOracle::feeder_heartbeat(RuntimeOrigin::signed(get_account_id(2))),
+			Error::<Test>::ThisAccountIdIsNotAuthorized
+		);
+		assert_ok!(DOracle::feeder_heartbeat(RuntimeOrigin::signed(ALICE)));
+
+		assert_eq!(DOracle::last_heartbeat(ALICE), Some(7));
+		System::assert_has_event(Event::<Test>::FeederHeartbeat(ALICE).into());
+
+		System::set_block_number(9);
+		assert_ok!(DOracle::set_updated_coin_infos(RuntimeOrigin::signed(ALICE), vec![]));
+		assert_eq!(DOracle::last_heartbeat(ALICE), Some(9));
+	})
+}
+
+#[test]
+fn set_extra_batching_apis_should_validate() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		let api = API.as_bytes().to_vec();
+
+		assert_err!(
+			DOracle::set_extra_batching_apis(RuntimeOrigin::signed(ALICE), vec![api.clone(); 3]),
+			Error::<Test>::TooManyBatchingApis
+		);
+		assert_err!(
+			DOracle::set_extra_batching_apis(
+				RuntimeOrigin::signed(ALICE),
+				vec![api.clone(), b"ftp://x".to_vec()]
+			),
+			Error::<Test>::InvalidApiUrl
+		);
+		assert_ok!(DOracle::set_extra_batching_apis(
+			RuntimeOrigin::signed(ALICE),
+			vec![api.clone(); 2]
+		));
+		assert_eq!(DOracle::extra_batching_apis(), vec![api.clone(); 2]);
+	})
+}
+
+#[test]
+fn set_derived_feeds_should_validate() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		let asset = |symbol: &[u8]| (b"Fiat".to_vec(), symbol.to_vec());
+		let inverse = |derived: &[u8], source: &[u8]| {
+			(asset(derived), DerivedFeed::Inverse(asset(source)))
+		};
+
+		assert_err!(
+			DOracle::set_derived_feeds(RuntimeOrigin::signed(ALICE), vec![inverse(b"A", b"B"); 3]),
+			Error::<Test>::TooManyDerivedFeeds
+		);
+		assert_err!(
+			DOracle::set_derived_feeds(
+				RuntimeOrigin::signed(ALICE),
+				vec![inverse(b"A", b"B"), inverse(b"C", b"A")]
+			),
+			Error::<Test>::InvalidDerivedFeed
+		);
+		assert_err!(
+			DOracle::set_derived_feeds(RuntimeOrigin::signed(ALICE), vec![inverse(b"A", b"A")]),
+			Error::<Test>::InvalidDerivedFeed
+		);
+		assert_ok!(DOracle::set_derived_feeds(
+			RuntimeOrigin::signed(ALICE),
+			vec![inverse(b"A", b"B")]
+		));
+		assert_eq!(DOracle::derived_feeds(), vec![inverse(b"A", b"B")]);
+	})
+}
+
+#[test]
+fn set_updated_coin_infos_should_update_derived_feeds() {
+	new_test_ext().execute_with(|| {
+		<AuthorizedAccounts<Test>>::insert(ALICE, ());
+		let asset = |symbol: &[u8]| (b"Fiat".to_vec(), symbol.to_vec());
+		<SupportedCurrencies<Test>>::insert(AssetId::new(b"Fiat".to_vec(), b"MXN".to_vec()), ());
+		<SupportedCurrencies<Test>>::insert(AssetId::new(b"Fiat".to_vec(), b"BRL".to_vec()), ());
+		assert_ok!(DOracle::set_derived_feeds(
+			RuntimeOrigin::root(),
+			vec![
+				(asset(b"USD-MXN"), DerivedFeed::Inverse(asset(b"MXN"))),
+				(
+					asset(b"BRL-MXN"),
+					DerivedFeed::Cross { base: asset(b"BRL"), quote: asset(b"MXN") },
+				),
+			]
+		));
+
+		// 1 MXN = 0.05 USD and 1 BRL = 0.2 USD, with 12 decimals
+		let quote = |price, last_update_timestamp| CoinInfo {
+			price,
+			last_update_timestamp,
+			..Default::default()
+		};
+		assert_ok!(DOracle::set_updated_coin_infos(
+			RuntimeOrigin::signed(ALICE),
+			vec![(asset(b"MXN"), quote(50_000_000_000, 900))]
+		));
+		let usd_mxn = DOracle::get_coin_info(b"Fiat".to_vec(), b"USD-MXN".to_vec()).unwrap();
+		assert_eq!((usd_mxn.price, usd_mxn.last_update_timestamp), (20_000_000_000_000, 900));
+		assert_eq!(usd_mxn.symbol, b"USD-MXN".to_vec());
+		assert!(DOracle::get_coin_info(b"Fiat".to_vec(), b"BRL-MXN".to_vec()).is_err());
+
+		assert_ok!(DOracle::set_updated_coin_infos(
+			RuntimeOrigin::signed(ALICE),
+			vec![(asset(b"BRL"), quote(200_000_000_000, 950))]
+		));
+		let brl_mxn = DOracle::get_coin_info(b"Fiat".to_vec(), b"BRL-MXN".to_vec()).unwrap();
+		assert_eq!((brl_mxn.price, brl_mxn.last_update_timestamp), (4_000_000_000_000, 900));
+	})
+}
+
+#[test]
+fn aggregate_sources_should_take_the_median_of_agreeing_sources() {
+	new_test_ext().execute_with(|| {
+		let quote = |symbol: &[u8], price| CoinInfo {
+			symbol: symbol.to_vec(),
+			blockchain: b"Chain".to_vec(),
+			price,
+			..Default::default()
+		};
+		let sources = vec![
+			vec![quote(b"AGREE", 100), quote(b"APART", 100), quote(b"ALONE", 100)],
+			vec![quote(b"AGREE", 104), quote(b"APART", 120)],
+			vec![quote(b"AGREE", 98), quote(b"APART", 100)],
+		];
+
+		let coin_infos = DOracle::aggregate_sources(sources, 3);
+
+		assert_eq!(coin_infos, vec![quote(b"AGREE", 100)]);
+	})
+}
+
+#[test]
+fn aggregate_sources_should_pass_a_single_source_through() {
+	new_test_ext().execute_with(|| {
+		let coin_info = CoinInfo { price: 1, ..Default::default() };
+
+		let coin_infos = DOracle::aggregate_sources(vec![vec![coin_info.clone()]], 1);
+
+		assert_eq!(coin_infos, vec![coin_info]);
+	})
+}
+
+#[test]
+fn scale_coin_info_should_convert_to_price_decimals() {
+	new_test_ext().execute_with(|| {
+		let coin_info = |price| CoinInfo { price, ..Default::default() };
+
+		assert_eq!(DOracle::scale_coin_info(coin_info(1_234_567)), Some(coin_info(1_234_567)));
+
+		PriceDecimals::set(6);
+		assert_eq!(DOracle::scale_coin_info(coin_info(1_234_567)), Some(coin_info(1)));
+		assert_eq!(DOracle::scale_coin_info(coin_info(999_999)), Some(coin_info(0)));
+		AllowZeroPrices::set(false);
+		assert_eq!(DOracle::scale_coin_info(coin_info(999_999)), None);
+		AllowZeroPrices::set(true);
+
+		PriceDecimals::set(18);
+		assert_eq!(
+			DOracle::scale_coin_info(coin_info(1_234_567)),
+			Some(coin_info(1_234_567_000_000))
+		);
+		assert_eq!(DOracle::scale_coin_info(coin_info(u128::MAX)), None);
+
+		PriceDecimals::set(12);
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn supported_currencies_request_bodies_should_follow_encoding() {
+	new_test_ext().execute_with(|| {
+		let asset_id = AssetId::new(b"Bitcoin".to_vec(), b"BTC".to_vec());
+		<SupportedCurrencies<Test>>::insert(asset_id.clone(), ());
+
+		assert_eq!(
+			DOracle::supported_currencies_request_bodies(),
+			vec![br#"[{"blockchain":"Bitcoin","symbol":"BTC"}]"#.to_vec()]
+		);
+
+		assert_ok!(DOracle::set_batching_api_encoding(
+			RuntimeOrigin::root(),
+			BatchingApiEncoding::Scale
+		));
+		assert_eq!(DOracle::supported_currencies_request_bodies(), vec![vec![asset_id].encode()]);
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn fetch_coin_infos_should_negotiate_scale() {
+	let (mut ext, state) = new_offchain_test_ext();
+	let coin_info = CoinInfo {
+		symbol: b"BTC".to_vec(),
+		name: b"Bitcoin".to_vec(),
+		blockchain: b"Bitcoin".to_vec(),
+		supply: 1,
+		last_update_timestamp: 2,
+		price: 3,
+		price_yesterday: 0,
+		volume_yesterday_usd: 0,
+		market_cap: None,
+		high_24h: None,
+		low_24h: None,
+	};
+	let scale_headers = vec![
+		("content-type".to_string(), SCALE_CONTENT_TYPE.to_string()),
+		("accept".to_string(), SCALE_CONTENT_TYPE.to_string()),
+	];
+	state.write().expect_request(testing::PendingRequest {
+		method: "POST".into(),
+		uri: API.into(),
+		headers: scale_headers.clone(),
+		body: b"\0".to_vec(),
+		response: Some(vec![coin_info.clone()].encode()),
+		response_headers: vec![("content-type".into(), SCALE_CONTENT_TYPE.into())],
+		sent: true,
+		..Default::default()
+	});
+	// A server without SCALE support answers with JSON
+	state.write().expect_request(testing::PendingRequest {
+		method: "POST".into(),
+		uri: API.into(),
+		headers: scale_headers,
+		body: b"\0".to_vec(),
+		response: Some(
+			br#"[{"symbol":"BTC","name":"Bitcoin","blockchain":"Bitcoin","supply":1,"lastUpdateTimestamp":2,"price":3}]"#.to_vec(),
+		),
+		response_headers: vec![("content-type".into(), "application/json".into())],
+		sent: true,
+		..Default::default()
+	});
+
+	ext.execute_with(|| {
+		assert_ok!(DOracle::set_batching_api_encoding(
+			RuntimeOrigin::root(),
+			BatchingApiEncoding::Scale
+		));
+
+		for _ in 0..2 {
+			assert_eq!(
+				DOracle::fetch_coin_infos_with_retry(API, b"\0").unwrap(),
+				vec![coin_info.clone()]
+			);
+		}
+	})
+}
+
+#[cfg(not(feature = "no-ocw"))]
+#[test]
+fn fetch_coin_infos_should_reject_too_large_responses() {
+	let (mut ext, state) = new_offchain_test_ext();
+	expect_batching_request(&state, b"[]");
+	expect_batching_request(&state, b"[ ]");
+
+	ext.execute_with(|| {
+		MaxResponseSize::set(2);
+
+		assert!(DOracle::fetch_coin_infos_with_retry(API, b"[]").unwrap().is_empty());
+		assert_err!(
+			DOracle::fetch_coin_infos_with_retry(API, b"[]").map_err(DispatchError::from),
+			Error::<Test>::ResponseTooLarge
+		);
+
+		MaxResponseSize::set(1 << 20);
+	})
+}
+
+#[test]
+fn genesis_presets_should_build() {
+	new_test_ext().execute_with(|| {
+		for name in GenesisConfig::<Test>::preset_names() {
+			let config = GenesisConfig::<Test>::preset(name, vec![ALICE]).unwrap();
+			let json = serde_json::to_string(&config).unwrap();
+			let config: GenesisConfig<Test> = serde_json::from_str(&json).unwrap();
+
+			GenesisBuild::<Test>::build(&config);
+
+			assert!(<AuthorizedAccounts<Test>>::contains_key(ALICE));
+			assert!(<SupportedCurrencies<Test>>::contains_key(AssetId::new(
+				b"Bitcoin".to_vec(),
+				b"BTC".to_vec()
+			)));
+			assert_eq!(
+				<BatchingApi<Test>>::get(),
+				Some(b"http://localhost:8070/currencies".to_vec())
+			);
+		}
+
+		assert!(GenesisConfig::<Test>::preset("unknown", vec![ALICE]).is_none());
+	})
+}
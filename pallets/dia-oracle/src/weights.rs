@@ -43,23 +43,46 @@ pub trait WeightInfo{
 	fn authorize_account_signed() -> Weight ;
 	fn deauthorize_account() -> Weight ;
 	fn deauthorize_account_signed() -> Weight ;
-	fn set_updated_coin_infos() -> Weight;
+	fn set_updated_coin_infos(a: u32, d: u32, ) -> Weight;
 	fn set_batching_api() -> Weight;
+	fn set_submitter_selection() -> Weight;
+	fn rotate_feeder_key() -> Weight;
+	fn reset_authorized_accounts(a: u32, r: u32, ) -> Weight;
+	fn set_update_interval() -> Weight;
+	fn set_batching_server_key() -> Weight;
+	fn feeder_heartbeat() -> Weight;
+	fn set_extra_batching_apis(a: u32, ) -> Weight;
+	fn set_batching_api_encoding() -> Weight;
+	fn clear_all_prices(a: u32, ) -> Weight;
+	fn purge_blockchain(a: u32, ) -> Weight;
+	fn set_derived_feeds(a: u32, ) -> Weight;
+	fn cancel_currency_addition() -> Weight;
+	fn block_currency() -> Weight;
+	fn unblock_currency() -> Weight;
+	fn set_ocw_config() -> Weight;
+	fn set_min_change_threshold() -> Weight;
 }
 pub struct DiaWeightInfo<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for DiaWeightInfo<T> {
 	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
 	// Storage: DiaOracle SupportedCurrencies (r:1 w:1)
+	// Storage: DiaOracle PendingCurrencies (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DiaOracle CurrencyDeposits (r:0 w:1)
+	// Storage: DiaOracle PendingActivations (r:0 w:1)
 	fn add_currency() -> Weight {
 		Weight::from_ref_time(1_494_649_000)
-			.saturating_add(T::DbWeight::get().reads(2))
-			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(4))
 	}
 	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
 	// Storage: DiaOracle SupportedCurrencies (r:1 w:0)
+	// Storage: DiaOracle CurrencyDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
 	fn remove_currency() -> Weight {
 		Weight::from_ref_time(542_550_000)
-			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(2))
 	}
 	// Storage: DiaOracle AuthorizedAccounts (r:1 w:1)
 	fn authorize_account() -> Weight {
@@ -85,34 +108,195 @@ impl<T: frame_system::Config> WeightInfo for DiaWeightInfo<T> {
 			.saturating_add(T::DbWeight::get().writes(1))
 	}
 	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle SupportedCurrencies (r:1 w:0)
+	// Storage: DiaOracle LastHeartbeat (r:0 w:1)
+	// Storage: DiaOracle BatchLog (r:1 w:1)
+	// Storage: DiaOracle FeederStatistics (r:1 w:1)
 	// Storage: DiaOracle CoinInfosMap (r:0 w:1)
-	fn set_updated_coin_infos() -> Weight {
-		Weight::from_ref_time(1_152_148_682_000)
+	// Storage: DiaOracle PriceSubmitters (r:0 w:1)
+	// Storage: DiaOracle DerivedFeeds (r:1 w:0)
+	// Storage: DiaOracle CoinInfosMap (r:2 w:1)
+	fn set_updated_coin_infos(a: u32, d: u32, ) -> Weight {
+		Weight::from_ref_time(48_612_000)
+			.saturating_add(Weight::from_ref_time(230_167_000).saturating_mul(a as u64))
+			.saturating_add(Weight::from_ref_time(27_430_000).saturating_mul(d as u64))
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(a as u64)))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(d as u64)))
+			.saturating_add(T::DbWeight::get().writes(3))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(a as u64)))
+			.saturating_add(T::DbWeight::get().writes((1 as u64).saturating_mul(d as u64)))
+	}
+
+	fn set_batching_api() -> Weight {
+		Weight::from_ref_time(1_241_248_000)
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
 	}
 
-	fn set_batching_api() -> Weight {
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle SubmitterSelectionStrategy (r:0 w:1)
+	fn set_submitter_selection() -> Weight {
 		Weight::from_ref_time(1_241_248_000)
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
 	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:2 w:2)
+	fn rotate_feeder_key() -> Weight {
+		Weight::from_ref_time(1_525_600_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:1)
+	fn reset_authorized_accounts(a: u32, r: u32, ) -> Weight {
+		Weight::from_ref_time(2_482_496_000)
+			.saturating_add(Weight::from_ref_time(1_241_248_000).saturating_mul(a as u64))
+			.saturating_add(Weight::from_ref_time(1_241_248_000).saturating_mul(r as u64))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().writes((1 as u64).saturating_mul(a as u64)))
+			.saturating_add(T::DbWeight::get().writes((1 as u64).saturating_mul(r as u64)))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle UpdateInterval (r:0 w:1)
+	fn set_update_interval() -> Weight {
+		Weight::from_ref_time(1_241_248_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle BatchingServerKey (r:0 w:1)
+	fn set_batching_server_key() -> Weight {
+		Weight::from_ref_time(1_198_370_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle LastHeartbeat (r:0 w:1)
+	fn feeder_heartbeat() -> Weight {
+		Weight::from_ref_time(1_156_031_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle ExtraBatchingApis (r:0 w:1)
+	fn set_extra_batching_apis(a: u32, ) -> Weight {
+		Weight::from_ref_time(1_207_413_000)
+			.saturating_add(Weight::from_ref_time(2_315_000).saturating_mul(a as u64))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle BatchingEncoding (r:0 w:1)
+	fn set_batching_api_encoding() -> Weight {
+		Weight::from_ref_time(1_187_560_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle CoinInfosMap (r:1 w:1)
+	// Storage: DiaOracle PriceSubmitters (r:0 w:1)
+	fn clear_all_prices(a: u32, ) -> Weight {
+		Weight::from_ref_time(1_034_200_000)
+			.saturating_add(Weight::from_ref_time(1_322_000).saturating_mul(a as u64))
+			.saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(a as u64)))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(a as u64)))
+	}
+
+	// Storage: DiaOracle CoinInfosMap (r:1 w:1)
+	// Storage: DiaOracle PriceSubmitters (r:0 w:1)
+	fn purge_blockchain(a: u32, ) -> Weight {
+		Weight::from_ref_time(1_051_700_000)
+			.saturating_add(Weight::from_ref_time(1_347_000).saturating_mul(a as u64))
+			.saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(a as u64)))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(a as u64)))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle DerivedFeeds (r:0 w:1)
+	fn set_derived_feeds(a: u32, ) -> Weight {
+		Weight::from_ref_time(1_196_824_000)
+			.saturating_add(Weight::from_ref_time(3_108_000).saturating_mul(a as u64))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle PendingCurrencies (r:1 w:1)
+	// Storage: DiaOracle PendingActivations (r:0 w:1)
+	fn cancel_currency_addition() -> Weight {
+		Weight::from_ref_time(1_093_517_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle SupportedCurrencies (r:1 w:1)
+	// Storage: DiaOracle PendingCurrencies (r:1 w:1)
+	// Storage: DiaOracle CurrencyDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DiaOracle BlockedCurrencies (r:0 w:1)
+	// Storage: DiaOracle PendingActivations (r:0 w:1)
+	fn block_currency() -> Weight {
+		Weight::from_ref_time(1_387_204_000)
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().writes(6))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle BlockedCurrencies (r:1 w:1)
+	fn unblock_currency() -> Weight {
+		Weight::from_ref_time(1_012_730_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle OcwSettings (r:0 w:1)
+	fn set_ocw_config() -> Weight {
+		Weight::from_ref_time(1_104_382_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle MinChangeThresholds (r:0 w:1)
+	fn set_min_change_threshold() -> Weight {
+		Weight::from_ref_time(1_096_915_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
 }
 
 
 impl WeightInfo for () {
 	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
 	// Storage: DiaOracle SupportedCurrencies (r:1 w:1)
+	// Storage: DiaOracle PendingCurrencies (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DiaOracle CurrencyDeposits (r:0 w:1)
+	// Storage: DiaOracle PendingActivations (r:0 w:1)
 	fn add_currency() -> Weight {
 		Weight::from_ref_time(1_494_649_000)
-			.saturating_add(RocksDbWeight::get().reads(2))
-			.saturating_add(RocksDbWeight::get().writes(1))
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().writes(4))
 	}
 	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
 	// Storage: DiaOracle SupportedCurrencies (r:1 w:0)
+	// Storage: DiaOracle CurrencyDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
 	fn remove_currency() -> Weight {
 		Weight::from_ref_time(542_550_000)
-			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().writes(2))
 	}
 	// Storage: DiaOracle AuthorizedAccounts (r:1 w:1)
 	fn authorize_account() -> Weight {
@@ -138,16 +322,170 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().writes(1))
 	}
 	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle SupportedCurrencies (r:1 w:0)
+	// Storage: DiaOracle LastHeartbeat (r:0 w:1)
+	// Storage: DiaOracle BatchLog (r:1 w:1)
+	// Storage: DiaOracle FeederStatistics (r:1 w:1)
 	// Storage: DiaOracle CoinInfosMap (r:0 w:1)
-	fn set_updated_coin_infos() -> Weight {
-		Weight::from_ref_time(1_152_148_682_000)
+	// Storage: DiaOracle PriceSubmitters (r:0 w:1)
+	// Storage: DiaOracle DerivedFeeds (r:1 w:0)
+	// Storage: DiaOracle CoinInfosMap (r:2 w:1)
+	fn set_updated_coin_infos(a: u32, d: u32, ) -> Weight {
+		Weight::from_ref_time(48_612_000)
+			.saturating_add(Weight::from_ref_time(230_167_000).saturating_mul(a as u64))
+			.saturating_add(Weight::from_ref_time(27_430_000).saturating_mul(d as u64))
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(a as u64)))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(d as u64)))
+			.saturating_add(RocksDbWeight::get().writes(3))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(a as u64)))
+			.saturating_add(RocksDbWeight::get().writes((1 as u64).saturating_mul(d as u64)))
+	}
+
+	fn set_batching_api() -> Weight {
+		Weight::from_ref_time(1_241_248_000)
 			.saturating_add(RocksDbWeight::get().reads(1))
 			.saturating_add(RocksDbWeight::get().writes(1))
 	}
 
-	fn set_batching_api() -> Weight {
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle SubmitterSelectionStrategy (r:0 w:1)
+	fn set_submitter_selection() -> Weight {
 		Weight::from_ref_time(1_241_248_000)
 			.saturating_add(RocksDbWeight::get().reads(1))
 			.saturating_add(RocksDbWeight::get().writes(1))
 	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:2 w:2)
+	fn rotate_feeder_key() -> Weight {
+		Weight::from_ref_time(1_525_600_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:1)
+	fn reset_authorized_accounts(a: u32, r: u32, ) -> Weight {
+		Weight::from_ref_time(2_482_496_000)
+			.saturating_add(Weight::from_ref_time(1_241_248_000).saturating_mul(a as u64))
+			.saturating_add(Weight::from_ref_time(1_241_248_000).saturating_mul(r as u64))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+			.saturating_add(RocksDbWeight::get().writes(1))
+			.saturating_add(RocksDbWeight::get().writes((1 as u64).saturating_mul(a as u64)))
+			.saturating_add(RocksDbWeight::get().writes((1 as u64).saturating_mul(r as u64)))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle UpdateInterval (r:0 w:1)
+	fn set_update_interval() -> Weight {
+		Weight::from_ref_time(1_241_248_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle BatchingServerKey (r:0 w:1)
+	fn set_batching_server_key() -> Weight {
+		Weight::from_ref_time(1_198_370_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle LastHeartbeat (r:0 w:1)
+	fn feeder_heartbeat() -> Weight {
+		Weight::from_ref_time(1_156_031_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle ExtraBatchingApis (r:0 w:1)
+	fn set_extra_batching_apis(a: u32, ) -> Weight {
+		Weight::from_ref_time(1_207_413_000)
+			.saturating_add(Weight::from_ref_time(2_315_000).saturating_mul(a as u64))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle BatchingEncoding (r:0 w:1)
+	fn set_batching_api_encoding() -> Weight {
+		Weight::from_ref_time(1_187_560_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle CoinInfosMap (r:1 w:1)
+	// Storage: DiaOracle PriceSubmitters (r:0 w:1)
+	fn clear_all_prices(a: u32, ) -> Weight {
+		Weight::from_ref_time(1_034_200_000)
+			.saturating_add(Weight::from_ref_time(1_322_000).saturating_mul(a as u64))
+			.saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(a as u64)))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(a as u64)))
+	}
+
+	// Storage: DiaOracle CoinInfosMap (r:1 w:1)
+	// Storage: DiaOracle PriceSubmitters (r:0 w:1)
+	fn purge_blockchain(a: u32, ) -> Weight {
+		Weight::from_ref_time(1_051_700_000)
+			.saturating_add(Weight::from_ref_time(1_347_000).saturating_mul(a as u64))
+			.saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(a as u64)))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(a as u64)))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle DerivedFeeds (r:0 w:1)
+	fn set_derived_feeds(a: u32, ) -> Weight {
+		Weight::from_ref_time(1_196_824_000)
+			.saturating_add(Weight::from_ref_time(3_108_000).saturating_mul(a as u64))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle PendingCurrencies (r:1 w:1)
+	// Storage: DiaOracle PendingActivations (r:0 w:1)
+	fn cancel_currency_addition() -> Weight {
+		Weight::from_ref_time(1_093_517_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle SupportedCurrencies (r:1 w:1)
+	// Storage: DiaOracle PendingCurrencies (r:1 w:1)
+	// Storage: DiaOracle CurrencyDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DiaOracle BlockedCurrencies (r:0 w:1)
+	// Storage: DiaOracle PendingActivations (r:0 w:1)
+	fn block_currency() -> Weight {
+		Weight::from_ref_time(1_387_204_000)
+			.saturating_add(RocksDbWeight::get().reads(5))
+			.saturating_add(RocksDbWeight::get().writes(6))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle BlockedCurrencies (r:1 w:1)
+	fn unblock_currency() -> Weight {
+		Weight::from_ref_time(1_012_730_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle OcwSettings (r:0 w:1)
+	fn set_ocw_config() -> Weight {
+		Weight::from_ref_time(1_104_382_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+
+	// Storage: DiaOracle AuthorizedAccounts (r:1 w:0)
+	// Storage: DiaOracle MinChangeThresholds (r:0 w:1)
+	fn set_min_change_threshold() -> Weight {
+		Weight::from_ref_time(1_096_915_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
 }
@@ -0,0 +1,181 @@
+//! Forwards price updates of the DIA oracle to sibling parachains, turning the chain into a hub
+//! feed. Set `XcmPriceDistributor<XcmRouter, PriceTargets, SendWeight>` as the `OnPriceUpdate`
+//! of the oracle pallet, where `PriceTargets` lists the parachains and the assets each of them
+//! receives and `SendWeight` is the weight of sending one message through `XcmRouter`.
+//!
+//! Every target gets an unpaid `Transact` of `set_updated_coin_infos` on its own dia-oracle
+//! pallet, dispatched from the sovereign account of this chain. The target therefore has to
+//! allow unpaid execution from this chain and authorize its sovereign account as a feeder.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Encode;
+use dia_oracle::{CoinInfo, OnPriceUpdate, SET_UPDATED_COIN_INFOS_CALL_INDEX};
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::{marker::PhantomData, vec, vec::Vec};
+use xcm::v2::{Instruction::Transact, MultiLocation, OriginKind, SendXcm, Xcm};
+
+/// A parachain receiving prices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceTarget {
+	/// Location of the parachain, e.g. `(1, Parachain(id))` for a sibling
+	pub dest: MultiLocation,
+	/// Index of the dia-oracle pallet in the runtime of the parachain
+	pub pallet_index: u8,
+	/// `(blockchain, symbol)` of the assets forwarded to the parachain
+	pub assets: Vec<(Vec<u8>, Vec<u8>)>,
+	/// Weight the parachain may spend on the update
+	pub weight: u64,
+}
+
+pub struct XcmPriceDistributor<Router, Targets, SendWeight>(
+	PhantomData<(Router, Targets, SendWeight)>,
+);
+
+impl<Router, Targets, SendWeight> OnPriceUpdate
+	for XcmPriceDistributor<Router, Targets, SendWeight>
+where
+	Router: SendXcm,
+	Targets: Get<Vec<PriceTarget>>,
+	SendWeight: Get<Weight>,
+{
+	fn on_price_update(coin_infos: &[((Vec<u8>, Vec<u8>), CoinInfo)]) {
+		for target in Targets::get() {
+			let selected: Vec<_> = coin_infos
+				.iter()
+				.filter(|(asset, _)| target.assets.contains(asset))
+				.cloned()
+				.collect();
+			if selected.is_empty() {
+				continue
+			}
+
+			let message = price_update_message(&target, selected);
+			if let Err(e) = Router::send_xcm(target.dest.clone(), message) {
+				log::warn!("Failed to send prices to {:?}: {:?}", target.dest, e);
+			}
+		}
+	}
+
+	/// One message send per target receiving any of the coin infos
+	fn on_price_update_weight(coin_infos: &[((Vec<u8>, Vec<u8>), CoinInfo)]) -> Weight {
+		let messages = Targets::get()
+			.iter()
+			.filter(|target| coin_infos.iter().any(|(asset, _)| target.assets.contains(asset)))
+			.count();
+		SendWeight::get().saturating_mul(messages as u64)
+	}
+}
+
+/// Message calling `set_updated_coin_infos` with `coin_infos` on the target's oracle pallet
+pub fn price_update_message(
+	target: &PriceTarget,
+	coin_infos: Vec<((Vec<u8>, Vec<u8>), CoinInfo)>,
+) -> Xcm<()> {
+	let call = (target.pallet_index, SET_UPDATED_COIN_INFOS_CALL_INDEX, coin_infos).encode();
+	Xcm(vec![Transact {
+		origin_type: OriginKind::SovereignAccount,
+		require_weight_at_most: target.weight,
+		call: call.into(),
+	}])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::cell::RefCell;
+	use xcm::v2::{Junction::Parachain, Junctions::X1, SendResult};
+
+	thread_local! {
+		static SENT: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+	}
+
+	struct MockRouter;
+
+	impl SendXcm for MockRouter {
+		fn send_xcm(destination: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+			SENT.with(|sent| sent.borrow_mut().push((destination.into(), message)));
+			Ok(())
+		}
+	}
+
+	fn sibling(id: u32) -> MultiLocation {
+		MultiLocation { parents: 1, interior: X1(Parachain(id)) }
+	}
+
+	fn target(id: u32, assets: &[&str]) -> PriceTarget {
+		PriceTarget {
+			dest: sibling(id),
+			pallet_index: 53,
+			assets: assets
+				.iter()
+				.map(|symbol| (b"Bitcoin".to_vec(), symbol.as_bytes().to_vec()))
+				.collect(),
+			weight: 1_000_000_000,
+		}
+	}
+
+	struct Targets;
+
+	impl Get<Vec<PriceTarget>> for Targets {
+		fn get() -> Vec<PriceTarget> {
+			vec![target(2000, &["BTC"]), target(2001, &["ETH"]), target(2002, &[])]
+		}
+	}
+
+	fn coin_info(symbol: &[u8]) -> ((Vec<u8>, Vec<u8>), CoinInfo) {
+		let coin_info = CoinInfo {
+			symbol: symbol.to_vec(),
+			blockchain: b"Bitcoin".to_vec(),
+			price: 16_826_489_316_709_616_000,
+			..Default::default()
+		};
+		((coin_info.blockchain.clone(), coin_info.symbol.clone()), coin_info)
+	}
+
+	#[test]
+	fn price_update_message_should_transact_set_updated_coin_infos() {
+		let coin_infos = vec![coin_info(b"BTC")];
+
+		let message = price_update_message(&target(2000, &["BTC"]), coin_infos.clone());
+
+		// `set_updated_coin_infos` is call 4 of the oracle pallet, here at index 53
+		let mut call = vec![53, 4];
+		call.extend(coin_infos.encode());
+		assert_eq!(
+			message,
+			Xcm(vec![Transact {
+				origin_type: OriginKind::SovereignAccount,
+				require_weight_at_most: 1_000_000_000,
+				call: call.into(),
+			}])
+		);
+	}
+
+	struct SendWeight;
+
+	impl Get<Weight> for SendWeight {
+		fn get() -> Weight {
+			Weight::from_ref_time(1_000)
+		}
+	}
+
+	type Distributor = XcmPriceDistributor<MockRouter, Targets, SendWeight>;
+
+	#[test]
+	fn distributor_should_only_send_selected_assets() {
+		Distributor::on_price_update(&[coin_info(b"BTC"), coin_info(b"DOT")]);
+
+		let sent = SENT.with(|sent| sent.take());
+		let expected = price_update_message(&target(2000, &["BTC"]), vec![coin_info(b"BTC")]);
+		assert_eq!(sent, vec![(sibling(2000), expected)]);
+	}
+
+	#[test]
+	fn distributor_should_weigh_one_send_per_receiving_target() {
+		assert_eq!(Distributor::on_price_update_weight(&[coin_info(b"DOT")]), Weight::zero());
+		assert_eq!(
+			Distributor::on_price_update_weight(&[coin_info(b"BTC"), coin_info(b"ETH")]),
+			Weight::from_ref_time(2_000)
+		);
+	}
+}
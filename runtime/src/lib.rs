@@ -19,7 +19,9 @@ use sp_runtime::{
 	traits::{
 		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{
+		TransactionLongevity, TransactionPriority, TransactionSource, TransactionValidity,
+	},
 	ApplyExtrinsicResult, MultiSignature,
 };
 use sp_std::{
@@ -291,11 +293,65 @@ impl pallet_sudo::Config for Runtime {
 	type RuntimeCall = RuntimeCall;
 }
 
+parameter_types! {
+	pub const MaxBatchLogEntries: u32 = 100;
+	pub const HttpRequestTimeout: u64 = 5_000;
+	pub const MaxCurrenciesPerRequest: u32 = 100;
+	pub const MaxResponseSize: u32 = 1 << 20;
+	pub const MaxHttpRetries: u32 = 2;
+	pub const HttpRetryBackoff: u64 = 500;
+	pub const MaxApiUrlLength: u32 = 256;
+	pub const AllowZeroPrices: bool = false;
+	pub const MaxPriceAge: u64 = 24 * 60 * 60;
+	pub const MaxFutureDrift: u64 = 30;
+	pub const OracleUpdatePriority: TransactionPriority = TransactionPriority::max_value() / 2;
+	pub const OracleUpdateLongevity: TransactionLongevity = 10;
+	pub const OracleHeartbeatInterval: BlockNumber = 10 * MINUTES;
+	pub const MaxExtraBatchingApis: u32 = 4;
+	pub const MaxDerivedFeeds: u32 = 32;
+	pub const CurrencyDeposit: Balance = 1_000 * EXISTENTIAL_DEPOSIT;
+	pub const CurrencyActivationDelay: BlockNumber = 0;
+	pub const DuplicateBatchWindow: BlockNumber = 10;
+	pub const MaxSourceDeviation: Permill = Permill::from_percent(2);
+	pub const OraclePriceDecimals: u8 = 12;
+}
+
 impl dia_oracle::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type RuntimeCall = RuntimeCall;
+	#[cfg(not(feature = "no-ocw"))]
 	type AuthorityId = dia_oracle::crypto::DiaAuthId;
 	type WeightInfo = ();
+	type MaxBatchLogEntries = MaxBatchLogEntries;
+	type FeederMembership = dia_oracle::AuthorizedAccountsMembership<Runtime>;
+	type SubmitterBalance = dia_oracle::CurrencyBalance<Balances>;
+	#[cfg(not(feature = "no-ocw"))]
+	type HttpRequestTimeout = HttpRequestTimeout;
+	#[cfg(not(feature = "no-ocw"))]
+	type MaxCurrenciesPerRequest = MaxCurrenciesPerRequest;
+	#[cfg(not(feature = "no-ocw"))]
+	type MaxResponseSize = MaxResponseSize;
+	#[cfg(not(feature = "no-ocw"))]
+	type MaxHttpRetries = MaxHttpRetries;
+	#[cfg(not(feature = "no-ocw"))]
+	type HttpRetryBackoff = HttpRetryBackoff;
+	type MaxApiUrlLength = MaxApiUrlLength;
+	type AllowZeroPrices = AllowZeroPrices;
+	type UnixTime = Timestamp;
+	type MaxPriceAge = MaxPriceAge;
+	type MaxFutureDrift = MaxFutureDrift;
+	type UpdatePriority = OracleUpdatePriority;
+	type UpdateLongevity = OracleUpdateLongevity;
+	type HeartbeatInterval = OracleHeartbeatInterval;
+	type MaxExtraBatchingApis = MaxExtraBatchingApis;
+	type MaxSourceDeviation = MaxSourceDeviation;
+	type PriceDecimals = OraclePriceDecimals;
+	type OnPriceUpdate = ();
+	type MaxDerivedFeeds = MaxDerivedFeeds;
+	type Currency = Balances;
+	type CurrencyDeposit = CurrencyDeposit;
+	type CurrencyActivationDelay = CurrencyActivationDelay;
+	type DuplicateBatchWindow = DuplicateBatchWindow;
 }
 
 impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
@@ -323,6 +379,7 @@ where
 			frame_system::CheckNonce::<Runtime>::from(index),
 			frame_system::CheckWeight::<Runtime>::new(),
 			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+			dia_oracle::PrioritizeOracleUpdates::<Runtime>::new(),
 		);
 
 		let raw_payload = SignedPayload::new(call, extra).ok()?;
@@ -381,6 +438,7 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	dia_oracle::PrioritizeOracleUpdates<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -395,6 +453,11 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	(
+		dia_oracle::migrations::v1::MigrateToDoubleMap<Runtime>,
+		dia_oracle::migrations::v2::AddDailyFields<Runtime>,
+		dia_oracle::migrations::v3::AddMarketFields<Runtime>,
+	),
 >;
 
 impl_runtime_apis! {
@@ -528,7 +591,7 @@ impl_runtime_apis! {
 		}
 	}
 
-		impl dia_oracle_runtime_api::DiaOracleApi<Block> for Runtime{
+		impl dia_oracle_runtime_api::DiaOracleApi<Block, AccountId> for Runtime{
 			fn get_value(blockchain: frame_support::sp_std::vec::Vec<u8>, symbol: frame_support::sp_std::vec::Vec<u8>)-> Result<dia_oracle_runtime_api::PriceInfo, sp_runtime::DispatchError>{
 				DiaOracleModule::get_value(blockchain, symbol)
 			}
@@ -536,6 +599,54 @@ impl_runtime_apis! {
 			fn get_coin_info(blockchain: frame_support::sp_std::vec::Vec<u8>, symbol: frame_support::sp_std::vec::Vec<u8>)-> Result<dia_oracle_runtime_api::CoinInfo,sp_runtime::DispatchError>{
 				DiaOracleModule::get_coin_info(blockchain, symbol)
 			}
+
+			fn get_supply(blockchain: frame_support::sp_std::vec::Vec<u8>, symbol: frame_support::sp_std::vec::Vec<u8>)-> Result<u128, sp_runtime::DispatchError>{
+				DiaOracleModule::get_supply(blockchain, symbol)
+			}
+
+			fn get_asset_overview(blockchain: frame_support::sp_std::vec::Vec<u8>, symbol: frame_support::sp_std::vec::Vec<u8>)-> dia_oracle_runtime_api::AssetOverview{
+				DiaOracleModule::asset_overview(blockchain, symbol)
+			}
+
+			fn get_coin_infos(assets: frame_support::sp_std::vec::Vec<(frame_support::sp_std::vec::Vec<u8>, frame_support::sp_std::vec::Vec<u8>)>)-> frame_support::sp_std::vec::Vec<Result<dia_oracle_runtime_api::CoinInfo, sp_runtime::DispatchError>>{
+				DiaOracleModule::get_coin_infos(assets)
+			}
+
+			fn list_supported_currencies()-> frame_support::sp_std::vec::Vec<(frame_support::sp_std::vec::Vec<u8>, frame_support::sp_std::vec::Vec<u8>)>{
+				DiaOracleModule::list_supported_currencies()
+			}
+
+			fn list_authorized_accounts()-> frame_support::sp_std::vec::Vec<AccountId>{
+				DiaOracleModule::list_authorized_accounts()
+			}
+
+			fn get_last_updated(blockchain: frame_support::sp_std::vec::Vec<u8>, symbol: frame_support::sp_std::vec::Vec<u8>)-> Option<u64>{
+				DiaOracleModule::get_last_updated(blockchain, symbol)
+			}
+
+			fn is_stale(blockchain: frame_support::sp_std::vec::Vec<u8>, symbol: frame_support::sp_std::vec::Vec<u8>)-> bool{
+				DiaOracleModule::is_stale(blockchain, symbol)
+			}
+
+			fn convert(from: (frame_support::sp_std::vec::Vec<u8>, frame_support::sp_std::vec::Vec<u8>), to: (frame_support::sp_std::vec::Vec<u8>, frame_support::sp_std::vec::Vec<u8>), amount: u128)-> Result<u128, sp_runtime::DispatchError>{
+				DiaOracleModule::convert(from, to, amount)
+			}
+
+			fn price_decimals()-> u8{
+				DiaOracleModule::price_decimals()
+			}
+
+			fn get_coins_by_blockchain(blockchain: frame_support::sp_std::vec::Vec<u8>)-> frame_support::sp_std::vec::Vec<dia_oracle_runtime_api::CoinInfo>{
+				DiaOracleModule::get_coins_by_blockchain(blockchain)
+			}
+
+			fn get_feeder_stats(account: AccountId)-> dia_oracle_runtime_api::FeederStats<BlockNumber>{
+				DiaOracleModule::get_feeder_stats(account)
+			}
+
+			fn get_price_submitter(blockchain: frame_support::sp_std::vec::Vec<u8>, symbol: frame_support::sp_std::vec::Vec<u8>)-> Option<(AccountId, BlockNumber)>{
+				DiaOracleModule::get_price_submitter(blockchain, symbol)
+			}
 		}
 
 	#[cfg(feature = "runtime-benchmarks")]